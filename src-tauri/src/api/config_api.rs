@@ -1,10 +1,14 @@
 use crate::domain::config::FrpcConfig;
+use crate::domain::import::ImportReport;
+use crate::domain::validation::{self, ValidationReport};
+use crate::services::auth_service::{self, OidcProbe};
+use crate::services::verify_service::{self, VerifyResult};
 use crate::{services::config_service as svc, state::AppState};
 use tauri::{AppHandle, State};
 
 #[tauri::command]
 pub fn load_config(state: State<AppState>) -> Result<FrpcConfig, String> {
-    Ok(state.read().config.clone())
+    Ok(state.read().config().clone())
 }
 
 #[tauri::command]
@@ -13,11 +17,59 @@ pub fn save_server(
     state: State<AppState>,
     partial: FrpcConfig,
 ) -> Result<(), String> {
-    svc::save_server_config(&app, &state, partial).expect("TODO: panic message");
-    Ok(())
+    let id = state.read().active_profile.clone();
+    svc::save_server_config(&app, &state, &id, partial).map_err(Into::into)
+}
+
+/// 校验整个配置，返回全部错误与警告；不传 profile 时校验当前激活的
+#[tauri::command]
+pub fn validate_config(
+    state: State<AppState>,
+    profile_id: Option<String>,
+) -> Result<ValidationReport, String> {
+    let g = state.read();
+    let cfg = match profile_id {
+        Some(id) => {
+            &g.profile(&id)
+                .ok_or_else(|| format!("profile not found: {id}"))?
+                .config
+        }
+        None => g.config(),
+    };
+    Ok(validation::validate_config(cfg))
+}
+
+/// 交给当前激活的 frpc 版本执行 verify
+#[tauri::command]
+pub async fn verify_config(
+    state: State<'_, AppState>,
+    profile_id: Option<String>,
+) -> Result<VerifyResult, String> {
+    let id = profile_id.unwrap_or_else(|| state.read().active_profile.clone());
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || verify_service::verify_config(&state, &id))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(Into::into)
 }
 
 #[tauri::command]
 pub fn save_now(app: AppHandle, state: State<AppState>) -> Result<(), String> {
     svc::save_now(&app, &state).map_err(Into::into)
 }
+
+#[tauri::command]
+pub fn import_config(
+    app: AppHandle,
+    state: State<AppState>,
+    path: String,
+    apply: bool,
+) -> Result<ImportReport, String> {
+    svc::import_config(&app, &state, &path, apply).map_err(Into::into)
+}
+
+#[tauri::command]
+pub async fn probe_oidc(state: State<'_, AppState>) -> Result<OidcProbe, String> {
+    let oidc = state.read().config().auth.oidc.clone();
+    auth_service::probe_oidc(&oidc).await.map_err(Into::into)
+}
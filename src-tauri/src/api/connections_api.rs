@@ -0,0 +1,53 @@
+use crate::services::local_proxy::{self, ConnInfo};
+use crate::state::{AppState, FrpcProcState};
+use tauri::State;
+
+// 未指定 profile 时作用于当前激活的 profile
+fn resolve_profile(state: &AppState, profile_id: Option<String>) -> String {
+    profile_id.unwrap_or_else(|| state.read().active_profile.clone())
+}
+
+/// 不传 proxy_id 时列出该实例全部代理的连接
+#[tauri::command]
+pub fn list_connections(
+    state: State<AppState>,
+    proc_state: State<FrpcProcState>,
+    profile_id: Option<String>,
+    proxy_id: Option<String>,
+) -> Result<Vec<ConnInfo>, String> {
+    let id = resolve_profile(&state, profile_id);
+    Ok(proc_state
+        .get(&id)
+        .map(|inst| local_proxy::list_connections(&inst, proxy_id.as_deref()))
+        .unwrap_or_default())
+}
+
+/// 断开一条连接，返回是否找到该连接
+#[tauri::command]
+pub fn kill_connection(
+    state: State<AppState>,
+    proc_state: State<FrpcProcState>,
+    profile_id: Option<String>,
+    proxy_id: String,
+    conn_id: u64,
+) -> Result<bool, String> {
+    let id = resolve_profile(&state, profile_id);
+    Ok(proc_state
+        .get(&id)
+        .is_some_and(|inst| local_proxy::kill_connections(&inst, &proxy_id, Some(conn_id)) > 0))
+}
+
+/// 断开某个代理的全部连接，返回断开的数量
+#[tauri::command]
+pub fn kill_proxy_connections(
+    state: State<AppState>,
+    proc_state: State<FrpcProcState>,
+    profile_id: Option<String>,
+    proxy_id: String,
+) -> Result<usize, String> {
+    let id = resolve_profile(&state, profile_id);
+    Ok(proc_state
+        .get(&id)
+        .map(|inst| local_proxy::kill_connections(&inst, &proxy_id, None))
+        .unwrap_or(0))
+}
@@ -0,0 +1,49 @@
+use crate::domain::history::{ConfigChange, ConfigSnapshot, SnapshotSummary};
+use crate::services::{history_service as svc, runner};
+use crate::state::{AppState, FrpcProcState};
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+pub fn list_snapshots(
+    app: AppHandle,
+    state: State<AppState>,
+    profile_id: Option<String>,
+) -> Result<Vec<SnapshotSummary>, String> {
+    let id = profile_id.unwrap_or_else(|| state.read().active_profile.clone());
+    svc::list(&app, &id).map_err(Into::into)
+}
+
+#[tauri::command]
+pub fn get_snapshot(
+    app: AppHandle,
+    state: State<AppState>,
+    id: String,
+) -> Result<ConfigSnapshot, String> {
+    svc::get(&app, &state, &id).map_err(Into::into)
+}
+
+/// 不传 to 时与当前配置比较
+#[tauri::command]
+pub fn diff_snapshots(
+    app: AppHandle,
+    state: State<AppState>,
+    from: String,
+    to: Option<String>,
+) -> Result<Vec<ConfigChange>, String> {
+    svc::diff(&app, &state, &from, to.as_deref()).map_err(Into::into)
+}
+
+// 回滚后若该 profile 的 frpc 正在运行，尝试热重载
+#[tauri::command]
+pub async fn rollback_snapshot(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    proc_state: State<'_, FrpcProcState>,
+    id: String,
+) -> Result<(), String> {
+    let profile_id = svc::rollback(&app, &state, &id)?;
+    runner::reload(&app, &state, &proc_state, &profile_id)
+        .await
+        .map(|_| ())
+        .map_err(|e| format!("rolled back, but reload failed: {e}"))
+}
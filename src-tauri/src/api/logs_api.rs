@@ -0,0 +1,49 @@
+use crate::domain::frpc_log::LogLevel;
+use crate::services::log_store::{self, LogPage, LogSession};
+use crate::state::AppState;
+use tauri::{AppHandle, State};
+
+const DEFAULT_PAGE_SIZE: usize = 500;
+
+// profile id 会拼进日志目录，只接受已存在的 profile
+fn resolve_profile(state: &AppState, profile_id: Option<String>) -> Result<String, String> {
+    let g = state.read();
+    let id = profile_id.unwrap_or_else(|| g.active_profile.clone());
+    match g.profile(&id) {
+        Some(_) => Ok(id),
+        None => Err(format!("profile not found: {id}")),
+    }
+}
+
+#[tauri::command]
+pub fn list_log_sessions(
+    app: AppHandle,
+    state: State<AppState>,
+    profile_id: Option<String>,
+) -> Result<Vec<LogSession>, String> {
+    let id = resolve_profile(&state, profile_id)?;
+    log_store::list_sessions(&app, &id).map_err(Into::into)
+}
+
+/// level 为最低级别，例如传 warn 只返回 warn 和 error
+#[tauri::command]
+pub fn read_log(
+    app: AppHandle,
+    state: State<AppState>,
+    profile_id: Option<String>,
+    session: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    level: Option<LogLevel>,
+) -> Result<LogPage, String> {
+    let id = resolve_profile(&state, profile_id)?;
+    log_store::read_session(
+        &app,
+        &id,
+        &session,
+        offset.unwrap_or(0),
+        limit.unwrap_or(DEFAULT_PAGE_SIZE),
+        level,
+    )
+    .map_err(Into::into)
+}
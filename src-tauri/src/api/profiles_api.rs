@@ -0,0 +1,47 @@
+use crate::domain::profile::{Profile, ProfileSummary};
+use crate::{services::profile_service as svc, state::AppState};
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+pub fn list_profiles(state: State<AppState>) -> Result<Vec<ProfileSummary>, String> {
+    Ok(svc::list(&state))
+}
+
+#[tauri::command]
+pub fn create_profile(
+    app: AppHandle,
+    state: State<AppState>,
+    name: String,
+) -> Result<Profile, String> {
+    svc::create(&app, &state, &name).map_err(Into::into)
+}
+
+#[tauri::command]
+pub fn clone_profile(
+    app: AppHandle,
+    state: State<AppState>,
+    id: String,
+    name: String,
+) -> Result<Profile, String> {
+    svc::clone(&app, &state, &id, &name).map_err(Into::into)
+}
+
+#[tauri::command]
+pub fn rename_profile(
+    app: AppHandle,
+    state: State<AppState>,
+    id: String,
+    name: String,
+) -> Result<(), String> {
+    svc::rename(&app, &state, &id, &name).map_err(Into::into)
+}
+
+#[tauri::command]
+pub fn delete_profile(app: AppHandle, state: State<AppState>, id: String) -> Result<(), String> {
+    svc::delete(&app, &state, &id).map_err(Into::into)
+}
+
+#[tauri::command]
+pub fn activate_profile(app: AppHandle, state: State<AppState>, id: String) -> Result<(), String> {
+    svc::activate(&app, &state, &id).map_err(Into::into)
+}
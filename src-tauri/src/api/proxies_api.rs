@@ -1,47 +1,78 @@
 use crate::domain::proxy::Proxy;
-use crate::{services::config_service as svc, state::AppState};
+use crate::domain::traffic::RateLimit;
+use crate::services::{local_proxy, proxy_service, runner};
+use crate::state::{AppState, FrpcProcState};
 use tauri::{AppHandle, State};
 
 #[tauri::command]
 pub fn load_proxies(state: State<AppState>) -> Result<Vec<Proxy>, String> {
-    Ok(state.read().config.proxies.clone())
+    Ok(state.read().config().proxies.clone())
 }
 
 #[tauri::command]
 pub fn get_proxy(state: State<AppState>, name: String) -> Result<Option<Proxy>, String> {
     Ok(state
         .read()
-        .config
+        .config()
         .proxies
         .iter()
         .find(|p| p.name == name)
         .cloned())
 }
 
-#[tauri::command]
-pub fn save_proxy(app: AppHandle, state: State<AppState>, proxy: Proxy) -> Result<(), String> {
-    {
-        let mut g = state.write();
-        let list = &mut g.config.proxies;
-        if let Some(idx) = list.iter().position(|p| p.id == proxy.id) {
-            list[idx] = proxy;
-        } else {
-            list.push(proxy);
-        }
-    }
-    svc::save_now(&app, &state)?;
-    Ok(())
+// 保存后若当前 profile 的 frpc 正在运行，尝试热重载
+async fn reload_active(
+    app: &AppHandle,
+    state: &AppState,
+    proc_state: &FrpcProcState,
+) -> Result<(), String> {
+    let id = state.read().active_profile.clone();
+    runner::reload(app, state, proc_state, &id)
+        .await
+        .map(|_| ())
+        .map_err(|e| format!("saved, but reload failed: {e}"))
 }
 
 #[tauri::command]
-pub fn remove_proxy(app: AppHandle, state: State<AppState>, id: String) -> Result<bool, String> {
-    let removed = {
-        let mut g = state.write();
-        let before = g.config.proxies.len();
-        g.config.proxies.retain(|p| !(p.id == id));
-        g.config.proxies.len() != before
-    };
+pub async fn save_proxy(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    proc_state: State<'_, FrpcProcState>,
+    proxy: Proxy,
+) -> Result<(), String> {
+    let id = state.read().active_profile.clone();
+    proxy_service::save(&app, &state, &id, proxy)?;
+    reload_active(&app, &state, &proc_state).await
+}
 
-    svc::save_now(&app, &state)?;
+#[tauri::command]
+pub async fn remove_proxy(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    proc_state: State<'_, FrpcProcState>,
+    id: String,
+) -> Result<bool, String> {
+    let profile_id = state.read().active_profile.clone();
+    let removed = proxy_service::remove(&app, &state, &profile_id, &id)?;
+    if removed {
+        reload_active(&app, &state, &proc_state).await?;
+    }
     Ok(removed)
 }
+
+/// 保存限速并立即作用于正在运行的 shim，不重载 frpc；返回是否已生效到运行中的代理
+#[tauri::command]
+pub fn set_proxy_rate_limit(
+    app: AppHandle,
+    state: State<AppState>,
+    proc_state: State<FrpcProcState>,
+    profile_id: Option<String>,
+    id: String,
+    limit: RateLimit,
+) -> Result<bool, String> {
+    let profile_id = profile_id.unwrap_or_else(|| state.read().active_profile.clone());
+    let proxy = proxy_service::set_rate_limit(&app, &state, &profile_id, &id, limit)?;
+    Ok(proc_state
+        .get(&profile_id)
+        .is_some_and(|inst| local_proxy::set_rate_limit(&inst, &proxy.id, &proxy.rate_limit)))
+}
@@ -1,21 +1,59 @@
+use crate::domain::proxy_status::ProxyStatus;
+use crate::services::runner::{FrpcStatus, InstanceStatus};
 use crate::state::{AppState, FrpcProcState};
 use tauri::{AppHandle, State};
 
+// 未指定 profile 时作用于当前激活的 profile
+fn resolve_profile(state: &AppState, profile_id: Option<String>) -> String {
+    profile_id.unwrap_or_else(|| state.read().active_profile.clone())
+}
+
 #[tauri::command]
 pub async fn start_frpc(
     app: AppHandle,
     frpc_proc_state: State<'_, FrpcProcState>,
     state: State<'_, AppState>,
+    profile_id: Option<String>,
 ) -> Result<u32, String> {
-    crate::services::runner::start(&app, &state, &frpc_proc_state).await
+    let id = resolve_profile(&state, profile_id);
+    crate::services::runner::start(&app, &state, &frpc_proc_state, &id).await
+}
+
+#[tauri::command]
+pub async fn stop_frpc(
+    app: AppHandle,
+    proc_state: State<'_, FrpcProcState>,
+    state: State<'_, AppState>,
+    profile_id: Option<String>,
+) -> Result<(), String> {
+    let id = resolve_profile(&state, profile_id);
+    crate::services::runner::stop(&app, &state, &proc_state, &id).await
 }
 
 #[tauri::command]
-pub async fn stop_frpc(app: AppHandle, state: State<'_, FrpcProcState>) -> Result<(), String> {
-    crate::services::runner::stop(&app, &state)
+pub async fn frpc_status(
+    proc_state: State<'_, FrpcProcState>,
+    state: State<'_, AppState>,
+    profile_id: Option<String>,
+) -> Result<FrpcStatus, String> {
+    let id = resolve_profile(&state, profile_id);
+    Ok(crate::services::runner::status(&proc_state, &state, &id))
 }
 
 #[tauri::command]
-pub async fn frpc_status(state: State<'_, FrpcProcState>) -> Result<bool, String> {
-    crate::services::runner::is_running(&state)
-}
\ No newline at end of file
+pub async fn list_instances(
+    proc_state: State<'_, FrpcProcState>,
+    state: State<'_, AppState>,
+) -> Result<Vec<InstanceStatus>, String> {
+    Ok(crate::services::runner::list_instances(&state, &proc_state))
+}
+
+#[tauri::command]
+pub async fn proxy_status(
+    proc_state: State<'_, FrpcProcState>,
+    state: State<'_, AppState>,
+    profile_id: Option<String>,
+) -> Result<Vec<ProxyStatus>, String> {
+    let id = resolve_profile(&state, profile_id);
+    Ok(crate::services::runner::proxy_status(&proc_state, &id))
+}
@@ -0,0 +1,61 @@
+use crate::domain::traffic::{ExportFormat, ProxyUsage, QuotaStatus, TrafficPoint, TrafficQuery};
+use crate::services::{local_proxy, quota_service, traffic_service as svc};
+use crate::state::{AppState, FrpcProcState};
+use tauri::{AppHandle, State};
+
+/// 时间序列，用于流量图表；query.profileId 为空时查当前激活的 profile
+#[tauri::command]
+pub fn query_traffic(
+    app: AppHandle,
+    state: State<AppState>,
+    query: TrafficQuery,
+) -> Result<Vec<TrafficPoint>, String> {
+    svc::query(&app, &state, &query).map_err(Into::into)
+}
+
+/// 各代理的用量合计，不传 from 时从本月 1 日开始
+#[tauri::command]
+pub fn traffic_usage(
+    app: AppHandle,
+    state: State<AppState>,
+    profile_id: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<Vec<ProxyUsage>, String> {
+    svc::usage(
+        &app,
+        &state,
+        profile_id.as_deref(),
+        from.as_deref(),
+        to.as_deref(),
+    )
+    .map_err(Into::into)
+}
+
+/// 返回导出的文本，由前端保存
+#[tauri::command]
+pub fn export_traffic(
+    app: AppHandle,
+    state: State<AppState>,
+    query: TrafficQuery,
+    format: ExportFormat,
+) -> Result<String, String> {
+    svc::export(&app, &state, &query, format).map_err(Into::into)
+}
+
+/// 设置了配额的代理在当前周期内的用量
+#[tauri::command]
+pub fn quota_status(
+    app: AppHandle,
+    state: State<AppState>,
+    proc_state: State<FrpcProcState>,
+    profile_id: Option<String>,
+) -> Result<Vec<QuotaStatus>, String> {
+    let id = profile_id.unwrap_or_else(|| state.read().active_profile.clone());
+    let pending = proc_state
+        .get(&id)
+        .map(|inst| local_proxy::unsaved_usage(&inst))
+        .unwrap_or_default();
+    let db = svc::open(&app)?;
+    quota_service::statuses(&app, &db, &id, &pending).map_err(Into::into)
+}
@@ -0,0 +1,35 @@
+use crate::services::vault_service::{self as svc, VaultStatus};
+use crate::state::AppState;
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+pub fn vault_status(state: State<AppState>) -> Result<VaultStatus, String> {
+    Ok(svc::status(&state))
+}
+
+#[tauri::command]
+pub fn enable_vault(
+    app: AppHandle,
+    state: State<AppState>,
+    passphrase: String,
+) -> Result<(), String> {
+    svc::enable(&app, &state, &passphrase).map_err(Into::into)
+}
+
+#[tauri::command]
+pub fn unlock_vault(
+    app: AppHandle,
+    state: State<AppState>,
+    passphrase: String,
+) -> Result<(), String> {
+    svc::unlock(&app, &state, &passphrase).map_err(Into::into)
+}
+
+#[tauri::command]
+pub fn disable_vault(
+    app: AppHandle,
+    state: State<AppState>,
+    passphrase: String,
+) -> Result<(), String> {
+    svc::disable(&app, &state, &passphrase).map_err(Into::into)
+}
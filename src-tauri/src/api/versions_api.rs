@@ -36,7 +36,9 @@ pub async fn deactivate_version(
     proc_state: State<'_, FrpcProcState>,
     name: String,
 ) -> Result<(), String> {
-    version_service::deactivate(&app, &state, &proc_state, &name).map_err(Into::into)
+    version_service::deactivate(&app, &state, &proc_state, &name)
+        .await
+        .map_err(Into::into)
 }
 
 #[tauri::command]
@@ -46,7 +48,9 @@ pub async fn delete_version(
     proc_state: State<'_, FrpcProcState>,
     name: String,
 ) -> Result<(), String> {
-    version_service::delete(&app, &state, &proc_state, &name).map_err(Into::into)
+    version_service::delete(&app, &state, &proc_state, &name)
+        .await
+        .map_err(Into::into)
 }
 
 #[tauri::command]
@@ -0,0 +1,53 @@
+use crate::domain::validation::validate_config;
+use crate::domain::visitor::Visitor;
+use crate::{services::config_service as svc, state::AppState};
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+pub fn load_visitors(state: State<AppState>) -> Result<Vec<Visitor>, String> {
+    Ok(state.read().config().visitors.clone())
+}
+
+#[tauri::command]
+pub fn save_visitor(
+    app: AppHandle,
+    state: State<AppState>,
+    visitor: Visitor,
+) -> Result<(), String> {
+    let name = {
+        let mut g = state.write();
+        let mut candidate = g.config().clone();
+        let id = visitor.id.clone();
+        let name = visitor.name.clone();
+        let list = &mut candidate.visitors;
+        if let Some(idx) = list.iter().position(|v| v.id == visitor.id) {
+            list[idx] = visitor;
+        } else {
+            list.push(visitor);
+        }
+        let report = validate_config(&candidate).only(Some(&id));
+        if let Some(msg) = report.error_summary() {
+            return Err(msg);
+        }
+        *g.config_mut() = candidate;
+        name
+    };
+    svc::save_with_reason(&app, &state, &format!("save visitor {name}"))?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn remove_visitor(app: AppHandle, state: State<AppState>, id: String) -> Result<bool, String> {
+    let removed = {
+        let mut g = state.write();
+        let list = &mut g.config_mut().visitors;
+        let idx = list.iter().position(|v| v.id == id);
+        idx.map(|i| list.remove(i).name.clone())
+    };
+
+    let Some(name) = removed else {
+        return Ok(false);
+    };
+    svc::save_with_reason(&app, &state, &format!("remove visitor {name}"))?;
+    Ok(true)
+}
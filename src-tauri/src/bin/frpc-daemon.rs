@@ -0,0 +1,3 @@
+fn main() {
+    frpc_lib::daemon::run()
+}
@@ -0,0 +1,758 @@
+//! 面向脚本的命令行工具，与桌面端共用 frpc.json 与 services。
+//! 图形界面运行时在内存里持有自己的一份配置，此时 CLI 拒绝修改 frpc.json，以免改动被覆盖。
+
+use crate::daemon::{self, DaemonHost, PASSPHRASE_ENV};
+use crate::domain::proxy::Proxy;
+use crate::domain::traffic::{self, TrafficQuery, TrafficSpan};
+use crate::domain::version::FrpVersion;
+use crate::host::Host;
+use crate::infra::config_format::ConfigFormat;
+use crate::infra::frpc_admin::AdminEndpoint;
+use crate::infra::gui_lock;
+use crate::services::{
+    config_service, proxy_service, quota_service, traffic_service, version_service,
+};
+use crate::state::AppState;
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+type CliResult<T = ()> = std::result::Result<T, String>;
+
+// 等待守护进程退出的上限，需长于 frpc 的停止宽限期
+const STOP_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Parser)]
+#[command(
+    name = "frpc-cli",
+    version,
+    about = "Manage frpc desktop profiles from scripts"
+)]
+struct Cli {
+    /// Directory for generated frpc toml files
+    #[arg(long, global = true)]
+    config_dir: Option<PathBuf>,
+    /// Directory containing frpc.json, downloads and logs
+    #[arg(long, global = true)]
+    data_dir: Option<PathBuf>,
+    /// Profile id or name; defaults to the active profile
+    #[arg(long, short, global = true)]
+    profile: Option<String>,
+    /// Print JSON instead of text
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Manage proxies
+    #[command(subcommand)]
+    Proxy(ProxyCommand),
+    /// Change server settings
+    #[command(subcommand)]
+    Server(ServerCommand),
+    /// List, download and activate frpc releases
+    #[command(subcommand)]
+    Version(VersionCommand),
+    /// Run frpc in the foreground until SIGTERM / Ctrl+C, same as frpc-daemon
+    Start {
+        /// More profiles to run together with --profile
+        profiles: Vec<String>,
+    },
+    /// Stop the running daemon
+    Stop,
+    /// Show daemon and proxy status
+    Status,
+    /// Print the frpc config of a profile
+    Export {
+        #[arg(long, value_enum, default_value_t = ExportFormat::Toml)]
+        format: ExportFormat,
+        /// Write to a file instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Show recorded traffic of the profile
+    #[command(subcommand)]
+    Traffic(TrafficCommand),
+}
+
+impl Command {
+    // 会写 frpc.json 的命令
+    fn writes_store(&self) -> bool {
+        match self {
+            Command::Proxy(cmd) => !matches!(cmd, ProxyCommand::List),
+            Command::Server(_) => true,
+            Command::Version(cmd) => !matches!(cmd, VersionCommand::List),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum TrafficCommand {
+    /// Bytes per proxy, largest first; defaults to the current month
+    Usage {
+        /// Start time, RFC 3339 or YYYY-MM-DD
+        #[arg(long)]
+        from: Option<String>,
+        /// End time, RFC 3339 or YYYY-MM-DD; defaults to now
+        #[arg(long)]
+        to: Option<String>,
+    },
+    /// Show quota usage of proxies that have a quota
+    Quota,
+    /// Export traffic buckets as CSV or JSON
+    Export {
+        #[arg(long, value_enum, default_value_t = SpanArg::Day)]
+        span: SpanArg,
+        /// Proxy id or name; defaults to all proxies
+        #[arg(long)]
+        proxy: Option<String>,
+        #[arg(long)]
+        from: Option<String>,
+        #[arg(long)]
+        to: Option<String>,
+        #[arg(long, value_enum, default_value_t = TrafficFormat::Csv)]
+        format: TrafficFormat,
+        /// Write to a file instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ProxyCommand {
+    /// List proxies of the profile
+    List,
+    /// Add a proxy, e.g. `proxy add tcp ssh localIP=127.0.0.1 localPort=22 remotePort=6000`
+    Add {
+        #[arg(value_parser = ["tcp", "udp", "http", "https", "stcp", "sudp", "xtcp"])]
+        kind: String,
+        name: String,
+        /// KEY=VALUE pairs using the stored field names; nested fields use dots
+        fields: Vec<String>,
+        /// Add the proxy disabled
+        #[arg(long)]
+        disabled: bool,
+    },
+    /// Change fields of a proxy, e.g. `proxy set ssh remotePort=6001`
+    Set {
+        /// Proxy id or name
+        proxy: String,
+        #[arg(required = true)]
+        fields: Vec<String>,
+    },
+    /// Remove a proxy
+    Rm {
+        /// Proxy id or name
+        proxy: String,
+    },
+    /// Enable a proxy
+    Enable {
+        /// Proxy id or name
+        proxy: String,
+    },
+    /// Disable a proxy
+    Disable {
+        /// Proxy id or name
+        proxy: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ServerCommand {
+    /// Change server settings, e.g. `server set serverAddr=frp.example.com switch.auth=true auth.token=xxx`
+    Set {
+        #[arg(required = true)]
+        fields: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum VersionCommand {
+    /// List frpc releases for this platform
+    List,
+    /// Download a release by version (e.g. v0.61.0) or asset name
+    Download { version: String },
+    /// Activate a downloaded release
+    Activate { version: String },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl From<ExportFormat> for ConfigFormat {
+    fn from(f: ExportFormat) -> Self {
+        match f {
+            ExportFormat::Toml => ConfigFormat::Toml,
+            ExportFormat::Yaml => ConfigFormat::Yaml,
+            ExportFormat::Json => ConfigFormat::Json,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SpanArg {
+    Minute,
+    Hour,
+    Day,
+}
+
+impl From<SpanArg> for TrafficSpan {
+    fn from(s: SpanArg) -> Self {
+        match s {
+            SpanArg::Minute => TrafficSpan::Minute,
+            SpanArg::Hour => TrafficSpan::Hour,
+            SpanArg::Day => TrafficSpan::Day,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum TrafficFormat {
+    Csv,
+    Json,
+}
+
+impl From<TrafficFormat> for traffic::ExportFormat {
+    fn from(f: TrafficFormat) -> Self {
+        match f {
+            TrafficFormat::Csv => traffic::ExportFormat::Csv,
+            TrafficFormat::Json => traffic::ExportFormat::Json,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StatusReport {
+    daemon: Option<daemon::DaemonInfo>,
+    profiles: Vec<ProfileStatus>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProfileStatus {
+    id: String,
+    name: String,
+    running: bool,
+    proxies: Vec<ProxyState>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProxyState {
+    name: String,
+    kind: &'static str,
+    enabled: bool,
+    // 未运行或未开启 webServer 时拿不到
+    phase: Option<String>,
+    error: String,
+}
+
+fn print_json<T: Serialize>(value: &T) -> CliResult {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(value).map_err(|e| e.to_string())?
+    );
+    Ok(())
+}
+
+// 按 id 或名称匹配，未指定时用当前激活的 profile
+fn resolve_profile(state: &AppState, selector: Option<&str>) -> CliResult<String> {
+    let g = state.read();
+    let Some(s) = selector else {
+        return Ok(g.active_profile.clone());
+    };
+    g.profiles
+        .iter()
+        .find(|p| p.id == s || p.name == s)
+        .map(|p| p.id.clone())
+        .ok_or_else(|| format!("profile not found: {s}"))
+}
+
+// 按已有值的类型解释：字符串原样保留，数组按逗号拆分，其余按 JSON 解析
+fn parse_field(current: Option<&Value>, raw: &str) -> Value {
+    match current {
+        Some(Value::String(_)) => Value::String(raw.to_string()),
+        Some(Value::Array(_)) if !raw.trim_start().starts_with('[') => Value::Array(
+            raw.split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(|s| Value::String(s.to_string()))
+                .collect(),
+        ),
+        _ => serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string())),
+    }
+}
+
+/// 把 KEY=VALUE 写入序列化后的结构；未知字段直接报错，避免拼错的键被静默忽略
+fn apply_fields(target: &mut Value, fields: &[String]) -> CliResult {
+    for field in fields {
+        let (path, raw) = field
+            .split_once('=')
+            .ok_or_else(|| format!("expected KEY=VALUE, got {field}"))?;
+        let mut node = &mut *target;
+        let mut keys = path.split('.').peekable();
+        while let Some(key) = keys.next() {
+            // 可选的嵌套结构（例如 https 的 plugin）未设置时为 null
+            if node.is_null() {
+                *node = Value::Object(Default::default());
+            }
+            let obj = node
+                .as_object_mut()
+                .ok_or_else(|| format!("{path}: {key} is not an object"))?;
+            if !obj.is_empty() && !obj.contains_key(key) {
+                return Err(format!("unknown field: {path}"));
+            }
+            if keys.peek().is_none() {
+                let value = parse_field(obj.get(key), raw);
+                obj.insert(key.to_string(), value);
+                break;
+            }
+            node = obj.entry(key).or_insert(Value::Null);
+        }
+    }
+    Ok(())
+}
+
+fn edit<T: Serialize + serde::de::DeserializeOwned>(item: &T, fields: &[String]) -> CliResult<T> {
+    let mut value = serde_json::to_value(item).map_err(|e| e.to_string())?;
+    apply_fields(&mut value, fields)?;
+    serde_json::from_value(value).map_err(|e| format!("invalid field value: {e}"))
+}
+
+fn remote_of(p: &Proxy) -> String {
+    let domains = |sub: &String, custom: &Vec<String>| {
+        if sub.is_empty() {
+            custom.join(",")
+        } else {
+            sub.clone()
+        }
+    };
+    match p {
+        Proxy::Tcp(t) => t.remote_port.to_string(),
+        Proxy::Udp(u) => u.remote_port.to_string(),
+        Proxy::Http(h) => domains(&h.subdomain, &h.custom_domains),
+        Proxy::Https(h) => domains(&h.subdomain, &h.custom_domains),
+        Proxy::Stcp(_) | Proxy::Sudp(_) | Proxy::Xtcp(_) => "-".into(),
+    }
+}
+
+fn find_proxy(state: &AppState, profile_id: &str, key: &str) -> CliResult<Proxy> {
+    proxy_service::find(state, profile_id, key).ok_or_else(|| format!("proxy not found: {key}"))
+}
+
+// 守护进程正在运行该 profile 时让它重新读取 store
+fn notify_daemon(host: &DaemonHost, profile_id: &str) {
+    if let Some(info) = daemon::running_daemon(host) {
+        if info.profiles.iter().any(|p| p == profile_id) && !daemon::request_reload(&info) {
+            eprintln!(
+                "note: restart the daemon (pid {}) to apply changes",
+                info.pid
+            );
+        }
+    }
+}
+
+fn set_enabled(
+    host: &DaemonHost,
+    state: &AppState,
+    profile_id: &str,
+    key: &str,
+    enable: bool,
+) -> CliResult {
+    let mut proxy = find_proxy(state, profile_id, key)?;
+    if proxy.enable == enable {
+        return Ok(());
+    }
+    proxy.enable = enable;
+    proxy_service::save(host, state, profile_id, proxy).map_err(Into::into)
+}
+
+async fn proxy_command(
+    host: &DaemonHost,
+    state: &AppState,
+    profile_id: &str,
+    cmd: ProxyCommand,
+    json: bool,
+) -> CliResult {
+    match cmd {
+        ProxyCommand::List => {
+            let proxies = state
+                .read()
+                .profile(profile_id)
+                .map(|p| p.config.proxies.clone())
+                .unwrap_or_default();
+            if json {
+                return print_json(&proxies);
+            }
+            for p in &proxies {
+                println!(
+                    "{:<20} {:<6} {:<9} {:<22} {}",
+                    p.name,
+                    p.kind(),
+                    if p.enable { "enabled" } else { "disabled" },
+                    format!("{}:{}", p.local_ip, p.local_port),
+                    remote_of(p)
+                );
+            }
+            return Ok(());
+        }
+        ProxyCommand::Add {
+            kind,
+            name,
+            fields,
+            disabled,
+        } => {
+            if proxy_service::find(state, profile_id, &name).is_some() {
+                return Err(format!("proxy {name} already exists, use `proxy set`"));
+            }
+            let mut proxy =
+                Proxy::with_kind(&kind).ok_or_else(|| format!("unknown type {kind}"))?;
+            proxy.name = name;
+            proxy.enable = !disabled;
+            // 空 id 在反序列化时会生成新的
+            let proxy = edit(&proxy, &fields)?;
+            proxy_service::save(host, state, profile_id, proxy)?;
+        }
+        ProxyCommand::Set { proxy, fields } => {
+            let current = find_proxy(state, profile_id, &proxy)?;
+            let mut updated: Proxy = edit(&current, &fields)?;
+            updated.id = current.id.clone();
+            proxy_service::save(host, state, profile_id, updated)?;
+        }
+        ProxyCommand::Rm { proxy } => {
+            let current = find_proxy(state, profile_id, &proxy)?;
+            proxy_service::remove(host, state, profile_id, &current.id)?;
+        }
+        ProxyCommand::Enable { proxy } => set_enabled(host, state, profile_id, &proxy, true)?,
+        ProxyCommand::Disable { proxy } => set_enabled(host, state, profile_id, &proxy, false)?,
+    }
+    notify_daemon(host, profile_id);
+    Ok(())
+}
+
+fn server_command(
+    host: &DaemonHost,
+    state: &AppState,
+    profile_id: &str,
+    cmd: ServerCommand,
+) -> CliResult {
+    let ServerCommand::Set { fields } = cmd;
+    let mut cfg = state
+        .read()
+        .profile(profile_id)
+        .map(|p| p.config.clone())
+        .ok_or_else(|| format!("profile not found: {profile_id}"))?;
+    // 代理与访问端有各自的子命令，这里只改服务端设置
+    cfg.proxies.clear();
+    cfg.visitors.clear();
+    let cfg = edit(&cfg, &fields)?;
+    config_service::save_server_config(host, state, profile_id, cfg)?;
+    notify_daemon(host, profile_id);
+    Ok(())
+}
+
+fn find_version<'a>(versions: &'a [FrpVersion], key: &str) -> CliResult<&'a FrpVersion> {
+    versions
+        .iter()
+        .find(|v| v.version == key || v.name == key)
+        .ok_or_else(|| format!("version not found: {key}"))
+}
+
+async fn version_command(
+    host: &DaemonHost,
+    state: &AppState,
+    cmd: VersionCommand,
+    json: bool,
+) -> CliResult {
+    let versions = version_service::get_versions(host, state).await?;
+    match cmd {
+        VersionCommand::List => {
+            if json {
+                return print_json(&versions);
+            }
+            for v in &versions {
+                println!(
+                    "{} {:<10} {:<10} {:<10} {}",
+                    if v.active { "*" } else { " " },
+                    v.version,
+                    if v.exist { "downloaded" } else { "-" },
+                    v.size,
+                    v.name
+                );
+            }
+        }
+        VersionCommand::Download { version } => {
+            let v = find_version(&versions, &version)?;
+            version_service::download(host, state, &v.name, &v.url).await?;
+        }
+        VersionCommand::Activate { version } => {
+            let v = find_version(&versions, &version)?;
+            version_service::activate(host, state, &v.name)?;
+        }
+    }
+    Ok(())
+}
+
+async fn status_command(
+    host: &DaemonHost,
+    state: &AppState,
+    selector: Option<&str>,
+    json: bool,
+) -> CliResult {
+    let daemon = daemon::running_daemon(host);
+    let ids = match selector {
+        Some(_) => vec![resolve_profile(state, selector)?],
+        None => state.read().profiles.iter().map(|p| p.id.clone()).collect(),
+    };
+    let mut profiles = Vec::new();
+    for id in ids {
+        let Some(profile) = state.read().profile(&id).cloned() else {
+            continue;
+        };
+        let running = daemon
+            .as_ref()
+            .is_some_and(|d| d.profiles.contains(&profile.id));
+        let live = match AdminEndpoint::from_config(&profile.config).filter(|_| running) {
+            Some(endpoint) => endpoint.status().await.unwrap_or_default(),
+            None => Vec::new(),
+        };
+        let proxies = profile
+            .config
+            .proxies
+            .iter()
+            .map(|p| {
+                let s = live.iter().find(|s| s.name == p.name);
+                ProxyState {
+                    name: p.name.clone(),
+                    kind: p.kind(),
+                    enabled: p.enable,
+                    phase: s.map(|s| s.status.clone()),
+                    error: s.map(|s| s.err.clone()).unwrap_or_default(),
+                }
+            })
+            .collect();
+        profiles.push(ProfileStatus {
+            id: profile.id,
+            name: profile.name,
+            running,
+            proxies,
+        });
+    }
+
+    let report = StatusReport { daemon, profiles };
+    if json {
+        return print_json(&report);
+    }
+    match &report.daemon {
+        Some(d) => println!("daemon: running (pid {}, since {})", d.pid, d.started_at),
+        None => println!("daemon: not running"),
+    }
+    for p in &report.profiles {
+        let state = if p.running { "running" } else { "stopped" };
+        println!("{} [{state}]", p.name);
+        for x in &p.proxies {
+            let phase = match (&x.phase, x.enabled) {
+                (Some(phase), _) => phase.as_str(),
+                (None, false) => "disabled",
+                (None, true) => "-",
+            };
+            if x.error.is_empty() {
+                println!("  {:<20} {:<6} {phase}", x.name, x.kind);
+            } else {
+                println!("  {:<20} {:<6} {phase} ({})", x.name, x.kind, x.error);
+            }
+        }
+    }
+    Ok(())
+}
+
+// 1536 → 1.5 KiB
+fn human_bytes(n: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut v = n as f64;
+    let mut unit = 0;
+    while v >= 1024.0 && unit < UNITS.len() - 1 {
+        v /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{n} B")
+    } else {
+        format!("{v:.1} {}", UNITS[unit])
+    }
+}
+
+fn traffic_command(
+    host: &DaemonHost,
+    state: &AppState,
+    profile_id: &str,
+    cmd: TrafficCommand,
+    json: bool,
+) -> CliResult {
+    match cmd {
+        TrafficCommand::Usage { from, to } => {
+            let usage = traffic_service::usage(
+                host,
+                state,
+                Some(profile_id),
+                from.as_deref(),
+                to.as_deref(),
+            )?;
+            if json {
+                return print_json(&usage);
+            }
+            for u in &usage {
+                let name = if u.proxy_name.is_empty() {
+                    &u.proxy_id
+                } else {
+                    &u.proxy_name
+                };
+                println!(
+                    "{:<20} up {:>10}  down {:>10}  total {:>10}",
+                    name,
+                    human_bytes(u.up),
+                    human_bytes(u.down),
+                    human_bytes(u.total)
+                );
+            }
+        }
+        TrafficCommand::Quota => {
+            // 运行中实例的内存计数在别的进程里，只能看到已落盘的部分
+            let db = traffic_service::open(host)?;
+            let statuses = quota_service::statuses(host, &db, profile_id, &HashMap::new())?;
+            if json {
+                return print_json(&statuses);
+            }
+            for s in &statuses {
+                println!(
+                    "{:<20} {:<8} {:>10} / {:<10} {:<9} resets {}",
+                    s.proxy_name,
+                    format!("{:?}", s.period).to_lowercase(),
+                    human_bytes(s.used),
+                    human_bytes(s.limit),
+                    format!("{:?}", s.level).to_lowercase(),
+                    s.resets_at
+                );
+            }
+        }
+        TrafficCommand::Export {
+            span,
+            proxy,
+            from,
+            to,
+            format,
+            output,
+        } => {
+            // 已删除的代理只能按 id 查
+            let proxy_id = proxy.map(|key| {
+                proxy_service::find(state, profile_id, &key).map_or(key, |p| p.id.clone())
+            });
+            let query = TrafficQuery {
+                profile_id: Some(profile_id.to_string()),
+                proxy_id,
+                span: span.into(),
+                from,
+                to,
+            };
+            let text = traffic_service::export(host, state, &query, format.into())?;
+            match output {
+                Some(path) => std::fs::write(&path, text).map_err(|e| e.to_string())?,
+                None => print!("{text}"),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn stop_command(host: &DaemonHost) -> CliResult {
+    let Some(info) = daemon::running_daemon(host) else {
+        println!("daemon is not running");
+        return Ok(());
+    };
+    daemon::request_stop(&info)?;
+    let deadline = Instant::now() + STOP_TIMEOUT;
+    while daemon::running_daemon(host).is_some() {
+        if Instant::now() >= deadline {
+            return Err(format!("daemon (pid {}) did not exit in time", info.pid));
+        }
+        std::thread::sleep(Duration::from_millis(200));
+    }
+    Ok(())
+}
+
+async fn execute(cli: Cli) -> CliResult {
+    let (default_config, default_data) = daemon::default_dirs();
+    let host = DaemonHost::new(
+        cli.config_dir.unwrap_or(default_config),
+        cli.data_dir.unwrap_or(default_data),
+    );
+    let selector = cli.profile.as_deref();
+
+    if let Command::Start { profiles } = &cli.command {
+        let profiles: Vec<String> = cli.profile.iter().chain(profiles).cloned().collect();
+        return daemon::serve(host, &profiles).await;
+    }
+
+    if cli.command.writes_store() {
+        if let Some(pid) = gui_lock::holder(&host) {
+            return Err(format!(
+                "the desktop app is running (pid {pid}) and would overwrite this change; quit it first"
+            ));
+        }
+    }
+
+    let state = host.app_state();
+    config_service::loaded_from_store(&host, &state)?;
+    // 未提供主密码时仍可执行不涉及敏感字段的操作
+    if std::env::var_os(PASSPHRASE_ENV).is_some() {
+        daemon::unlock(&host)?;
+    }
+
+    match cli.command {
+        Command::Proxy(cmd) => {
+            let id = resolve_profile(&state, selector)?;
+            proxy_command(&host, &state, &id, cmd, cli.json).await
+        }
+        Command::Server(cmd) => {
+            let id = resolve_profile(&state, selector)?;
+            server_command(&host, &state, &id, cmd)
+        }
+        Command::Version(cmd) => version_command(&host, &state, cmd, cli.json).await,
+        Command::Stop => stop_command(&host),
+        Command::Status => status_command(&host, &state, selector, cli.json).await,
+        Command::Export { format, output } => {
+            let id = resolve_profile(&state, selector)?;
+            let text = config_service::export_config(&state, &id, format.into())?;
+            match output {
+                Some(path) => config_service::write_private(&path, &text)?,
+                None => print!("{text}"),
+            }
+            Ok(())
+        }
+        Command::Traffic(cmd) => {
+            let id = resolve_profile(&state, selector)?;
+            traffic_command(&host, &state, &id, cmd, cli.json)
+        }
+        Command::Start { .. } => unreachable!(),
+    }
+}
+
+pub fn run() {
+    let cli = Cli::parse();
+    if let Err(e) = tauri::async_runtime::block_on(execute(cli)) {
+        eprintln!("error: {e}");
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,488 @@
+//! 无界面的守护进程：读取与桌面端相同的 frpc.json，启动 frpc 并把日志打印到 stdout。
+//! unix 下收到 SIGHUP 时重新读取配置并热重载（未开启 webServer 的实例会重启）。
+//! 运行期间在数据目录写 daemon.json，供 frpc-cli 的 stop / status 使用。
+
+use crate::errors::Result;
+use crate::events::{
+    EVT_CLOSE, EVT_DOWNLOAD_PROGRESS, EVT_LOG_ERROR, EVT_LOG_STDERR, EVT_LOG_STDOUT,
+    EVT_PROXY_STATUS, EVT_RESTART, EVT_STARTED,
+};
+use crate::host::{EventSink, Host, KvStore};
+use crate::infra::json_store::JsonStore;
+use crate::infra::paths::DAEMON_FILE;
+use crate::services::{config_service, runner, vault_service};
+use crate::state::{AppState, FrpcProcState};
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::{Arc, Mutex};
+
+// 与 tauri.conf.json 中的 identifier 一致，和桌面端共用同一份数据
+const IDENTIFIER: &str = "frpc";
+// 开启了主密码时从该环境变量读取
+pub(crate) const PASSPHRASE_ENV: &str = "FRPC_PASSPHRASE";
+
+#[derive(Parser)]
+#[command(
+    name = "frpc-daemon",
+    version,
+    about = "Run frpc desktop profiles without the GUI"
+)]
+struct Options {
+    /// Profile to run, may be repeated; defaults to the active profile
+    #[arg(long = "profile", value_name = "ID|NAME")]
+    profiles: Vec<String>,
+    /// Directory for generated frpc toml files
+    #[arg(long)]
+    config_dir: Option<PathBuf>,
+    /// Directory containing frpc.json, downloads and logs
+    #[arg(long)]
+    data_dir: Option<PathBuf>,
+}
+
+fn home_dir() -> PathBuf {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+        .unwrap_or_default()
+}
+
+// 与 Tauri 的 app_config_dir / app_data_dir 对应
+pub(crate) fn default_dirs() -> (PathBuf, PathBuf) {
+    let env_dir = |key: &str, fallback: &str| {
+        std::env::var_os(key)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home_dir().join(fallback))
+    };
+    if cfg!(target_os = "macos") {
+        let dir = home_dir()
+            .join("Library/Application Support")
+            .join(IDENTIFIER);
+        (dir.clone(), dir)
+    } else if cfg!(windows) {
+        let dir = env_dir("APPDATA", "AppData/Roaming").join(IDENTIFIER);
+        (dir.clone(), dir)
+    } else {
+        (
+            env_dir("XDG_CONFIG_HOME", ".config").join(IDENTIFIER),
+            env_dir("XDG_DATA_HOME", ".local/share").join(IDENTIFIER),
+        )
+    }
+}
+
+struct DaemonInner {
+    config_dir: PathBuf,
+    data_dir: PathBuf,
+    state: AppState,
+    stores: Mutex<HashMap<String, Arc<JsonStore>>>,
+    watchdog: Mutex<Option<(Child, ChildStdin)>>,
+}
+
+#[derive(Clone)]
+pub struct DaemonHost(Arc<DaemonInner>);
+
+impl DaemonHost {
+    pub(crate) fn new(config_dir: PathBuf, data_dir: PathBuf) -> Self {
+        Self(Arc::new(DaemonInner {
+            config_dir,
+            data_dir,
+            state: AppState::default(),
+            stores: Mutex::new(HashMap::new()),
+            watchdog: Mutex::new(None),
+        }))
+    }
+
+    // watchdog 与本程序放在同一目录；找不到时照常运行，只是异常退出后 frpc 不会被回收
+    fn spawn_watchdog(&self) {
+        let Some(path) = std::env::current_exe().ok().and_then(|exe| {
+            let dir = exe.parent()?.to_path_buf();
+            Some(dir.join(format!("frpc-watchdog{}", std::env::consts::EXE_SUFFIX)))
+        }) else {
+            return;
+        };
+        if !path.exists() {
+            eprintln!("[daemon] watchdog not found at {}", path.display());
+            return;
+        }
+        match Command::new(&path).stdin(Stdio::piped()).spawn() {
+            Ok(mut child) => {
+                if let Some(stdin) = child.stdin.take() {
+                    *self.0.watchdog.lock().unwrap() = Some((child, stdin));
+                }
+            }
+            Err(e) => eprintln!("[daemon] spawn watchdog failed: {e}"),
+        }
+    }
+
+    pub(crate) fn label(&self, id: &str) -> String {
+        self.0
+            .state
+            .read()
+            .profile(id)
+            .map(|p| p.name.clone())
+            .unwrap_or_else(|| id.to_string())
+    }
+}
+
+// 只打印各实例自己通道上的事件，全局通道是同一份内容
+fn split_instance_event(event: &str) -> Option<(&'static str, &str)> {
+    [
+        EVT_LOG_STDOUT,
+        EVT_LOG_STDERR,
+        EVT_LOG_ERROR,
+        EVT_STARTED,
+        EVT_CLOSE,
+        EVT_RESTART,
+        EVT_PROXY_STATUS,
+    ]
+    .into_iter()
+    .find_map(|base| Some((base, event.strip_prefix(base)?.strip_prefix('/')?)))
+}
+
+fn format_event(base: &str, p: &Value) -> String {
+    match base {
+        EVT_LOG_STDOUT | EVT_LOG_STDERR => p.as_str().unwrap_or_default().to_string(),
+        EVT_LOG_ERROR => format!("error: {}", p.as_str().unwrap_or_default()),
+        EVT_STARTED => format!("frpc started, pid={}", p["pid"]),
+        EVT_CLOSE => format!("frpc exited, code={}", p["code"]),
+        EVT_RESTART if p["gaveUp"] == Value::Bool(true) => {
+            format!("restart gave up after {} attempts", p["attempt"])
+        }
+        EVT_RESTART => format!(
+            "restarting in {}ms (attempt {})",
+            p["delayMs"], p["attempt"]
+        ),
+        EVT_PROXY_STATUS => {
+            let items: Vec<String> = p["proxies"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|x| {
+                    let name = x["name"].as_str().unwrap_or_default();
+                    let phase = x["phase"].as_str().unwrap_or_default();
+                    match x["error"].as_str().filter(|e| !e.is_empty()) {
+                        Some(err) => format!("{name}={phase} ({err})"),
+                        None => format!("{name}={phase}"),
+                    }
+                })
+                .collect();
+            format!("proxies: {}", items.join(", "))
+        }
+        _ => p.to_string(),
+    }
+}
+
+impl EventSink for DaemonHost {
+    fn emit<S: Serialize + Clone>(&self, event: &str, payload: S) {
+        let Ok(value) = serde_json::to_value(payload) else {
+            return;
+        };
+        // frpc-cli version download 的进度，原地刷新
+        if event == EVT_DOWNLOAD_PROGRESS {
+            let progress = value["progress"].as_u64().unwrap_or_default();
+            let mut err = std::io::stderr().lock();
+            let _ = write!(err, "\rdownloading {} {progress}%", value["name"]);
+            if progress >= 100 {
+                let _ = writeln!(err);
+            }
+            return;
+        }
+        let Some((base, id)) = split_instance_event(event) else {
+            return;
+        };
+        let line = format!("[{}] {}", self.label(id), format_event(base, &value));
+        let mut out = std::io::stdout().lock();
+        let _ = writeln!(out, "{line}");
+    }
+}
+
+impl Host for DaemonHost {
+    fn config_dir(&self) -> PathBuf {
+        self.0.config_dir.clone()
+    }
+    fn data_dir(&self) -> PathBuf {
+        self.0.data_dir.clone()
+    }
+    // 与 tauri-plugin-store 一样，相对路径放在数据目录下
+    fn store(&self, file: &str) -> Result<Arc<dyn KvStore>> {
+        let mut stores = self.0.stores.lock().unwrap();
+        if let Some(st) = stores.get(file) {
+            return Ok(st.clone());
+        }
+        let st = Arc::new(JsonStore::open(self.0.data_dir.join(file))?);
+        stores.insert(file.to_string(), st.clone());
+        Ok(st)
+    }
+    fn app_state(&self) -> AppState {
+        self.0.state.clone()
+    }
+    fn notify_watchdog(&self, msg: &str) {
+        if let Some((_, stdin)) = self.0.watchdog.lock().unwrap().as_mut() {
+            let _ = writeln!(stdin, "{msg}");
+        }
+    }
+}
+
+/// daemon.json 的内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DaemonInfo {
+    pub pid: u32,
+    pub profiles: Vec<String>,
+    pub started_at: String,
+}
+
+fn write_info(host: &DaemonHost, info: &DaemonInfo) {
+    let path = host.data_dir().join(DAEMON_FILE);
+    let result = serde_json::to_vec_pretty(info)
+        .map_err(std::io::Error::from)
+        .and_then(|bytes| std::fs::write(&path, bytes));
+    if let Err(e) = result {
+        eprintln!("[daemon] write {} failed: {e}", path.display());
+    }
+}
+
+// pid 为 0 或超出 i32 时 kill 会发给整个进程组，按不存在处理
+#[cfg(unix)]
+fn send_signal(pid: u32, sig: i32) -> bool {
+    let Some(pid) = i32::try_from(pid).ok().filter(|p| *p > 0) else {
+        return false;
+    };
+    unsafe { libc::kill(pid, sig) == 0 }
+}
+
+/// 读取正在运行的守护进程信息；进程已不存在（例如被强杀）时返回 None
+pub fn running_daemon<H: Host>(host: &H) -> Option<DaemonInfo> {
+    let bytes = std::fs::read(host.data_dir().join(DAEMON_FILE)).ok()?;
+    let info: DaemonInfo = serde_json::from_slice(&bytes).ok()?;
+    #[cfg(unix)]
+    if !send_signal(info.pid, 0) {
+        return None;
+    }
+    Some(info)
+}
+
+/// 通知守护进程重新读取 store（SIGHUP）
+#[cfg(unix)]
+pub fn request_reload(info: &DaemonInfo) -> bool {
+    send_signal(info.pid, libc::SIGHUP)
+}
+
+// Windows 没有 SIGHUP，改动在守护进程重启后生效
+#[cfg(not(unix))]
+pub fn request_reload(_info: &DaemonInfo) -> bool {
+    false
+}
+
+/// 让守护进程停止所有实例并退出（SIGTERM）
+#[cfg(unix)]
+pub fn request_stop(info: &DaemonInfo) -> std::result::Result<(), String> {
+    if send_signal(info.pid, libc::SIGTERM) {
+        Ok(())
+    } else {
+        Err(format!("signal daemon (pid {}) failed", info.pid))
+    }
+}
+
+#[cfg(not(unix))]
+pub fn request_stop(info: &DaemonInfo) -> std::result::Result<(), String> {
+    Err(format!(
+        "stopping the daemon is not supported on this platform, press Ctrl+C in its console (pid {})",
+        info.pid
+    ))
+}
+
+// 未指定时运行当前激活的 profile；按 id 或名称匹配
+fn resolve_targets(
+    state: &AppState,
+    selectors: &[String],
+) -> std::result::Result<Vec<String>, String> {
+    let g = state.read();
+    if selectors.is_empty() {
+        return Ok(vec![g.active_profile.clone()]);
+    }
+    selectors
+        .iter()
+        .map(|s| {
+            g.profiles
+                .iter()
+                .find(|p| p.id == *s || p.name == *s)
+                .map(|p| p.id.clone())
+                .ok_or_else(|| format!("profile not found: {s}"))
+        })
+        .collect()
+}
+
+// 让运行中的实例与目标列表一致：多余的停掉，已运行的热重载，未运行的启动
+async fn apply(
+    host: &DaemonHost,
+    proc_state: &FrpcProcState,
+    selectors: &[String],
+    started_at: &str,
+) {
+    let state = host.app_state();
+    let targets = match resolve_targets(&state, selectors) {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("[daemon] {e}");
+            return;
+        }
+    };
+    write_info(
+        host,
+        &DaemonInfo {
+            pid: std::process::id(),
+            profiles: targets.clone(),
+            started_at: started_at.to_string(),
+        },
+    );
+    for (id, _) in proc_state.all() {
+        if !targets.contains(&id) && runner::is_running(proc_state, &id).unwrap_or(false) {
+            if let Err(e) = runner::stop(host, &state, proc_state, &id).await {
+                eprintln!("[daemon] [{}] stop failed: {e}", host.label(&id));
+            }
+        }
+    }
+    for id in &targets {
+        let result = if runner::is_running(proc_state, id).unwrap_or(false) {
+            match runner::reload(host, &state, proc_state, id).await {
+                Ok(true) => Ok(()),
+                // 没有 admin API 时只能重启
+                Ok(false) => match runner::stop(host, &state, proc_state, id).await {
+                    Ok(()) => runner::start(host, &state, proc_state, id)
+                        .await
+                        .map(|_| ()),
+                    Err(e) => Err(e),
+                },
+                Err(e) => Err(e),
+            }
+        } else {
+            runner::start(host, &state, proc_state, id)
+                .await
+                .map(|_| ())
+        };
+        if let Err(e) = result {
+            eprintln!("[daemon] [{}] {e}", host.label(id));
+        }
+    }
+}
+
+pub(crate) fn unlock(host: &DaemonHost) -> std::result::Result<(), String> {
+    let state = host.app_state();
+    if !state.read().vault.locked() {
+        return Ok(());
+    }
+    let passphrase = std::env::var(PASSPHRASE_ENV)
+        .map_err(|_| format!("secrets are encrypted, set {PASSPHRASE_ENV} to unlock"))?;
+    vault_service::unlock(host, &state, &passphrase).map_err(Into::into)
+}
+
+#[cfg(unix)]
+async fn wait_for_signals(
+    host: &DaemonHost,
+    proc_state: &FrpcProcState,
+    selectors: &[String],
+    started_at: &str,
+) {
+    use tokio::signal::unix::{signal, SignalKind};
+    let (Ok(mut hup), Ok(mut term)) = (
+        signal(SignalKind::hangup()),
+        signal(SignalKind::terminate()),
+    ) else {
+        eprintln!("[daemon] install signal handlers failed");
+        let _ = tokio::signal::ctrl_c().await;
+        return;
+    };
+    loop {
+        tokio::select! {
+            _ = hup.recv() => {
+                println!("[daemon] SIGHUP received, reloading");
+                let state = host.app_state();
+                match config_service::reload_from_store(host, &state) {
+                    Ok(()) => apply(host, proc_state, selectors, started_at).await,
+                    Err(e) => eprintln!("[daemon] reload failed: {e}"),
+                }
+            }
+            _ = term.recv() => return,
+            _ = tokio::signal::ctrl_c() => return,
+        }
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_signals(
+    _host: &DaemonHost,
+    _proc_state: &FrpcProcState,
+    _selectors: &[String],
+    _started_at: &str,
+) {
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// 前台运行直到收到 SIGTERM / Ctrl+C；frpc-cli start 也走这里
+pub(crate) async fn serve(
+    host: DaemonHost,
+    profiles: &[String],
+) -> std::result::Result<(), String> {
+    config_service::loaded_from_store(&host, &host.app_state())?;
+    unlock(&host)?;
+    if let Some(other) = running_daemon(&host) {
+        return Err(format!("another daemon is running (pid {})", other.pid));
+    }
+    host.spawn_watchdog();
+
+    let started_at = chrono::Utc::now().to_rfc3339();
+    let proc_state = FrpcProcState::default();
+    apply(&host, &proc_state, profiles, &started_at).await;
+    wait_for_signals(&host, &proc_state, profiles, &started_at).await;
+
+    println!("[daemon] stopping");
+    runner::stop_all(&host, &host.app_state(), &proc_state).await;
+    let _ = std::fs::remove_file(host.data_dir().join(DAEMON_FILE));
+    Ok(())
+}
+
+pub fn run() {
+    let opts = Options::parse();
+    let (default_config, default_data) = default_dirs();
+    let host = DaemonHost::new(
+        opts.config_dir.unwrap_or(default_config),
+        opts.data_dir.unwrap_or(default_data),
+    );
+    if let Err(e) = tauri::async_runtime::block_on(serve(host, &opts.profiles)) {
+        eprintln!("[daemon] {e}");
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn invalid_pid_is_not_running() {
+        let dir = std::env::temp_dir().join(format!("frpc-daemon-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let host = DaemonHost::new(dir.clone(), dir.clone());
+        let info = |pid: u32| DaemonInfo {
+            pid,
+            profiles: Vec::new(),
+            started_at: String::new(),
+        };
+
+        write_info(&host, &info(std::process::id()));
+        assert!(running_daemon(&host).is_some());
+        for pid in [0, u32::MAX, i32::MAX as u32 + 1] {
+            write_info(&host, &info(pid));
+            assert!(running_daemon(&host).is_none(), "pid {pid}");
+            assert!(request_stop(&info(pid)).is_err(), "pid {pid}");
+            assert!(!request_reload(&info(pid)), "pid {pid}");
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
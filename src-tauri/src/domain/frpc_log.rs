@@ -0,0 +1,259 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    #[default]
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    fn from_tag(tag: &str) -> Self {
+        match tag {
+            "T" => LogLevel::Trace,
+            "D" => LogLevel::Debug,
+            "W" => LogLevel::Warn,
+            "E" => LogLevel::Error,
+            _ => LogLevel::Info,
+        }
+    }
+}
+
+/// frpc 的一行日志，例如：
+/// 2024-05-01 12:00:00.000 [I] [client/control.go:168] [0ab1c2d3e4f5] [ssh] start proxy success
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogLine {
+    // 原样保留 frpc 输出的时间（本地时区，无时区信息）
+    pub ts: Option<String>,
+    pub level: LogLevel,
+    pub source: Option<String>,
+    pub run_id: Option<String>,
+    pub proxy: Option<String>,
+    pub message: String,
+    pub raw: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LogEventKind {
+    LoginSuccess,
+    LoginFailed,
+    ProxyStarted,
+    ProxyStartError,
+    Reconnecting,
+    WorkConnError,
+}
+
+fn line_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        // 新版日期用 -，旧版用 /；毫秒可选
+        Regex::new(
+            r"^(?P<ts>\d{4}[-/]\d{2}[-/]\d{2} \d{2}:\d{2}:\d{2}(?:\.\d+)?)\s+\[(?P<lvl>[TDIWE])\]\s+\[(?P<src>[^\]]+)\]\s*(?P<rest>.*)$",
+        )
+        .unwrap()
+    })
+}
+
+fn ansi_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\x1b\[[0-9;]*m").unwrap())
+}
+
+fn take_bracket(s: &str) -> Option<(&str, &str)> {
+    let s = s.strip_prefix('[')?;
+    let end = s.find(']')?;
+    Some((&s[..end], s[end + 1..].trim_start()))
+}
+
+fn is_run_id(s: &str) -> bool {
+    !s.is_empty() && s.len() <= 32 && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// 认不出格式时整行作为 message，level 取默认
+pub fn parse_line(raw: &str) -> LogLine {
+    let clean = ansi_re().replace_all(raw, "");
+    let clean = clean.trim_end();
+    let Some(caps) = line_re().captures(clean) else {
+        return LogLine {
+            ts: None,
+            level: LogLevel::default(),
+            source: None,
+            run_id: None,
+            proxy: None,
+            message: clean.to_string(),
+            raw: raw.to_string(),
+        };
+    };
+
+    // message 前可能依次带 [runId] [proxyName]
+    let mut rest = caps.name("rest").map_or("", |m| m.as_str());
+    let mut run_id = None;
+    let mut proxy = None;
+    if let Some((inner, tail)) = take_bracket(rest) {
+        if is_run_id(inner) {
+            run_id = Some(inner.to_string());
+            rest = tail;
+        }
+    }
+    if let Some((inner, tail)) = take_bracket(rest) {
+        if !inner.is_empty() {
+            proxy = Some(inner.to_string());
+            rest = tail;
+        }
+    }
+
+    LogLine {
+        ts: caps.name("ts").map(|m| m.as_str().to_string()),
+        level: LogLevel::from_tag(&caps["lvl"]),
+        source: caps.name("src").map(|m| m.as_str().to_string()),
+        run_id,
+        proxy,
+        message: rest.to_string(),
+        raw: raw.to_string(),
+    }
+}
+
+/// 识别关心的关键事件；其余日志返回 None
+pub fn classify(line: &LogLine) -> Option<LogEventKind> {
+    let msg = line.message.to_ascii_lowercase();
+    if msg.contains("login to server success") || msg.contains("login to the server success") {
+        return Some(LogEventKind::LoginSuccess);
+    }
+    if msg.contains("login to server failed")
+        || msg.contains("login to the server failed")
+        || (msg.contains("connect to server error") && line.level == LogLevel::Error)
+    {
+        return Some(LogEventKind::LoginFailed);
+    }
+    if msg.contains("try to reconnect") || msg.contains("reconnect to server") {
+        return Some(LogEventKind::Reconnecting);
+    }
+    if line.proxy.is_some() {
+        if msg.contains("start proxy success") {
+            return Some(LogEventKind::ProxyStarted);
+        }
+        if msg.contains("start error") {
+            return Some(LogEventKind::ProxyStartError);
+        }
+    }
+    // 正常的 "get a new work connection" 是 info 级别，只看警告以上
+    if line.level >= LogLevel::Warn
+        && (msg.contains("work conn") || msg.contains("connect to local service"))
+    {
+        return Some(LogEventKind::WorkConnError);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RUN_ID: &str = "0ab1c2d3e4f5a6b7";
+
+    #[test]
+    fn classifies_frpc_lines() {
+        let cases: &[(&str, Option<LogEventKind>)] = &[
+            (
+                "2024-05-01 12:00:00.123 [I] [client/service.go:295] [0ab1c2d3e4f5a6b7] login to server success, get run id [0ab1c2d3e4f5a6b7]",
+                Some(LogEventKind::LoginSuccess),
+            ),
+            (
+                "2024-05-01 12:00:00.123 [W] [client/service.go:128] login to the server failed: dial tcp 203.0.113.5:7000: connect: connection refused. With loginFailExit enabled, no additional retries will be attempted",
+                Some(LogEventKind::LoginFailed),
+            ),
+            (
+                "2024-05-01 12:00:00.123 [E] [client/connector.go:190] connect to server error: session shutdown",
+                Some(LogEventKind::LoginFailed),
+            ),
+            (
+                "2024-05-01 12:00:05.000 [I] [client/service.go:217] [0ab1c2d3e4f5a6b7] try to reconnect to server...",
+                Some(LogEventKind::Reconnecting),
+            ),
+            (
+                "2024-05-01 12:00:00.789 [I] [client/proxy/proxy_wrapper.go:206] [0ab1c2d3e4f5a6b7] [ssh] start proxy success",
+                Some(LogEventKind::ProxyStarted),
+            ),
+            (
+                "2024-05-01 12:00:00.789 [W] [client/proxy/proxy_wrapper.go:179] [0ab1c2d3e4f5a6b7] [web] start error: port unavailable",
+                Some(LogEventKind::ProxyStartError),
+            ),
+            (
+                "2024-05-01 12:01:00.000 [W] [client/proxy/proxy.go:204] [0ab1c2d3e4f5a6b7] [ssh] connect to local service [127.0.0.1:22] error: dial tcp 127.0.0.1:22: connect: connection refused",
+                Some(LogEventKind::WorkConnError),
+            ),
+            // info 级别的新工作连接不算错误
+            (
+                "2024-05-01 12:01:00.000 [I] [client/control.go:160] [0ab1c2d3e4f5a6b7] [ssh] get a new work connection: [198.51.100.7:51234]",
+                None,
+            ),
+            (
+                "2024-05-01 12:00:00.456 [I] [proxy/proxy_manager.go:177] [0ab1c2d3e4f5a6b7] proxy added: [ssh web]",
+                None,
+            ),
+            // 没有代理名时 start error 不算代理事件
+            (
+                "2024-05-01 12:00:00.000 [E] [client/admin.go:50] start error: listen tcp 127.0.0.1:7400: bind: address already in use",
+                None,
+            ),
+            ("frpc version 0.58.1", None),
+            ("", None),
+        ];
+        for (raw, want) in cases {
+            assert_eq!(classify(&parse_line(raw)), *want, "{raw}");
+        }
+    }
+
+    #[test]
+    fn parses_fields() {
+        let line = parse_line(
+            "2024-05-01 12:00:00.789 [W] [client/proxy/proxy_wrapper.go:179] [0ab1c2d3e4f5a6b7] [web] start error: port unavailable",
+        );
+        assert_eq!(line.ts.as_deref(), Some("2024-05-01 12:00:00.789"));
+        assert_eq!(line.level, LogLevel::Warn);
+        assert_eq!(
+            line.source.as_deref(),
+            Some("client/proxy/proxy_wrapper.go:179")
+        );
+        assert_eq!(line.run_id.as_deref(), Some(RUN_ID));
+        assert_eq!(line.proxy.as_deref(), Some("web"));
+        assert_eq!(line.message, "start error: port unavailable");
+
+        // 旧版日期格式，没有 runId 时方括号里的是代理名
+        let line =
+            parse_line("2023/01/02 03:04:05 [E] [proxy.go:99] [my-proxy] work connection closed");
+        assert_eq!(line.ts.as_deref(), Some("2023/01/02 03:04:05"));
+        assert_eq!(line.level, LogLevel::Error);
+        assert_eq!(line.run_id, None);
+        assert_eq!(line.proxy.as_deref(), Some("my-proxy"));
+        assert_eq!(classify(&line), Some(LogEventKind::WorkConnError));
+    }
+
+    #[test]
+    fn strips_ansi_colors() {
+        let raw = "\x1b[1;34m2024-05-01 12:00:00.123 [I] [client/service.go:295] [0ab1c2d3e4f5a6b7] login to server success, get run id [0ab1c2d3e4f5a6b7]\x1b[0m\r\n";
+        let line = parse_line(raw);
+        assert_eq!(line.level, LogLevel::Info);
+        assert_eq!(line.run_id.as_deref(), Some(RUN_ID));
+        assert_eq!(
+            line.message,
+            "login to server success, get run id [0ab1c2d3e4f5a6b7]"
+        );
+        assert_eq!(line.raw, raw);
+        assert_eq!(classify(&line), Some(LogEventKind::LoginSuccess));
+
+        let line = parse_line("\x1b[31m[W] [ssh] start error\x1b[0m");
+        assert_eq!(line.ts, None);
+        assert_eq!(line.level, LogLevel::Info);
+        assert_eq!(line.message, "[W] [ssh] start error");
+        assert_eq!(classify(&line), None);
+    }
+}
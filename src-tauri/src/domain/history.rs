@@ -0,0 +1,265 @@
+use crate::domain::config::FrpcConfig;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+
+/// 某个 profile 在一次保存后的完整配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigSnapshot {
+    pub id: String,
+    pub profile_id: String,
+    // RFC 3339，UTC
+    pub created_at: String,
+    pub reason: String,
+    pub config: FrpcConfig,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotSummary {
+    pub id: String,
+    pub profile_id: String,
+    pub created_at: String,
+    pub reason: String,
+    pub proxy_count: usize,
+    pub visitor_count: usize,
+}
+
+impl From<&ConfigSnapshot> for SnapshotSummary {
+    fn from(s: &ConfigSnapshot) -> Self {
+        Self {
+            id: s.id.clone(),
+            profile_id: s.profile_id.clone(),
+            created_at: s.created_at.clone(),
+            reason: s.reason.clone(),
+            proxy_count: s.config.proxies.len(),
+            visitor_count: s.config.visitors.len(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// 一处差异；path 与校验结果一致，形如 serverPort、proxies[ssh].localPort
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigChange {
+    pub path: String,
+    pub kind: ChangeKind,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+/// 比较两份配置，返回从 from 到 to 的全部改动
+pub fn diff_configs(from: &FrpcConfig, to: &FrpcConfig) -> Vec<ConfigChange> {
+    let a = serde_json::to_value(from).unwrap_or(Value::Null);
+    let b = serde_json::to_value(to).unwrap_or(Value::Null);
+    let mut out = Vec::new();
+    diff_value("", &a, &b, &mut out);
+    out
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+fn diff_value(path: &str, a: &Value, b: &Value, out: &mut Vec<ConfigChange>) {
+    match (a, b) {
+        (Value::Object(a), Value::Object(b)) => diff_object(path, a, b, out),
+        (Value::Array(a), Value::Array(b)) if keyed(a) && keyed(b) => diff_list(path, a, b, out),
+        _ if a != b => out.push(ConfigChange {
+            path: path.to_string(),
+            kind: ChangeKind::Changed,
+            before: Some(a.clone()),
+            after: Some(b.clone()),
+        }),
+        _ => {}
+    }
+}
+
+fn diff_object(
+    path: &str,
+    a: &Map<String, Value>,
+    b: &Map<String, Value>,
+    out: &mut Vec<ConfigChange>,
+) {
+    for (k, av) in a {
+        match b.get(k) {
+            Some(bv) => diff_value(&join(path, k), av, bv, out),
+            None => out.push(ConfigChange {
+                path: join(path, k),
+                kind: ChangeKind::Removed,
+                before: Some(av.clone()),
+                after: None,
+            }),
+        }
+    }
+    for (k, bv) in b {
+        if !a.contains_key(k) {
+            out.push(ConfigChange {
+                path: join(path, k),
+                kind: ChangeKind::Added,
+                before: None,
+                after: Some(bv.clone()),
+            });
+        }
+    }
+}
+
+// 代理 / 访问端这类带 id 的列表按 id 对齐，改名也能认出是同一项
+fn keyed(items: &[Value]) -> bool {
+    items
+        .iter()
+        .all(|v| v.get("id").and_then(Value::as_str).is_some())
+}
+
+fn item_id(v: &Value) -> &str {
+    v.get("id").and_then(Value::as_str).unwrap_or_default()
+}
+
+// 路径里显示名称，名称为空时退回 id
+fn item_label(path: &str, v: &Value) -> String {
+    let name = v
+        .get("name")
+        .and_then(Value::as_str)
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| item_id(v));
+    format!("{path}[{name}]")
+}
+
+fn diff_list(path: &str, a: &[Value], b: &[Value], out: &mut Vec<ConfigChange>) {
+    let b_ids: HashSet<&str> = b.iter().map(item_id).collect();
+    for av in a {
+        if !b_ids.contains(item_id(av)) {
+            out.push(ConfigChange {
+                path: item_label(path, av),
+                kind: ChangeKind::Removed,
+                before: Some(av.clone()),
+                after: None,
+            });
+        }
+    }
+    for bv in b {
+        match a.iter().find(|av| item_id(av) == item_id(bv)) {
+            Some(av) => diff_value(&item_label(path, bv), av, bv, out),
+            None => out.push(ConfigChange {
+                path: item_label(path, bv),
+                kind: ChangeKind::Added,
+                before: None,
+                after: Some(bv.clone()),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::proxy::{Proxy, ProxyCommon, TcpProxy};
+    use serde_json::json;
+
+    fn tcp(id: &str, name: &str, remote_port: u16) -> Proxy {
+        Proxy::Tcp(TcpProxy {
+            common: ProxyCommon {
+                id: id.into(),
+                name: name.into(),
+                enable: true,
+                local_ip: "127.0.0.1".into(),
+                local_port: 22,
+                ..Default::default()
+            },
+            remote_port,
+        })
+    }
+
+    fn config(proxies: Vec<Proxy>) -> FrpcConfig {
+        FrpcConfig {
+            server_addr: "frp.example.com".into(),
+            server_port: 7000,
+            proxies,
+            ..Default::default()
+        }
+    }
+
+    fn summary(changes: &[ConfigChange]) -> Vec<(&str, ChangeKind)> {
+        changes.iter().map(|c| (c.path.as_str(), c.kind)).collect()
+    }
+
+    #[test]
+    fn identical_configs_have_no_changes() {
+        let cfg = config(vec![tcp("a", "ssh", 6000)]);
+        assert!(diff_configs(&cfg, &cfg).is_empty());
+    }
+
+    #[test]
+    fn scalar_change() {
+        let a = config(Vec::new());
+        let mut b = a.clone();
+        b.server_port = 7001;
+        let changes = diff_configs(&a, &b);
+        assert_eq!(summary(&changes), [("serverPort", ChangeKind::Changed)]);
+        assert_eq!(changes[0].before, Some(json!(7000)));
+        assert_eq!(changes[0].after, Some(json!(7001)));
+    }
+
+    #[test]
+    fn reordering_is_not_a_change() {
+        let a = config(vec![tcp("a", "ssh", 6000), tcp("b", "rdp", 6001)]);
+        let b = config(vec![tcp("b", "rdp", 6001), tcp("a", "ssh", 6000)]);
+        assert!(diff_configs(&a, &b).is_empty());
+    }
+
+    #[test]
+    fn aligns_list_items_by_id() {
+        let a = config(vec![tcp("a", "ssh", 6000), tcp("b", "rdp", 6001)]);
+        let b = config(vec![tcp("a", "ssh2", 6002), tcp("c", "vnc", 6003)]);
+        let changes = diff_configs(&a, &b);
+        // 改名后仍认作同一项，路径用新名称
+        assert_eq!(
+            summary(&changes),
+            [
+                ("proxies[rdp]", ChangeKind::Removed),
+                ("proxies[ssh2].name", ChangeKind::Changed),
+                ("proxies[ssh2].remotePort", ChangeKind::Changed),
+                ("proxies[vnc]", ChangeKind::Added),
+            ]
+        );
+        assert_eq!(changes[0].before.as_ref().unwrap()["id"], "b");
+        assert_eq!(changes[0].after, None);
+        assert_eq!(changes[3].before, None);
+        assert_eq!(changes[3].after.as_ref().unwrap()["remotePort"], 6003);
+    }
+
+    #[test]
+    fn unnamed_items_fall_back_to_id() {
+        let a = config(Vec::new());
+        let b = config(vec![tcp("a", "", 6000)]);
+        assert_eq!(
+            summary(&diff_configs(&a, &b)),
+            [("proxies[a]", ChangeKind::Added)]
+        );
+    }
+
+    #[test]
+    fn plain_lists_compare_as_a_whole() {
+        let a = json!({ "customDomains": ["a.com", "b.com"] });
+        let b = json!({ "customDomains": ["b.com"] });
+        let mut out = Vec::new();
+        diff_value("proxies[web]", &a, &b, &mut out);
+        assert_eq!(
+            summary(&out),
+            [("proxies[web].customDomains", ChangeKind::Changed)]
+        );
+    }
+}
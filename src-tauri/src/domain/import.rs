@@ -0,0 +1,651 @@
+use crate::domain::config::FrpcConfig;
+use crate::domain::proxy::{
+    gen_id, HttpProxy, HttpSwitch, HttpsPlugin, HttpsPluginOptions, HttpsProxy, Proxy,
+    ProxyCommon, SecretProxy, TcpProxy, UdpProxy,
+};
+use crate::domain::types::{AuthScope, AuthType, DomainType, TransportProtocol};
+use crate::domain::visitor::{Visitor, VisitorCommon, XtcpVisitor};
+use crate::infra::config_format::ConfigFormat;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+// 导入时无法映射到 FrpcConfig 的字段，原样带回给前端展示
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsupportedField {
+    pub path: String,
+    pub value: Value,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub format: ConfigFormat,
+    pub config: FrpcConfig,
+    pub unsupported: Vec<UnsupportedField>,
+}
+
+/// 一层表：取走认识的键，剩下的在 finish 时全部记为不支持
+struct Section {
+    path: String,
+    map: Map<String, Value>,
+    unsupported: Vec<UnsupportedField>,
+}
+
+impl Section {
+    fn new(path: String, map: Map<String, Value>) -> Self {
+        Self {
+            path,
+            map,
+            unsupported: Vec::new(),
+        }
+    }
+
+    fn key_path(&self, key: &str) -> String {
+        if self.path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", self.path, key)
+        }
+    }
+
+    fn reject(&mut self, path: String, value: Value, reason: &str) {
+        self.unsupported.push(UnsupportedField {
+            path,
+            value,
+            reason: reason.to_string(),
+        });
+    }
+
+    fn string(&mut self, key: &str) -> Option<String> {
+        match self.map.remove(key)? {
+            Value::String(s) => Some(s),
+            other => {
+                self.reject(self.key_path(key), other, "expected a string");
+                None
+            }
+        }
+    }
+
+    fn port(&mut self, key: &str) -> Option<u16> {
+        let v = self.map.remove(key)?;
+        match v.as_u64().and_then(|n| u16::try_from(n).ok()) {
+            Some(n) => Some(n),
+            None => {
+                self.reject(self.key_path(key), v, "expected a port number");
+                None
+            }
+        }
+    }
+
+    fn int(&mut self, key: &str) -> Option<i64> {
+        let v = self.map.remove(key)?;
+        match v.as_i64() {
+            Some(n) => Some(n),
+            None => {
+                self.reject(self.key_path(key), v, "expected an integer");
+                None
+            }
+        }
+    }
+
+    fn uint(&mut self, key: &str) -> Option<u32> {
+        let v = self.map.remove(key)?;
+        match v.as_u64().and_then(|n| u32::try_from(n).ok()) {
+            Some(n) => Some(n),
+            None => {
+                self.reject(self.key_path(key), v, "expected a non-negative integer");
+                None
+            }
+        }
+    }
+
+    fn bool(&mut self, key: &str) -> Option<bool> {
+        match self.map.remove(key)? {
+            Value::Bool(b) => Some(b),
+            other => {
+                self.reject(self.key_path(key), other, "expected a boolean");
+                None
+            }
+        }
+    }
+
+    fn strings(&mut self, key: &str) -> Vec<String> {
+        match self.map.remove(key) {
+            None => Vec::new(),
+            Some(Value::Array(items)) if items.iter().all(|i| i.is_string()) => items
+                .into_iter()
+                .filter_map(|i| i.as_str().map(str::to_string))
+                .collect(),
+            Some(other) => {
+                self.reject(self.key_path(key), other, "expected a list of strings");
+                Vec::new()
+            }
+        }
+    }
+
+    fn section(&mut self, key: &str) -> Option<Section> {
+        match self.map.remove(key)? {
+            Value::Object(map) => Some(Section::new(self.key_path(key), map)),
+            other => {
+                self.reject(self.key_path(key), other, "expected a table");
+                None
+            }
+        }
+    }
+
+    fn list(&mut self, key: &str) -> Vec<Section> {
+        let items = match self.map.remove(key) {
+            None => return Vec::new(),
+            Some(Value::Array(items)) => items,
+            Some(other) => {
+                self.reject(self.key_path(key), other, "expected a list of tables");
+                return Vec::new();
+            }
+        };
+        let mut out = Vec::with_capacity(items.len());
+        for (i, item) in items.into_iter().enumerate() {
+            let path = format!("{}[{}]", self.key_path(key), i);
+            match item {
+                Value::Object(map) => out.push(Section::new(path, map)),
+                other => self.reject(path, other, "expected a table"),
+            }
+        }
+        out
+    }
+
+    /// 把子表剩余字段并入当前层
+    fn close(&mut self, child: Section) {
+        let leftovers = child.finish();
+        self.unsupported.extend(leftovers);
+    }
+
+    fn finish(mut self) -> Vec<UnsupportedField> {
+        let path = self.path.clone();
+        for (k, v) in std::mem::take(&mut self.map) {
+            let p = if path.is_empty() { k } else { format!("{path}.{k}") };
+            self.reject(p, v, "not supported");
+        }
+        self.unsupported
+    }
+}
+
+pub fn import_config(root: Value, format: ConfigFormat) -> ImportReport {
+    let map = match root {
+        Value::Object(map) => map,
+        _ => Map::new(),
+    };
+    let mut root = Section::new(String::new(), map);
+    let mut cfg = FrpcConfig {
+        server_addr: root.string("serverAddr").unwrap_or_default(),
+        server_port: root.port("serverPort").unwrap_or(7000),
+        ..Default::default()
+    };
+
+    if let Some(mut auth) = root.section("auth") {
+        if let Some(method) = auth.string("method") {
+            match method.as_str() {
+                "token" => cfg.auth.method = AuthType::Token,
+                "oidc" => cfg.auth.method = AuthType::Oidc,
+                _ => auth.reject(auth.key_path("method"), method.into(), "unknown auth method"),
+            }
+        }
+        cfg.auth.token = auth.string("token").unwrap_or_default();
+        for scope in auth.strings("additionalScopes") {
+            match scope.as_str() {
+                "HeartBeats" => cfg.auth.additional_scopes.push(AuthScope::HeartBeats),
+                "NewWorkConns" => cfg.auth.additional_scopes.push(AuthScope::NewWorkConns),
+                _ => auth.reject(
+                    auth.key_path("additionalScopes"),
+                    scope.into(),
+                    "unknown auth scope",
+                ),
+            }
+        }
+        if let Some(mut oidc) = auth.section("oidc") {
+            let o = &mut cfg.auth.oidc;
+            o.client_id = oidc.string("clientID").unwrap_or_default();
+            o.client_secret = oidc.string("clientSecret").unwrap_or_default();
+            o.audience = oidc.string("audience").unwrap_or_default();
+            o.scope = oidc.string("scope").unwrap_or_default();
+            o.token_endpoint_url = oidc.string("tokenEndpointURL").unwrap_or_default();
+            if let Some(mut params) = oidc.section("additionalEndpointParams") {
+                for (k, v) in std::mem::take(&mut params.map) {
+                    match v {
+                        Value::String(v) => {
+                            o.additional_endpoint_params.insert(k, v);
+                        }
+                        other => params.reject(params.key_path(&k), other, "expected a string"),
+                    }
+                }
+                oidc.close(params);
+            }
+            auth.close(oidc);
+        }
+        cfg.switch.auth = true;
+        root.close(auth);
+    }
+
+    if let Some(mut ws) = root.section("webServer") {
+        cfg.web_server.addr = ws.string("addr").unwrap_or_else(|| "127.0.0.1".into());
+        cfg.web_server.port = ws.port("port").unwrap_or_default();
+        cfg.web_server.user = ws.string("user").unwrap_or_default();
+        cfg.web_server.password = ws.string("password").unwrap_or_default();
+        cfg.switch.web_server = true;
+        root.close(ws);
+    }
+
+    if let Some(mut tr) = root.section("transport") {
+        import_transport(&mut tr, &mut cfg);
+        cfg.switch.transport = true;
+        root.close(tr);
+    }
+
+    for sec in root.list("proxies") {
+        if let Some(p) = import_proxy(&mut root, sec) {
+            cfg.proxies.push(p);
+        }
+    }
+    for sec in root.list("visitors") {
+        if let Some(v) = import_visitor(&mut root, sec) {
+            cfg.visitors.push(v);
+        }
+    }
+
+    ImportReport {
+        format,
+        config: cfg,
+        unsupported: root.finish(),
+    }
+}
+
+fn import_transport(sec: &mut Section, cfg: &mut FrpcConfig) {
+    let t = &mut cfg.transport;
+    if let Some(protocol) = sec.string("protocol") {
+        match protocol.as_str() {
+            "tcp" => t.protocol = TransportProtocol::Tcp,
+            "kcp" => t.protocol = TransportProtocol::Kcp,
+            "quic" => t.protocol = TransportProtocol::Quic,
+            "websocket" => t.protocol = TransportProtocol::Websocket,
+            "wss" => t.protocol = TransportProtocol::Wss,
+            _ => sec.reject(sec.key_path("protocol"), protocol.into(), "unknown protocol"),
+        }
+    }
+    if let Some(n) = sec.uint("poolCount") {
+        t.pool_count = n;
+    }
+    if let Some(b) = sec.bool("tcpMux") {
+        t.tcp_mux = b;
+    }
+    if let Some(n) = sec.int("heartbeatInterval") {
+        t.heartbeat_interval = n;
+    }
+    if let Some(n) = sec.int("heartbeatTimeout") {
+        t.heartbeat_timeout = n;
+    }
+    if let Some(s) = sec.string("proxyURL") {
+        t.proxy_url = s;
+    }
+    if let Some(n) = sec.int("dialServerTimeout") {
+        t.dial_server_timeout = n;
+    }
+    if let Some(mut tls) = sec.section("tls") {
+        if let Some(b) = tls.bool("enable") {
+            t.tls.enable = b;
+        }
+        t.tls.cert_file = tls.string("certFile").unwrap_or_default();
+        t.tls.key_file = tls.string("keyFile").unwrap_or_default();
+        t.tls.trusted_ca_file = tls.string("trustedCaFile").unwrap_or_default();
+        t.tls.server_name = tls.string("serverName").unwrap_or_default();
+        sec.close(tls);
+    }
+}
+
+fn import_proxy(parent: &mut Section, mut sec: Section) -> Option<Proxy> {
+    let ty = sec.string("type").unwrap_or_else(|| "tcp".into());
+    let mut common = ProxyCommon {
+        id: gen_id(),
+        name: sec.string("name").unwrap_or_default(),
+        enable: true,
+        local_ip: sec.string("localIP").unwrap_or_else(|| "127.0.0.1".into()),
+        local_port: sec.port("localPort").unwrap_or_default(),
+        quota: Default::default(),
+        rate_limit: Default::default(),
+        proxy_protocol: false,
+    };
+    let proxy = match ty.as_str() {
+        "tcp" => Proxy::Tcp(TcpProxy {
+            common,
+            remote_port: sec.port("remotePort").unwrap_or_default(),
+        }),
+        "udp" => Proxy::Udp(UdpProxy {
+            common,
+            remote_port: sec.port("remotePort").unwrap_or_default(),
+        }),
+        "http" => {
+            let http_user = sec.string("httpUser").unwrap_or_default();
+            let custom_domains = sec.strings("customDomains");
+            let subdomain = sec.string("subdomain").unwrap_or_default();
+            Proxy::Http(HttpProxy {
+                switch: HttpSwitch {
+                    domain: domain_type(&subdomain, &custom_domains),
+                    auth: !http_user.is_empty(),
+                },
+                common,
+                subdomain,
+                custom_domains,
+                locations: sec.strings("locations"),
+                http_user,
+                http_password: sec.string("httpPassword").unwrap_or_default(),
+            })
+        }
+        "https" => {
+            let custom_domains = sec.strings("customDomains");
+            let subdomain = sec.string("subdomain").unwrap_or_default();
+            let plugin = sec
+                .section("plugin")
+                .and_then(|p| import_https_plugin(&mut sec, p, &mut common));
+            Proxy::Https(HttpsProxy {
+                switch: HttpSwitch {
+                    domain: domain_type(&subdomain, &custom_domains),
+                    auth: false,
+                },
+                common,
+                subdomain,
+                custom_domains,
+                plugin,
+            })
+        }
+        "stcp" | "sudp" | "xtcp" => {
+            let p = SecretProxy {
+                common,
+                secret_key: sec.string("secretKey").unwrap_or_default(),
+                allow_users: sec.strings("allowUsers"),
+            };
+            match ty.as_str() {
+                "stcp" => Proxy::Stcp(p),
+                "sudp" => Proxy::Sudp(p),
+                _ => Proxy::Xtcp(p),
+            }
+        }
+        _ => {
+            let path = sec.path.clone();
+            sec.map.insert("type".into(), ty.into());
+            parent.reject(path, Value::Object(sec.map), "unsupported proxy type");
+            return None;
+        }
+    };
+    parent.close(sec);
+    Some(proxy)
+}
+
+// 插件的 localAddr 回填到代理本地地址上，导出时再挪回插件
+fn import_https_plugin(
+    parent: &mut Section,
+    mut sec: Section,
+    common: &mut ProxyCommon,
+) -> Option<HttpsPlugin> {
+    let ty = sec.string("type").unwrap_or_default();
+    if !matches!(ty.as_str(), "https2http" | "https2https") {
+        let path = sec.path.clone();
+        sec.map.insert("type".into(), ty.into());
+        parent.reject(path, Value::Object(sec.map), "unsupported plugin type");
+        return None;
+    }
+    if let Some(addr) = sec.string("localAddr") {
+        match addr.rsplit_once(':').map(|(h, p)| (h, p.parse::<u16>())) {
+            Some((host, Ok(port))) => {
+                common.local_ip = host.to_string();
+                common.local_port = port;
+            }
+            _ => sec.reject(sec.key_path("localAddr"), addr.into(), "expected host:port"),
+        }
+    }
+    let mut request_headers = BTreeMap::new();
+    if let Some(mut headers) = sec.section("requestHeaders") {
+        if let Some(mut set) = headers.section("set") {
+            for (k, v) in std::mem::take(&mut set.map) {
+                match v {
+                    Value::String(v) => {
+                        request_headers.insert(k, v);
+                    }
+                    other => set.reject(set.key_path(&k), other, "expected a string"),
+                }
+            }
+            headers.close(set);
+        }
+        sec.close(headers);
+    }
+    let options = HttpsPluginOptions {
+        crt_path: sec.string("crtPath").unwrap_or_default(),
+        key_path: sec.string("keyPath").unwrap_or_default(),
+        host_header_rewrite: sec.string("hostHeaderRewrite").unwrap_or_default(),
+        request_headers,
+    };
+    parent.close(sec);
+    Some(match ty.as_str() {
+        "https2http" => HttpsPlugin::Https2http(options),
+        _ => HttpsPlugin::Https2https(options),
+    })
+}
+
+fn domain_type(subdomain: &str, custom_domains: &[String]) -> DomainType {
+    if subdomain.is_empty() && !custom_domains.is_empty() {
+        DomainType::Custom
+    } else {
+        DomainType::Sub
+    }
+}
+
+fn import_visitor(parent: &mut Section, mut sec: Section) -> Option<Visitor> {
+    let ty = sec.string("type").unwrap_or_default();
+    if !matches!(ty.as_str(), "stcp" | "sudp" | "xtcp") {
+        let path = sec.path.clone();
+        sec.map.insert("type".into(), ty.into());
+        parent.reject(path, Value::Object(sec.map), "unsupported visitor type");
+        return None;
+    }
+    let common = VisitorCommon {
+        id: gen_id(),
+        name: sec.string("name").unwrap_or_default(),
+        enable: true,
+        server_user: sec.string("serverUser").unwrap_or_default(),
+        server_name: sec.string("serverName").unwrap_or_default(),
+        secret_key: sec.string("secretKey").unwrap_or_default(),
+        bind_addr: sec.string("bindAddr").unwrap_or_default(),
+        bind_port: sec
+            .int("bindPort")
+            .and_then(|n| i32::try_from(n).ok())
+            .unwrap_or_default(),
+    };
+    let visitor = match ty.as_str() {
+        "stcp" => Visitor::Stcp(common),
+        "sudp" => Visitor::Sudp(common),
+        _ => Visitor::Xtcp(XtcpVisitor {
+            common,
+            protocol: sec.string("protocol").unwrap_or_default(),
+            keep_tunnel_open: sec.bool("keepTunnelOpen").unwrap_or_default(),
+            max_retries_an_hour: sec.uint("maxRetriesAnHour").unwrap_or_default(),
+            min_retry_interval: sec.uint("minRetryInterval").unwrap_or_default(),
+            fallback_to: sec.string("fallbackTo").unwrap_or_default(),
+            fallback_timeout_ms: sec.uint("fallbackTimeoutMs").unwrap_or_default(),
+        }),
+    };
+    parent.close(sec);
+    Some(visitor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::config_format::parse_to_value;
+    use std::path::Path;
+
+    fn load(name: &str, content: &str) -> ImportReport {
+        let format = ConfigFormat::detect(Path::new(name), content);
+        import_config(parse_to_value(content, format).unwrap(), format)
+    }
+
+    // 四种格式的样例描述的是同一份配置
+    fn assert_common(report: &ImportReport) {
+        let cfg = &report.config;
+        assert_eq!(cfg.server_addr, "frp.example.com");
+        assert_eq!(cfg.server_port, 7001);
+        assert_eq!(cfg.auth.method, AuthType::Token);
+        assert_eq!(cfg.auth.token, "s3cret");
+        assert!(cfg.switch.auth);
+        assert_eq!(cfg.transport.protocol, TransportProtocol::Kcp);
+        assert_eq!(cfg.transport.pool_count, 5);
+
+        let names: Vec<_> = cfg
+            .proxies
+            .iter()
+            .map(|p| p.common().name.as_str())
+            .collect();
+        assert_eq!(names, ["ssh", "web", "secure"]);
+        match &cfg.proxies[0] {
+            Proxy::Tcp(p) => {
+                assert_eq!(p.common.local_ip, "127.0.0.1");
+                assert_eq!(p.common.local_port, 22);
+                assert_eq!(p.remote_port, 6000);
+            }
+            other => panic!("expected tcp, got {}", other.kind()),
+        }
+        match &cfg.proxies[1] {
+            Proxy::Http(p) => {
+                assert_eq!(p.common.local_port, 8080);
+                assert_eq!(p.custom_domains, ["web.example.com"]);
+                assert_eq!(p.locations, ["/api"]);
+                assert_eq!(p.switch.domain, DomainType::Custom);
+            }
+            other => panic!("expected http, got {}", other.kind()),
+        }
+        match &cfg.proxies[2] {
+            Proxy::Https(p) => {
+                // 插件的 localAddr 回填到代理上
+                assert_eq!(p.common.local_ip, "127.0.0.1");
+                assert_eq!(p.common.local_port, 8443);
+                match &p.plugin {
+                    Some(HttpsPlugin::Https2http(o)) => {
+                        assert_eq!(o.crt_path, "./server.crt");
+                        assert_eq!(o.key_path, "./server.key");
+                    }
+                    other => panic!("expected https2http plugin, got {other:?}"),
+                }
+            }
+            other => panic!("expected https, got {}", other.kind()),
+        }
+
+        assert_eq!(cfg.visitors.len(), 1);
+        match &cfg.visitors[0] {
+            Visitor::Stcp(v) => {
+                assert_eq!(v.name, "ssh-visitor");
+                assert_eq!(v.server_name, "ssh");
+                assert_eq!(v.secret_key, "abc");
+                assert_eq!(v.bind_port, 9000);
+            }
+            other => panic!("expected stcp visitor, got {other:?}"),
+        }
+    }
+
+    fn unsupported(report: &ImportReport) -> Vec<(&str, &str)> {
+        let mut out: Vec<_> = report
+            .unsupported
+            .iter()
+            .map(|u| (u.path.as_str(), u.reason.as_str()))
+            .collect();
+        out.sort();
+        out
+    }
+
+    const V1_UNSUPPORTED: [(&str, &str); 4] = [
+        ("loginFailExit", "not supported"),
+        ("proxies[0].transport", "not supported"),
+        ("proxies[3]", "unsupported proxy type"),
+        ("transport.bandwidthLimit", "not supported"),
+    ];
+
+    #[test]
+    fn imports_toml() {
+        let r = load(
+            "frpc.toml",
+            include_str!("../../tests/fixtures/import/frpc.toml"),
+        );
+        assert_eq!(r.format, ConfigFormat::Toml);
+        assert_common(&r);
+        assert_eq!(unsupported(&r), V1_UNSUPPORTED);
+    }
+
+    #[test]
+    fn imports_yaml() {
+        let r = load(
+            "frpc.yaml",
+            include_str!("../../tests/fixtures/import/frpc.yaml"),
+        );
+        assert_eq!(r.format, ConfigFormat::Yaml);
+        assert_common(&r);
+        assert_eq!(unsupported(&r), V1_UNSUPPORTED);
+    }
+
+    #[test]
+    fn imports_json() {
+        let r = load(
+            "frpc.json",
+            include_str!("../../tests/fixtures/import/frpc.json"),
+        );
+        assert_eq!(r.format, ConfigFormat::Json);
+        assert_common(&r);
+        assert_eq!(unsupported(&r), V1_UNSUPPORTED);
+    }
+
+    #[test]
+    fn imports_legacy_ini() {
+        let r = load(
+            "frpc.ini",
+            include_str!("../../tests/fixtures/import/frpc.ini"),
+        );
+        assert_eq!(r.format, ConfigFormat::Ini);
+        assert_common(&r);
+        // 旧版不认识的键保持原名
+        assert_eq!(
+            unsupported(&r),
+            [
+                ("login_fail_exit", "not supported"),
+                ("proxies[0].use_encryption", "not supported"),
+                ("proxies[3]", "unsupported proxy type"),
+            ]
+        );
+        let mux = &r
+            .unsupported
+            .iter()
+            .find(|u| u.path == "proxies[3]")
+            .unwrap()
+            .value;
+        assert_eq!(mux["type"], "tcpmux");
+        assert_eq!(mux["multiplexer"], "httpconnect");
+    }
+
+    #[test]
+    fn rejects_wrong_value_types() {
+        let root = serde_json::json!({
+            "serverAddr": 1,
+            "serverPort": 70000,
+            "proxies": [{ "name": "a", "type": "tcp", "localPort": "22" }, 5],
+        });
+        let r = import_config(root, ConfigFormat::Json);
+        assert_eq!(r.config.server_addr, "");
+        assert_eq!(r.config.server_port, 7000);
+        assert_eq!(r.config.proxies.len(), 1);
+        assert_eq!(
+            unsupported(&r),
+            [
+                ("proxies[0].localPort", "expected a port number"),
+                ("proxies[1]", "expected a table"),
+                ("serverAddr", "expected a string"),
+                ("serverPort", "expected a port number"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,36 @@
+use crate::domain::config::FrpcConfig;
+use crate::domain::proxy::{empty_string_as_default_id, gen_id};
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_PROFILE_NAME: &str = "默认";
+
+// 一个 profile 对应一台 frps：各自独立的服务端、鉴权、传输设置与代理列表
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    #[serde(default = "gen_id", deserialize_with = "empty_string_as_default_id")]
+    pub id: String,
+    pub name: String,
+    pub config: FrpcConfig,
+}
+
+impl Profile {
+    pub fn new(name: &str, config: FrpcConfig) -> Self {
+        Self {
+            id: gen_id(),
+            name: name.to_string(),
+            config,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileSummary {
+    pub id: String,
+    pub name: String,
+    pub server_addr: String,
+    pub server_port: u16,
+    pub proxy_count: usize,
+    pub active: bool,
+}
@@ -1,5 +1,7 @@
+use super::traffic::{RateLimit, TrafficQuota};
 use super::types::DomainType;
 use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
 use std::ops::{Deref, DerefMut};
 use uuid::Uuid;
 
@@ -9,24 +11,84 @@ pub struct HttpSwitch {
     pub auth: bool,
 }
 
+impl HttpSwitch {
+    // 子域名与自定义域名二选一，只导出开关选中的那一组
+    fn pick_domains(
+        &self,
+        subdomain: &str,
+        custom_domains: &[String],
+    ) -> (Option<String>, Vec<String>) {
+        match self.domain {
+            DomainType::Sub => (
+                Some(subdomain.to_string()).filter(|s| !s.is_empty()),
+                Vec::new(),
+            ),
+            DomainType::Custom => (None, custom_domains.to_vec()),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum Proxy {
+    Tcp(TcpProxy),
+    Udp(UdpProxy),
     Http(HttpProxy),
     Https(HttpsProxy),
+    Stcp(SecretProxy),
+    Sudp(SecretProxy),
+    Xtcp(SecretProxy),
 }
 
 impl Proxy {
+    /// 按类型名（tcp / udp / http / https / stcp / sudp / xtcp）创建空白代理
+    pub fn with_kind(kind: &str) -> Option<Self> {
+        Some(match kind {
+            "tcp" => Proxy::Tcp(TcpProxy::default()),
+            "udp" => Proxy::Udp(UdpProxy::default()),
+            "http" => Proxy::Http(HttpProxy::default()),
+            "https" => Proxy::Https(HttpsProxy::default()),
+            "stcp" => Proxy::Stcp(SecretProxy::default()),
+            "sudp" => Proxy::Sudp(SecretProxy::default()),
+            "xtcp" => Proxy::Xtcp(SecretProxy::default()),
+            _ => return None,
+        })
+    }
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Proxy::Tcp(_) => "tcp",
+            Proxy::Udp(_) => "udp",
+            Proxy::Http(_) => "http",
+            Proxy::Https(_) => "https",
+            Proxy::Stcp(_) => "stcp",
+            Proxy::Sudp(_) => "sudp",
+            Proxy::Xtcp(_) => "xtcp",
+        }
+    }
     pub fn common(&self) -> &ProxyCommon {
         match self {
+            Proxy::Tcp(p) => &p.common,
+            Proxy::Udp(p) => &p.common,
             Proxy::Http(p) => &p.common,
             Proxy::Https(p) => &p.common,
+            Proxy::Stcp(p) | Proxy::Sudp(p) | Proxy::Xtcp(p) => &p.common,
+        }
+    }
+    /// https 插件自己连本地服务，xtcp 的打洞连接没有来源地址，UDP 类不经 TCP 转发，都收不到 PROXY protocol 头
+    pub fn supports_proxy_protocol(&self) -> bool {
+        match self {
+            Proxy::Https(h) => h.plugin.is_none(),
+            Proxy::Udp(_) | Proxy::Sudp(_) | Proxy::Xtcp(_) => false,
+            _ => true,
         }
     }
     pub fn common_mut(&mut self) -> &mut ProxyCommon {
         match self {
+            Proxy::Tcp(p) => &mut p.common,
+            Proxy::Udp(p) => &mut p.common,
             Proxy::Http(p) => &mut p.common,
             Proxy::Https(p) => &mut p.common,
+            Proxy::Stcp(p) | Proxy::Sudp(p) | Proxy::Xtcp(p) => &mut p.common,
         }
     }
 }
@@ -46,18 +108,23 @@ impl DerefMut for Proxy {
 #[derive(Serialize)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum ProxyExport {
+    Tcp(TcpProxyExport),
+    Udp(UdpProxyExport),
     Http(HttpProxyExport),
     Https(HttpsProxyExport),
+    Stcp(SecretProxyExport),
+    Sudp(SecretProxyExport),
+    Xtcp(SecretProxyExport),
 }
 
 // 生成默认 id
-fn gen_id() -> String {
+pub(crate) fn gen_id() -> String {
     Uuid::new_v4().to_string()
 }
 
 // 如果是空串或只包含空白，则用默认 id；
 // 如果字段缺失，依赖 #[serde(default = "gen_id")] 兜底。
-fn empty_string_as_default_id<'de, D>(de: D) -> Result<String, D::Error>
+pub(crate) fn empty_string_as_default_id<'de, D>(de: D) -> Result<String, D::Error>
 where
     D: Deserializer<'de>,
 {
@@ -79,14 +146,78 @@ pub struct ProxyCommon {
     pub local_ip: String,
     #[serde(rename = "localPort", default)]
     pub local_port: u16,
+    // 以下只在桌面端的 shim 中生效，不写入 frpc 配置
+    #[serde(default)]
+    pub quota: TrafficQuota,
+    #[serde(rename = "rateLimit", default)]
+    pub rate_limit: RateLimit,
+    // 让 frpc 在每个连接前发 PROXY protocol v2 头，shim 从中取出真实访问者地址后剥掉；
+    // 只对经 shim 的 TCP 连接有效，frpc 不发头时连接会被拒绝
+    #[serde(rename = "proxyProtocol", default)]
+    pub proxy_protocol: bool,
 }
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ProxyCommonExport {
     name: String,
-    local_ip: String,
-    local_port: u16,
+    #[serde(rename = "localIP", skip_serializing_if = "Option::is_none")]
+    local_ip: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    local_port: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    transport: Option<ProxyTransportExport>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxyTransportExport {
+    proxy_protocol_version: &'static str,
+}
+
+impl ProxyCommonExport {
+    // 挂了插件时由插件去连本地服务，本地地址挪到 plugin.localAddr
+    fn take_local_addr(&mut self) -> String {
+        format!(
+            "{}:{}",
+            self.local_ip.take().unwrap_or_default(),
+            self.local_port.take().unwrap_or_default()
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TcpProxy {
+    #[serde(flatten)]
+    pub common: ProxyCommon,
+    #[serde(default)]
+    pub remote_port: u16,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TcpProxyExport {
+    #[serde(flatten)]
+    common: ProxyCommonExport,
+    remote_port: u16,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UdpProxy {
+    #[serde(flatten)]
+    pub common: ProxyCommon,
+    #[serde(default)]
+    pub remote_port: u16,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UdpProxyExport {
+    #[serde(flatten)]
+    common: ProxyCommonExport,
+    remote_port: u16,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -107,11 +238,15 @@ pub struct HttpProxy {
 pub struct HttpProxyExport {
     #[serde(flatten)]
     pub common: ProxyCommonExport,
-    pub subdomain: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subdomain: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub custom_domains: Vec<String>,
     pub locations: Vec<String>,
-    pub http_user: String,
-    pub http_password: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_user: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_password: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -119,6 +254,14 @@ pub struct HttpProxyExport {
 pub struct HttpsProxy {
     #[serde(flatten)]
     pub common: ProxyCommon,
+    #[serde(default)]
+    pub subdomain: String,
+    #[serde(default)]
+    pub custom_domains: Vec<String>,
+    #[serde(default)]
+    pub switch: HttpSwitch,
+    #[serde(default)]
+    pub plugin: Option<HttpsPlugin>,
 }
 
 #[derive(Serialize)]
@@ -126,68 +269,252 @@ pub struct HttpsProxy {
 pub struct HttpsProxyExport {
     #[serde(flatten)]
     common: ProxyCommonExport,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    subdomain: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    custom_domains: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    plugin: Option<HttpsPluginExport>,
+}
+
+// 客户端插件：在本机终结 TLS，再以 http / https 转发给本地服务
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum HttpsPlugin {
+    Https2http(HttpsPluginOptions),
+    Https2https(HttpsPluginOptions),
+}
+
+impl HttpsPlugin {
+    pub fn options(&self) -> &HttpsPluginOptions {
+        match self {
+            HttpsPlugin::Https2http(o) | HttpsPlugin::Https2https(o) => o,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpsPluginOptions {
+    #[serde(default)]
+    pub crt_path: String,
+    #[serde(default)]
+    pub key_path: String,
+    #[serde(default)]
+    pub host_header_rewrite: String,
+    #[serde(default)]
+    pub request_headers: BTreeMap<String, String>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum HttpsPluginExport {
+    Https2http(HttpsPluginOptionsExport),
+    Https2https(HttpsPluginOptionsExport),
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpsPluginOptionsExport {
+    local_addr: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    crt_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    key_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    host_header_rewrite: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_headers: Option<HeaderOperations>,
+}
+
+#[derive(Serialize)]
+pub struct HeaderOperations {
+    set: BTreeMap<String, String>,
+}
+
+fn to_plugin_export(plugin: &HttpsPlugin, local_addr: String) -> HttpsPluginExport {
+    let o = plugin.options();
+    let non_empty = |s: &String| Some(s.clone()).filter(|s| !s.is_empty());
+    let options = HttpsPluginOptionsExport {
+        local_addr,
+        crt_path: non_empty(&o.crt_path),
+        key_path: non_empty(&o.key_path),
+        host_header_rewrite: non_empty(&o.host_header_rewrite),
+        request_headers: (!o.request_headers.is_empty()).then(|| HeaderOperations {
+            set: o.request_headers.clone(),
+        }),
+    };
+    match plugin {
+        HttpsPlugin::Https2http(_) => HttpsPluginExport::Https2http(options),
+        HttpsPlugin::Https2https(_) => HttpsPluginExport::Https2https(options),
+    }
+}
+
+// stcp / sudp / xtcp 共用：访问端需要持有相同的 secretKey
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretProxy {
+    #[serde(flatten)]
+    pub common: ProxyCommon,
+    #[serde(default)]
+    pub secret_key: String,
+    #[serde(default)]
+    pub allow_users: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretProxyExport {
+    #[serde(flatten)]
+    common: ProxyCommonExport,
+    secret_key: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    allow_users: Vec<String>,
+}
+
+impl SecretProxyExport {
+    fn new(common: ProxyCommonExport, p: &SecretProxy) -> Self {
+        Self {
+            common,
+            secret_key: p.secret_key.clone(),
+            allow_users: p.allow_users.clone(),
+        }
+    }
+}
+
+/// instance 为 None 时按真实本地地址导出（给用户另存的配置），否则经由 shim
+pub fn to_proxy_export(proxy: &Proxy, instance: Option<&FrpcInstance>) -> Option<ProxyExport> {
+    let proxy_protocol = proxy.proxy_protocol && proxy.supports_proxy_protocol();
+    let common = match (proxy, instance) {
+        (_, None) => ProxyCommonExport {
+            name: proxy.name.clone(),
+            local_ip: Some(proxy.local_ip.clone()),
+            local_port: Some(proxy.local_port),
+            transport: None,
+        },
+        (Proxy::Udp(_) | Proxy::Sudp(_), Some(instance)) => {
+            shim_common_export(proxy.common(), instance, ShimProtocol::Udp, false)?
+        }
+        (_, Some(instance)) => {
+            shim_common_export(proxy.common(), instance, ShimProtocol::Tcp, proxy_protocol)?
+        }
+    };
+    match proxy {
+        Proxy::Tcp(t) => Some(ProxyExport::Tcp(TcpProxyExport {
+            common,
+            remote_port: t.remote_port,
+        })),
+        Proxy::Udp(u) => Some(ProxyExport::Udp(UdpProxyExport {
+            common,
+            remote_port: u.remote_port,
+        })),
+        Proxy::Http(h) => {
+            let (subdomain, custom_domains) =
+                h.switch.pick_domains(&h.subdomain, &h.custom_domains);
+            Some(ProxyExport::Http(HttpProxyExport {
+                common,
+                subdomain,
+                custom_domains,
+                locations: h.locations.clone(),
+                http_user: h.switch.auth.then(|| h.http_user.clone()),
+                http_password: h.switch.auth.then(|| h.http_password.clone()),
+            }))
+        }
+        Proxy::Https(h) => {
+            let mut common = common;
+            let (subdomain, custom_domains) =
+                h.switch.pick_domains(&h.subdomain, &h.custom_domains);
+            let plugin = h
+                .plugin
+                .as_ref()
+                .map(|p| to_plugin_export(p, common.take_local_addr()));
+            Some(ProxyExport::Https(HttpsProxyExport {
+                common,
+                subdomain,
+                custom_domains,
+                plugin,
+            }))
+        }
+        Proxy::Stcp(p) => Some(ProxyExport::Stcp(SecretProxyExport::new(common, p))),
+        Proxy::Sudp(p) => Some(ProxyExport::Sudp(SecretProxyExport::new(common, p))),
+        Proxy::Xtcp(p) => Some(ProxyExport::Xtcp(SecretProxyExport::new(common, p))),
+    }
 }
 
-pub fn to_proxy_export(proxy: &Proxy, proc_state: &FrpcProcState) -> Option<ProxyExport> {
-    let (listener, addr) = reserve_listener_loopback().expect("reserve_listener_loopback failed");
-    let (id, target_addr_str) = match proxy {
-        Proxy::Http(h) => (
-            h.common.id.clone(),
-            format!("{}:{}", h.common.local_ip, h.common.local_port),
-        ),
-        Proxy::Https(h) => (
-            h.common.id.clone(),
-            format!("{}:{}", h.common.local_ip, h.common.local_port),
-        ),
+// 为代理预留一个本地 shim 端口，frpc 连 shim，shim 再转发到真实的 local_ip:local_port
+// 已有同目标的监听时沿用原端口，热重载时 frpc 看到的 localPort 不变
+// 本地地址不合法或端口预留失败时跳过该代理（启动前的校验会先拦下这类配置）
+fn shim_common_export(
+    common: &ProxyCommon,
+    instance: &FrpcInstance,
+    protocol: ShimProtocol,
+    proxy_protocol: bool,
+) -> Option<ProxyCommonExport> {
+    let ip: IpAddr = common.local_ip.parse().ok()?;
+    let target = SocketAddr::new(ip, common.local_port);
+    let reuse = instance
+        .shim_routes
+        .lock()
+        .ok()?
+        .get(&common.id)
+        .filter(|r| {
+            r.target == target
+                && r.protocol == protocol
+                && r.proxy_protocol == proxy_protocol
+                && !r.task.is_finished()
+        })
+        .map(|r| r.listen);
+    let (listener, addr) = match reuse {
+        Some(addr) => (None, addr),
+        None => {
+            let (listener, addr) = reserve_loopback(protocol)
+                .map_err(|e| eprintln!("[shim:{}] reserve listener failed: {e}", common.id))
+                .ok()?;
+            (Some(listener), addr)
+        }
     };
-    let target: SocketAddr = target_addr_str
-        .parse()
-        .expect("invalid local_ip/local_port");
 
     {
-        let mut guard = proc_state
-            .proxy_specs
-            .lock()
-            .expect("lock proxy_specs failed");
+        let mut guard = instance.proxy_specs.lock().ok()?;
         guard.push(ProxySpec {
-            id,
+            id: common.id.clone(),
             listener,
             target,
+            proxy_protocol,
+            rate_limit: common.rate_limit,
         });
     }
-    match proxy {
-        Proxy::Http(h) => Some(ProxyExport::Http(HttpProxyExport {
-            common: ProxyCommonExport {
-                name: h.common.name.clone(),
-                local_ip: addr.ip().to_string(),
-                local_port: addr.port(),
-            },
-            subdomain: h.subdomain.clone(),
-            custom_domains: h.custom_domains.clone(),
-            locations: h.locations.clone(),
-            http_user: h.http_user.clone(),
-            http_password: h.http_password.clone(),
-        })),
-        Proxy::Https(h) => Some(ProxyExport::Https(HttpsProxyExport {
-            common: ProxyCommonExport {
-                name: h.common.name.clone(),
-                local_ip: addr.ip().to_string(),
-                local_port: addr.port(),
-            },
-        })),
-    }
+    Some(ProxyCommonExport {
+        name: common.name.clone(),
+        local_ip: Some(addr.ip().to_string()),
+        local_port: Some(addr.port()),
+        transport: proxy_protocol.then_some(ProxyTransportExport {
+            proxy_protocol_version: "v2",
+        }),
+    })
 }
 
-use crate::services::local_proxy::ProxySpec;
-use crate::state::FrpcProcState;
-use std::net::{SocketAddr, TcpListener as StdTcpListener};
-use tokio::net::TcpListener;
+use crate::services::local_proxy::{ProxySpec, ShimListener, ShimProtocol};
+use crate::state::FrpcInstance;
+use std::net::{IpAddr, SocketAddr, TcpListener as StdTcpListener, UdpSocket as StdUdpSocket};
+use tokio::net::{TcpListener, UdpSocket};
 
-fn reserve_listener_loopback() -> std::io::Result<(TcpListener, SocketAddr)> {
+fn reserve_loopback(protocol: ShimProtocol) -> std::io::Result<(ShimListener, SocketAddr)> {
     // 让操作系统选择一个空闲端口
-    let std_tcp_listener = StdTcpListener::bind(("127.0.0.1", 0))?;
-    std_tcp_listener.set_nonblocking(true)?;
-    let addr = std_tcp_listener.local_addr()?;
-    let listener = TcpListener::from_std(std_tcp_listener)?;
-    Ok((listener, addr))
+    match protocol {
+        ShimProtocol::Tcp => {
+            let std_tcp_listener = StdTcpListener::bind(("127.0.0.1", 0))?;
+            std_tcp_listener.set_nonblocking(true)?;
+            let addr = std_tcp_listener.local_addr()?;
+            let listener = TcpListener::from_std(std_tcp_listener)?;
+            Ok((ShimListener::Tcp(listener), addr))
+        }
+        ShimProtocol::Udp => {
+            let std_udp_socket = StdUdpSocket::bind(("127.0.0.1", 0))?;
+            std_udp_socket.set_nonblocking(true)?;
+            let addr = std_udp_socket.local_addr()?;
+            let socket = UdpSocket::from_std(std_udp_socket)?;
+            Ok((ShimListener::Udp(socket), addr))
+        }
+    }
 }
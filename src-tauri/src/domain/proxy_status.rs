@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+// 与 frpc admin /api/status 中的 status 字段对应
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProxyPhase {
+    New,
+    WaitStart,
+    StartError,
+    Running,
+    CheckFailed,
+    Closed,
+    #[default]
+    Unknown,
+}
+
+impl ProxyPhase {
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "new" => ProxyPhase::New,
+            "wait start" => ProxyPhase::WaitStart,
+            "start error" => ProxyPhase::StartError,
+            "running" => ProxyPhase::Running,
+            "check failed" => ProxyPhase::CheckFailed,
+            "closed" => ProxyPhase::Closed,
+            _ => ProxyPhase::Unknown,
+        }
+    }
+}
+
+/// 单个代理在 frps 上的注册状态，id 对应 ProxyCommon.id
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxyStatus {
+    pub id: String,
+    pub name: String,
+    pub phase: ProxyPhase,
+    pub error: String,
+    pub remote_addr: String,
+}
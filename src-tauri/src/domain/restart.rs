@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartMode {
+    #[default]
+    Never,
+    OnFailure,
+    Always,
+}
+
+// frpc 退出后的自动重启策略；延迟按 initialDelayMs * 2^(n-1) 增长，封顶 maxDelayMs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestartPolicy {
+    #[serde(default)]
+    pub mode: RestartMode,
+    #[serde(default = "default_initial_delay")]
+    pub initial_delay_ms: u64,
+    #[serde(default = "default_max_delay")]
+    pub max_delay_ms: u64,
+    // 0 表示不限次数
+    #[serde(default)]
+    pub max_retries: u32,
+    // 在延迟上额外叠加 [0, jitterMs] 的随机值，避免多台机器同时重连
+    #[serde(default)]
+    pub jitter_ms: u64,
+}
+
+fn default_initial_delay() -> u64 {
+    1_000
+}
+
+fn default_max_delay() -> u64 {
+    60_000
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            mode: RestartMode::Never,
+            initial_delay_ms: default_initial_delay(),
+            max_delay_ms: default_max_delay(),
+            max_retries: 0,
+            jitter_ms: 0,
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// code 为 None 表示被信号杀死或根本没能启动，视为失败
+    pub fn should_restart(&self, code: Option<i32>) -> bool {
+        match self.mode {
+            RestartMode::Never => false,
+            RestartMode::OnFailure => code != Some(0),
+            RestartMode::Always => true,
+        }
+    }
+
+    pub fn exhausted(&self, attempt: u32) -> bool {
+        self.max_retries != 0 && attempt >= self.max_retries
+    }
+
+    /// 第 attempt 次（从 1 开始）重试前的等待时间
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        let shift = attempt.saturating_sub(1).min(32);
+        let base = self
+            .initial_delay_ms
+            .saturating_mul(1u64 << shift)
+            .min(self.max_delay_ms.max(self.initial_delay_ms));
+        let jitter = fastrand::u64(0..=self.jitter_ms);
+        Duration::from_millis(base.saturating_add(jitter))
+    }
+}
+
+/// 运行期的重启状态，随 frpc_status 一起返回
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RestartState {
+    pub attempt: u32,
+    pub last_exit_code: Option<i32>,
+    // 下次重试的时间（unix 毫秒），没有待执行的重试时为空
+    pub next_retry_at: Option<i64>,
+    pub gave_up: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(initial: u64, max: u64, retries: u32) -> RestartPolicy {
+        RestartPolicy {
+            mode: RestartMode::Always,
+            initial_delay_ms: initial,
+            max_delay_ms: max,
+            max_retries: retries,
+            jitter_ms: 0,
+        }
+    }
+
+    #[test]
+    fn delay_doubles_up_to_max() {
+        let p = policy(1_000, 10_000, 0);
+        let delays: Vec<u64> = (1..=6).map(|n| p.delay_for(n).as_millis() as u64).collect();
+        assert_eq!(delays, [1_000, 2_000, 4_000, 8_000, 10_000, 10_000]);
+        // attempt 0 与第一次相同
+        assert_eq!(p.delay_for(0), Duration::from_millis(1_000));
+        // 次数很大时不溢出
+        assert_eq!(p.delay_for(u32::MAX), Duration::from_millis(10_000));
+    }
+
+    #[test]
+    fn max_below_initial_keeps_initial() {
+        let p = policy(5_000, 1_000, 0);
+        assert_eq!(p.delay_for(3), Duration::from_millis(5_000));
+    }
+
+    #[test]
+    fn jitter_stays_in_range() {
+        let mut p = policy(1_000, 1_000, 0);
+        p.jitter_ms = 50;
+        for _ in 0..200 {
+            let d = p.delay_for(1).as_millis() as u64;
+            assert!((1_000..=1_050).contains(&d), "{d}");
+        }
+        p.initial_delay_ms = u64::MAX;
+        p.max_delay_ms = u64::MAX;
+        p.jitter_ms = u64::MAX;
+        assert_eq!(p.delay_for(1), Duration::from_millis(u64::MAX));
+    }
+
+    #[test]
+    fn exhausted_after_max_retries() {
+        let unlimited = policy(1_000, 1_000, 0);
+        assert!(!unlimited.exhausted(0));
+        assert!(!unlimited.exhausted(u32::MAX));
+
+        let three = policy(1_000, 1_000, 3);
+        assert!(!three.exhausted(2));
+        assert!(three.exhausted(3));
+        assert!(three.exhausted(4));
+    }
+
+    #[test]
+    fn should_restart_by_mode() {
+        let mut p = RestartPolicy::default();
+        let codes = [Some(0), Some(1), None];
+        let cases = [
+            (RestartMode::Never, [false, false, false]),
+            (RestartMode::OnFailure, [false, true, true]),
+            (RestartMode::Always, [true, true, true]),
+        ];
+        for (mode, want) in cases {
+            p.mode = mode;
+            let got = codes.map(|c| p.should_restart(c));
+            assert_eq!(got, want, "{mode:?}");
+        }
+    }
+}
@@ -0,0 +1,487 @@
+use chrono::{
+    DateTime, Datelike, Duration, Local, LocalResult, Months, NaiveDate, NaiveTime, TimeZone,
+    Timelike,
+};
+use serde::{Deserialize, Serialize};
+
+/// 流量汇总的粒度；小时与天按本地时间对齐
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrafficSpan {
+    Minute,
+    Hour,
+    Day,
+}
+
+impl TrafficSpan {
+    pub const ALL: [TrafficSpan; 3] = [TrafficSpan::Minute, TrafficSpan::Hour, TrafficSpan::Day];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TrafficSpan::Minute => "minute",
+            TrafficSpan::Hour => "hour",
+            TrafficSpan::Day => "day",
+        }
+    }
+
+    /// 包含 t 的桶的起点（unix 秒）
+    pub fn bucket_start(self, t: DateTime<Local>) -> i64 {
+        let naive = match self {
+            TrafficSpan::Minute => t
+                .naive_local()
+                .with_second(0)
+                .and_then(|n| n.with_nanosecond(0)),
+            TrafficSpan::Hour => t.date_naive().and_hms_opt(t.hour(), 0, 0),
+            TrafficSpan::Day => Some(t.date_naive().and_time(NaiveTime::MIN)),
+        };
+        naive
+            .and_then(|n| local_earliest(n.and_local_timezone(Local)))
+            .map_or(t.timestamp(), |d| d.timestamp())
+    }
+
+    /// 超过这个时长的桶会被清理；按天的桶一直保留，用于按月对账
+    pub fn retention(self) -> Option<Duration> {
+        match self {
+            TrafficSpan::Minute => Some(Duration::days(2)),
+            TrafficSpan::Hour => Some(Duration::days(90)),
+            TrafficSpan::Day => None,
+        }
+    }
+
+    // 图表未指定起点时显示的时长
+    pub fn default_window(self) -> Duration {
+        match self {
+            TrafficSpan::Minute => Duration::hours(1),
+            TrafficSpan::Hour => Duration::hours(48),
+            TrafficSpan::Day => Duration::days(31),
+        }
+    }
+}
+
+// 夏令时切换时本地时间可能重复或不存在，取较早的一个
+fn local_earliest(r: LocalResult<DateTime<Local>>) -> Option<DateTime<Local>> {
+    match r {
+        LocalResult::Single(d) => Some(d),
+        LocalResult::Ambiguous(a, _) => Some(a),
+        LocalResult::None => None,
+    }
+}
+
+fn local_midnight(date: NaiveDate) -> Option<DateTime<Local>> {
+    local_earliest(Local.from_local_datetime(&date.and_time(NaiveTime::MIN)))
+}
+
+/// 本月 1 日零点，用量报表的默认起点
+pub fn month_start(now: DateTime<Local>) -> DateTime<Local> {
+    cycle_start(now, 1)
+}
+
+/// 按月计费周期的起点：最近一个 reset_day 日的零点
+pub fn cycle_start(now: DateTime<Local>, reset_day: u8) -> DateTime<Local> {
+    let day = u32::from(reset_day.clamp(1, MAX_RESET_DAY));
+    let today = now.date_naive();
+    let mut start = today.with_day(day).unwrap_or(today);
+    if start > today {
+        start = start.checked_sub_months(Months::new(1)).unwrap_or(start);
+    }
+    local_midnight(start).unwrap_or(now)
+}
+
+fn cycle_end(start: DateTime<Local>) -> DateTime<Local> {
+    start
+        .date_naive()
+        .checked_add_months(Months::new(1))
+        .and_then(local_midnight)
+        .unwrap_or(start)
+}
+
+/// 接受 RFC 3339 或本地日期 YYYY-MM-DD
+pub fn parse_time(s: &str) -> Option<DateTime<Local>> {
+    if let Ok(t) = DateTime::parse_from_rfc3339(s) {
+        return Some(t.with_timezone(&Local));
+    }
+    local_midnight(NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()?)
+}
+
+pub fn format_time(ts: i64) -> String {
+    Local
+        .timestamp_opt(ts, 0)
+        .single()
+        .map(|t| t.to_rfc3339())
+        .unwrap_or_default()
+}
+
+/// 查询条件；proxy_id 为空时包含该 profile 的全部代理，from / to 为空时按粒度取默认范围
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficQuery {
+    pub profile_id: Option<String>,
+    pub proxy_id: Option<String>,
+    pub span: TrafficSpan,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// 某个代理在一个桶内的字节数
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficPoint {
+    pub proxy_id: String,
+    // 代理已删除时为记录时的名称
+    pub proxy_name: String,
+    // 桶的起点，RFC 3339，本地时区
+    pub bucket: String,
+    pub up: u64,
+    pub down: u64,
+}
+
+/// 某个代理在一段时间内的合计，按总量从大到小排列
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxyUsage {
+    pub proxy_id: String,
+    pub proxy_name: String,
+    pub up: u64,
+    pub down: u64,
+    pub total: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+// 含逗号、引号或换行的字段加引号
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+pub fn points_to_csv(points: &[TrafficPoint]) -> String {
+    let mut out = String::from("bucket,proxy_id,proxy_name,up,down\n");
+    for p in points {
+        out.push_str(&format!(
+            "{},{},{},{},{}\n",
+            p.bucket,
+            csv_field(&p.proxy_id),
+            csv_field(&p.proxy_name),
+            p.up,
+            p.down
+        ));
+    }
+    out
+}
+
+// 每月 29 日之后的日期不是每个月都有
+pub const MAX_RESET_DAY: u8 = 28;
+
+/// 单个代理的流量配额，按上行加下行计算，0 表示不限
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficQuota {
+    #[serde(default)]
+    pub daily_bytes: u64,
+    #[serde(default)]
+    pub monthly_bytes: u64,
+    // 用量达到限额的这个百分比时发出警告，0 表示不警告
+    #[serde(default = "default_warn_percent")]
+    pub warn_percent: u8,
+    // 每月从这一天零点开始重新计算，与 VPS 的账单日对齐
+    #[serde(default = "default_reset_day")]
+    pub reset_day: u8,
+}
+
+fn default_warn_percent() -> u8 {
+    80
+}
+
+fn default_reset_day() -> u8 {
+    1
+}
+
+impl Default for TrafficQuota {
+    fn default() -> Self {
+        Self {
+            daily_bytes: 0,
+            monthly_bytes: 0,
+            warn_percent: default_warn_percent(),
+            reset_day: default_reset_day(),
+        }
+    }
+}
+
+impl TrafficQuota {
+    pub fn is_set(&self) -> bool {
+        self.daily_bytes > 0 || self.monthly_bytes > 0
+    }
+
+    /// 各个已设置的周期：(周期, 限额, 起点, 下次重置)
+    pub fn windows(
+        &self,
+        now: DateTime<Local>,
+    ) -> Vec<(QuotaPeriod, u64, DateTime<Local>, DateTime<Local>)> {
+        let mut out = Vec::new();
+        if self.daily_bytes > 0 {
+            let today = now.date_naive();
+            let start = local_midnight(today).unwrap_or(now);
+            let end = local_midnight(today + Duration::days(1)).unwrap_or(now);
+            out.push((QuotaPeriod::Daily, self.daily_bytes, start, end));
+        }
+        if self.monthly_bytes > 0 {
+            let start = cycle_start(now, self.reset_day);
+            out.push((
+                QuotaPeriod::Monthly,
+                self.monthly_bytes,
+                start,
+                cycle_end(start),
+            ));
+        }
+        out
+    }
+
+    pub fn level(&self, used: u64, limit: u64) -> QuotaLevel {
+        if used >= limit {
+            QuotaLevel::Exceeded
+        } else if self.warn_percent > 0
+            && u128::from(used) * 100 >= u128::from(limit) * u128::from(self.warn_percent)
+        {
+            QuotaLevel::Warning
+        } else {
+            QuotaLevel::Ok
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuotaPeriod {
+    Daily,
+    Monthly,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuotaLevel {
+    Ok,
+    Warning,
+    Exceeded,
+}
+
+/// 某个代理在当前周期内的配额用量
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaStatus {
+    pub proxy_id: String,
+    pub proxy_name: String,
+    pub period: QuotaPeriod,
+    pub used: u64,
+    pub limit: u64,
+    pub level: QuotaLevel,
+    // 本周期起点与下次重置时间，RFC 3339
+    pub since: String,
+    pub resets_at: String,
+}
+
+/// 单个代理的限速，字节/秒，0 表示不限；同一代理的全部连接共用
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimit {
+    // 访问者发往本地服务
+    #[serde(default)]
+    pub up_bytes_per_sec: u64,
+    // 本地服务发往访问者，占用本机所在网络的上行带宽
+    #[serde(default)]
+    pub down_bytes_per_sec: u64,
+    // 空闲后允许一次性通过的字节数，0 时取一秒的量
+    #[serde(default)]
+    pub burst_bytes: u64,
+}
+
+impl RateLimit {
+    pub fn burst_for(&self, rate: u64) -> u64 {
+        if self.burst_bytes > 0 {
+            self.burst_bytes
+        } else {
+            rate
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 避开夏令时切换的日子
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32, sec: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(y, m, d, h, min, sec).unwrap()
+    }
+
+    #[test]
+    fn bucket_start_aligns_to_local_time() {
+        let t = at(2024, 6, 15, 13, 47, 29) + Duration::milliseconds(250);
+        assert_eq!(
+            TrafficSpan::Minute.bucket_start(t),
+            at(2024, 6, 15, 13, 47, 0).timestamp()
+        );
+        assert_eq!(
+            TrafficSpan::Hour.bucket_start(t),
+            at(2024, 6, 15, 13, 0, 0).timestamp()
+        );
+        assert_eq!(
+            TrafficSpan::Day.bucket_start(t),
+            at(2024, 6, 15, 0, 0, 0).timestamp()
+        );
+        // 已经在边界上时不变
+        let midnight = at(2024, 6, 15, 0, 0, 0);
+        for span in TrafficSpan::ALL {
+            assert_eq!(span.bucket_start(midnight), midnight.timestamp());
+        }
+    }
+
+    #[test]
+    fn month_and_cycle_start() {
+        let now = at(2024, 6, 15, 13, 47, 29);
+        assert_eq!(month_start(now), at(2024, 6, 1, 0, 0, 0));
+        assert_eq!(cycle_start(now, 10), at(2024, 6, 10, 0, 0, 0));
+        // 当天就是重置日
+        assert_eq!(cycle_start(now, 15), at(2024, 6, 15, 0, 0, 0));
+        // 重置日还没到，从上个月算起
+        assert_eq!(cycle_start(now, 20), at(2024, 5, 20, 0, 0, 0));
+        // 超出范围的重置日被夹到 1-28
+        assert_eq!(cycle_start(now, 0), at(2024, 6, 1, 0, 0, 0));
+        assert_eq!(cycle_start(now, 31), at(2024, 5, 28, 0, 0, 0));
+        // 跨年
+        assert_eq!(
+            cycle_start(at(2024, 1, 5, 8, 0, 0), 20),
+            at(2023, 12, 20, 0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn retention_keeps_day_buckets() {
+        let now = at(2024, 6, 15, 13, 0, 0);
+        let cutoff = |span: TrafficSpan| span.retention().map(|keep| (now - keep).timestamp());
+        assert_eq!(
+            cutoff(TrafficSpan::Minute),
+            Some(now.timestamp() - 2 * 86_400)
+        );
+        assert_eq!(
+            cutoff(TrafficSpan::Hour),
+            Some(now.timestamp() - 90 * 86_400)
+        );
+        assert_eq!(cutoff(TrafficSpan::Day), None);
+    }
+
+    #[test]
+    fn parses_rfc3339_and_local_dates() {
+        assert_eq!(parse_time("2024-06-15"), Some(at(2024, 6, 15, 0, 0, 0)));
+        assert_eq!(
+            parse_time("2024-06-15T12:00:00Z").map(|t| t.timestamp()),
+            Some(1_718_452_800)
+        );
+        assert_eq!(parse_time("2024-13-01"), None);
+        assert_eq!(parse_time("yesterday"), None);
+    }
+
+    #[test]
+    fn csv_quotes_special_fields() {
+        let point = |name: &str| TrafficPoint {
+            proxy_id: "id-1".into(),
+            proxy_name: name.into(),
+            bucket: "2024-06-15T00:00:00+00:00".into(),
+            up: 1,
+            down: 2,
+        };
+        let csv = points_to_csv(&[
+            point("ssh"),
+            point("a,b"),
+            point("say \"hi\""),
+            point("x\ny"),
+        ]);
+        assert_eq!(
+            csv,
+            "bucket,proxy_id,proxy_name,up,down\n\
+             2024-06-15T00:00:00+00:00,id-1,ssh,1,2\n\
+             2024-06-15T00:00:00+00:00,id-1,\"a,b\",1,2\n\
+             2024-06-15T00:00:00+00:00,id-1,\"say \"\"hi\"\"\",1,2\n\
+             2024-06-15T00:00:00+00:00,id-1,\"x\ny\",1,2\n"
+        );
+        assert_eq!(points_to_csv(&[]), "bucket,proxy_id,proxy_name,up,down\n");
+    }
+
+    fn quota(daily: u64, monthly: u64, warn_percent: u8, reset_day: u8) -> TrafficQuota {
+        TrafficQuota {
+            daily_bytes: daily,
+            monthly_bytes: monthly,
+            warn_percent,
+            reset_day,
+        }
+    }
+
+    #[test]
+    fn windows_follow_reset_day() {
+        let now = at(2024, 6, 15, 13, 47, 29);
+        assert!(quota(0, 0, 80, 1).windows(now).is_empty());
+
+        let w = quota(100, 0, 80, 1).windows(now);
+        assert_eq!(
+            w,
+            [(
+                QuotaPeriod::Daily,
+                100,
+                at(2024, 6, 15, 0, 0, 0),
+                at(2024, 6, 16, 0, 0, 0)
+            )]
+        );
+
+        let monthly = |reset_day: u8| {
+            let w = quota(0, 1000, 80, reset_day).windows(now);
+            assert_eq!(w.len(), 1);
+            assert_eq!((w[0].0, w[0].1), (QuotaPeriod::Monthly, 1000));
+            (w[0].2, w[0].3)
+        };
+        assert_eq!(
+            monthly(10),
+            (at(2024, 6, 10, 0, 0, 0), at(2024, 7, 10, 0, 0, 0))
+        );
+        assert_eq!(
+            monthly(15),
+            (at(2024, 6, 15, 0, 0, 0), at(2024, 7, 15, 0, 0, 0))
+        );
+        assert_eq!(
+            monthly(20),
+            (at(2024, 5, 20, 0, 0, 0), at(2024, 6, 20, 0, 0, 0))
+        );
+        assert_eq!(
+            monthly(31),
+            (at(2024, 5, 28, 0, 0, 0), at(2024, 6, 28, 0, 0, 0))
+        );
+
+        let both = quota(100, 1000, 80, 1).windows(now);
+        let periods: Vec<_> = both.iter().map(|w| w.0).collect();
+        assert_eq!(periods, [QuotaPeriod::Daily, QuotaPeriod::Monthly]);
+    }
+
+    #[test]
+    fn level_thresholds() {
+        let q = quota(0, 0, 80, 1);
+        assert_eq!(q.level(0, 100), QuotaLevel::Ok);
+        assert_eq!(q.level(79, 100), QuotaLevel::Ok);
+        assert_eq!(q.level(80, 100), QuotaLevel::Warning);
+        assert_eq!(q.level(99, 100), QuotaLevel::Warning);
+        assert_eq!(q.level(100, 100), QuotaLevel::Exceeded);
+        assert_eq!(q.level(u64::MAX, 100), QuotaLevel::Exceeded);
+        // 大数相乘不溢出
+        assert_eq!(q.level(u64::MAX - 1, u64::MAX), QuotaLevel::Warning);
+
+        // 0 表示不警告，只在超出时变化
+        let silent = quota(0, 0, 0, 1);
+        assert_eq!(silent.level(99, 100), QuotaLevel::Ok);
+        assert_eq!(silent.level(100, 100), QuotaLevel::Exceeded);
+    }
+}
@@ -8,6 +8,13 @@ pub enum AuthType {
     Oidc,
 }
 
+// 除登录外还需要附带鉴权信息的消息
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuthScope {
+    HeartBeats,
+    NewWorkConns,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum DomainType {
@@ -15,3 +22,14 @@ pub enum DomainType {
     Sub,
     Custom,
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TransportProtocol {
+    #[default]
+    Tcp,
+    Kcp,
+    Quic,
+    Websocket,
+    Wss,
+}
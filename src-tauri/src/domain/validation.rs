@@ -0,0 +1,658 @@
+use crate::domain::config::FrpcConfig;
+use crate::domain::proxy::{HttpSwitch, Proxy};
+use crate::domain::traffic::MAX_RESET_DAY;
+use crate::domain::types::{AuthType, DomainType};
+use crate::domain::visitor::Visitor;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// 一条字段级问题；path 形如 serverAddr、proxies[ssh].localIP
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Issue {
+    pub severity: Severity,
+    pub path: String,
+    // 所属代理 / 访问端的 id，服务端级别的问题为空
+    pub target_id: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct ValidationReport {
+    pub issues: Vec<Issue>,
+}
+
+impl ValidationReport {
+    /// 只保留某个代理 / 访问端（None 为服务端设置）相关的问题
+    pub fn only(self, target_id: Option<&str>) -> Self {
+        Self {
+            issues: self
+                .issues
+                .into_iter()
+                .filter(|i| i.target_id.as_deref() == target_id)
+                .collect(),
+        }
+    }
+
+    /// 有错误时拼成一行文字，供命令直接返回
+    pub fn error_summary(&self) -> Option<String> {
+        let errors: Vec<String> = self
+            .issues
+            .iter()
+            .filter(|i| i.severity == Severity::Error)
+            .map(|i| format!("{}: {}", i.path, i.message))
+            .collect();
+        (!errors.is_empty()).then(|| format!("invalid config: {}", errors.join("; ")))
+    }
+}
+
+struct Collector {
+    issues: Vec<Issue>,
+    // 当前对象已禁用时错误降为警告，不影响启动
+    target: Option<(String, bool)>,
+}
+
+impl Collector {
+    fn push(&mut self, severity: Severity, path: String, message: impl Into<String>) {
+        let (target_id, enabled) = match &self.target {
+            Some((id, enabled)) => (Some(id.clone()), *enabled),
+            None => (None, true),
+        };
+        let severity = if enabled { severity } else { Severity::Warning };
+        self.issues.push(Issue {
+            severity,
+            path,
+            target_id,
+            message: message.into(),
+        });
+    }
+    fn error(&mut self, path: String, message: impl Into<String>) {
+        self.push(Severity::Error, path, message);
+    }
+    fn warn(&mut self, path: String, message: impl Into<String>) {
+        self.push(Severity::Warning, path, message);
+    }
+}
+
+fn is_ip(s: &str) -> bool {
+    s.parse::<IpAddr>().is_ok()
+}
+
+pub fn validate_config(cfg: &FrpcConfig) -> ValidationReport {
+    let mut c = Collector {
+        issues: Vec::new(),
+        target: None,
+    };
+    validate_server(cfg, &mut c);
+
+    let mut names: HashMap<&str, usize> = HashMap::new();
+    for p in &cfg.proxies {
+        *names.entry(p.name.as_str()).or_default() += 1;
+    }
+    // (类型, 域名, location) → 第一个声明它的代理名
+    let mut routes: HashMap<(&str, String, String), String> = HashMap::new();
+    for p in &cfg.proxies {
+        c.target = Some((p.id.clone(), p.enable));
+        let base = format!("proxies[{}]", p.name);
+        if p.name.trim().is_empty() {
+            c.error(format!("{base}.name"), "name is empty");
+        } else if names.get(p.name.as_str()).copied().unwrap_or(0) > 1 {
+            c.error(format!("{base}.name"), "duplicate proxy name");
+        }
+        validate_proxy(p, &base, &mut c);
+        if p.enable {
+            check_route_conflicts(p, &base, &mut routes, &mut c);
+        }
+    }
+
+    let mut names: HashMap<&str, usize> = HashMap::new();
+    for v in &cfg.visitors {
+        *names.entry(v.name.as_str()).or_default() += 1;
+    }
+    let mut binds: HashMap<(String, i32), String> = HashMap::new();
+    for v in &cfg.visitors {
+        c.target = Some((v.id.clone(), v.enable));
+        let base = format!("visitors[{}]", v.name);
+        if v.name.trim().is_empty() {
+            c.error(format!("{base}.name"), "name is empty");
+        } else if names.get(v.name.as_str()).copied().unwrap_or(0) > 1 {
+            c.error(format!("{base}.name"), "duplicate visitor name");
+        }
+        validate_visitor(v, &base, &names, &mut c);
+        if v.enable && v.bind_port > 0 {
+            let addr = if v.bind_addr.is_empty() {
+                "127.0.0.1".to_string()
+            } else {
+                v.bind_addr.clone()
+            };
+            if let Some(other) = binds.insert((addr, v.bind_port), v.name.clone()) {
+                c.error(
+                    format!("{base}.bindPort"),
+                    format!("bind port already used by visitor {other}"),
+                );
+            }
+        }
+    }
+
+    ValidationReport { issues: c.issues }
+}
+
+fn validate_server(cfg: &FrpcConfig, c: &mut Collector) {
+    if cfg.server_addr.trim().is_empty() {
+        c.error("serverAddr".into(), "server address is empty");
+    }
+    if cfg.server_port == 0 {
+        c.error("serverPort".into(), "port out of range (1-65535)");
+    }
+    if cfg.switch.auth {
+        match cfg.auth.method {
+            AuthType::Token => {
+                if cfg.auth.token.is_empty() {
+                    c.error("auth.token".into(), "auth token is empty");
+                }
+            }
+            AuthType::Oidc => {
+                if cfg.auth.oidc.client_id.is_empty() {
+                    c.error("auth.oidc.clientID".into(), "oidc clientID is empty");
+                }
+                if cfg.auth.oidc.token_endpoint_url.is_empty() {
+                    c.error(
+                        "auth.oidc.tokenEndpointURL".into(),
+                        "oidc tokenEndpointURL is empty",
+                    );
+                }
+            }
+        }
+    }
+    if cfg.switch.web_server {
+        if cfg.web_server.port == 0 {
+            c.error("webServer.port".into(), "port out of range (1-65535)");
+        }
+        let addr = &cfg.web_server.addr;
+        if !addr.is_empty() && !is_ip(addr) && addr != "localhost" {
+            c.error("webServer.addr".into(), "must be an IP address");
+        }
+    }
+    if cfg.switch.transport {
+        let t = &cfg.transport;
+        if t.heartbeat_interval > 0
+            && t.heartbeat_timeout > 0
+            && t.heartbeat_timeout <= t.heartbeat_interval
+        {
+            c.warn(
+                "transport.heartbeatTimeout".into(),
+                "heartbeat timeout should be longer than the interval",
+            );
+        }
+    }
+}
+
+fn validate_proxy(p: &Proxy, base: &str, c: &mut Collector) {
+    if !is_ip(&p.local_ip) {
+        // shim 需要直接连接本地地址，只接受 IP
+        c.error(format!("{base}.localIP"), "must be an IP address");
+    }
+    if p.local_port == 0 {
+        c.error(format!("{base}.localPort"), "port out of range (1-65535)");
+    }
+    if !(1..=MAX_RESET_DAY).contains(&p.quota.reset_day) {
+        c.error(
+            format!("{base}.quota.resetDay"),
+            format!("reset day out of range (1-{MAX_RESET_DAY})"),
+        );
+    }
+    if p.quota.warn_percent > 100 {
+        c.error(
+            format!("{base}.quota.warnPercent"),
+            "warn percent out of range (0-100)",
+        );
+    }
+    if p.proxy_protocol && !p.supports_proxy_protocol() {
+        c.warn(
+            format!("{base}.proxyProtocol"),
+            "proxy protocol is ignored for this proxy type",
+        );
+    }
+
+    match p {
+        Proxy::Http(h) => {
+            check_domains(&h.switch, &h.subdomain, &h.custom_domains, base, c);
+            for (i, loc) in h.locations.iter().enumerate() {
+                if !loc.starts_with('/') {
+                    c.error(
+                        format!("{base}.locations[{i}]"),
+                        "location must start with /",
+                    );
+                }
+            }
+            if h.switch.auth && h.http_user.is_empty() {
+                c.error(format!("{base}.httpUser"), "http user is empty");
+            }
+        }
+        Proxy::Https(h) => {
+            check_domains(&h.switch, &h.subdomain, &h.custom_domains, base, c);
+            if let Some(plugin) = &h.plugin {
+                let o = plugin.options();
+                if o.crt_path.is_empty() != o.key_path.is_empty() {
+                    c.error(
+                        format!("{base}.plugin"),
+                        "crtPath and keyPath must be set together",
+                    );
+                }
+            }
+        }
+        Proxy::Stcp(s) | Proxy::Sudp(s) | Proxy::Xtcp(s) => {
+            if s.secret_key.is_empty() {
+                c.warn(
+                    format!("{base}.secretKey"),
+                    "secret key is empty, any visitor can connect",
+                );
+            }
+        }
+        Proxy::Tcp(_) | Proxy::Udp(_) => {}
+    }
+}
+
+fn check_domains(
+    switch: &HttpSwitch,
+    subdomain: &str,
+    custom_domains: &[String],
+    base: &str,
+    c: &mut Collector,
+) {
+    match switch.domain {
+        DomainType::Sub => {
+            if subdomain.is_empty() {
+                c.error(format!("{base}.subdomain"), "subdomain is empty");
+            } else if subdomain.contains('.') {
+                c.error(
+                    format!("{base}.subdomain"),
+                    "subdomain must not contain '.'",
+                );
+            }
+            if !custom_domains.is_empty() {
+                c.warn(
+                    format!("{base}.customDomains"),
+                    "customDomains is ignored while subdomain is selected",
+                );
+            }
+        }
+        DomainType::Custom => {
+            if custom_domains.iter().all(|d| d.trim().is_empty()) {
+                c.error(format!("{base}.customDomains"), "custom domains is empty");
+            }
+            if !subdomain.is_empty() {
+                c.warn(
+                    format!("{base}.subdomain"),
+                    "subdomain is ignored while customDomains is selected",
+                );
+            }
+        }
+    }
+}
+
+// 同类型代理在同一域名 + location 上只能有一个，否则 frps 拒绝后注册的那个
+fn check_route_conflicts<'a>(
+    p: &'a Proxy,
+    base: &str,
+    routes: &mut HashMap<(&'a str, String, String), String>,
+    c: &mut Collector,
+) {
+    let (kind, switch, subdomain, custom_domains, locations) = match p {
+        Proxy::Http(h) => (
+            "http",
+            &h.switch,
+            &h.subdomain,
+            &h.custom_domains,
+            h.locations.as_slice(),
+        ),
+        Proxy::Https(h) => ("https", &h.switch, &h.subdomain, &h.custom_domains, &[][..]),
+        _ => return,
+    };
+    let domains: Vec<(String, &str)> = match switch.domain {
+        DomainType::Sub => vec![(format!("{subdomain}.*"), "subdomain")],
+        DomainType::Custom => custom_domains
+            .iter()
+            .map(|d| (d.trim().to_ascii_lowercase(), "customDomains"))
+            .collect(),
+    };
+    let locations: Vec<String> = if locations.is_empty() {
+        vec!["/".to_string()]
+    } else {
+        locations.to_vec()
+    };
+    let mut seen = HashSet::new();
+    for (domain, field) in domains {
+        for loc in &locations {
+            if !seen.insert((domain.clone(), loc.clone())) {
+                continue;
+            }
+            let key = (kind, domain.clone(), loc.clone());
+            if let Some(other) = routes.get(&key) {
+                c.error(
+                    format!("{base}.{field}"),
+                    format!("{domain}{loc} conflicts with proxy {other}"),
+                );
+            } else {
+                routes.insert(key, p.name.clone());
+            }
+        }
+    }
+}
+
+fn validate_visitor(v: &Visitor, base: &str, names: &HashMap<&str, usize>, c: &mut Collector) {
+    if v.server_name.is_empty() {
+        c.error(format!("{base}.serverName"), "server name is empty");
+    }
+    if !v.bind_addr.is_empty() && !is_ip(&v.bind_addr) {
+        c.error(format!("{base}.bindAddr"), "must be an IP address");
+    }
+    let fallback_only = matches!(v, Visitor::Xtcp(_)) && v.bind_port == -1;
+    if !fallback_only && !(1..=65535).contains(&v.bind_port) {
+        c.error(format!("{base}.bindPort"), "port out of range (1-65535)");
+    }
+    if let Visitor::Xtcp(x) = v {
+        if !x.fallback_to.is_empty() && !names.contains_key(x.fallback_to.as_str()) {
+            c.error(
+                format!("{base}.fallbackTo"),
+                format!("visitor {} does not exist", x.fallback_to),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::proxy::{HttpProxy, ProxyCommon, TcpProxy, UdpProxy};
+    use crate::domain::visitor::VisitorCommon;
+
+    fn common(name: &str, local_port: u16) -> ProxyCommon {
+        ProxyCommon {
+            id: format!("id-{name}"),
+            name: name.into(),
+            enable: true,
+            local_ip: "127.0.0.1".into(),
+            local_port,
+            ..Default::default()
+        }
+    }
+
+    fn tcp(name: &str) -> Proxy {
+        Proxy::Tcp(TcpProxy {
+            common: common(name, 22),
+            remote_port: 6000,
+        })
+    }
+
+    fn http(name: &str, domain: DomainType, subdomain: &str, custom: &[&str]) -> HttpProxy {
+        HttpProxy {
+            common: common(name, 80),
+            switch: HttpSwitch {
+                domain,
+                auth: false,
+            },
+            subdomain: subdomain.into(),
+            custom_domains: custom.iter().map(|d| d.to_string()).collect(),
+            locations: Vec::new(),
+            http_user: String::new(),
+            http_password: String::new(),
+        }
+    }
+
+    fn visitor(name: &str, bind_port: i32) -> Visitor {
+        Visitor::Stcp(VisitorCommon {
+            id: format!("id-{name}"),
+            name: name.into(),
+            enable: true,
+            server_user: String::new(),
+            server_name: "ssh".into(),
+            secret_key: "abc".into(),
+            bind_addr: "127.0.0.1".into(),
+            bind_port,
+        })
+    }
+
+    fn valid() -> FrpcConfig {
+        FrpcConfig {
+            server_addr: "frp.example.com".into(),
+            server_port: 7000,
+            proxies: vec![tcp("ssh")],
+            visitors: vec![visitor("ssh-visitor", 9000)],
+            ..Default::default()
+        }
+    }
+
+    fn issues(cfg: &FrpcConfig) -> Vec<(Severity, String)> {
+        validate_config(cfg)
+            .issues
+            .into_iter()
+            .map(|i| (i.severity, i.path))
+            .collect()
+    }
+
+    fn error(path: &str) -> (Severity, String) {
+        (Severity::Error, path.to_string())
+    }
+
+    fn warning(path: &str) -> (Severity, String) {
+        (Severity::Warning, path.to_string())
+    }
+
+    #[test]
+    fn valid_config_has_no_issues() {
+        let report = validate_config(&valid());
+        assert!(report.issues.is_empty(), "{:?}", report.issues);
+        assert_eq!(report.error_summary(), None);
+    }
+
+    #[test]
+    fn duplicate_names() {
+        let mut cfg = valid();
+        cfg.proxies.push(tcp("ssh"));
+        cfg.visitors.push(visitor("ssh-visitor", 9001));
+        assert_eq!(
+            issues(&cfg),
+            [
+                error("proxies[ssh].name"),
+                error("proxies[ssh].name"),
+                error("visitors[ssh-visitor].name"),
+                error("visitors[ssh-visitor].name"),
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_server_addr() {
+        let mut cfg = valid();
+        cfg.server_addr = "  ".into();
+        assert_eq!(issues(&cfg), [error("serverAddr")]);
+        assert_eq!(
+            validate_config(&cfg).error_summary().as_deref(),
+            Some("invalid config: serverAddr: server address is empty")
+        );
+    }
+
+    #[test]
+    fn invalid_ip() {
+        let mut cfg = valid();
+        cfg.proxies[0].local_ip = "localhost".into();
+        cfg.visitors[0].bind_addr = "0.0.0.300".into();
+        cfg.switch.web_server = true;
+        cfg.web_server.port = 7400;
+        cfg.web_server.addr = "admin.local".into();
+        assert_eq!(
+            issues(&cfg),
+            [
+                error("webServer.addr"),
+                error("proxies[ssh].localIP"),
+                error("visitors[ssh-visitor].bindAddr"),
+            ]
+        );
+        // webServer 允许 localhost，代理的本地地址不允许
+        cfg.web_server.addr = "localhost".into();
+        cfg.proxies[0].local_ip = "::1".into();
+        cfg.visitors[0].bind_addr = String::new();
+        assert!(issues(&cfg).is_empty());
+    }
+
+    #[test]
+    fn port_range() {
+        let mut cfg = valid();
+        cfg.server_port = 0;
+        cfg.proxies[0].local_port = 0;
+        cfg.visitors[0].bind_port = 70000;
+        assert_eq!(
+            issues(&cfg),
+            [
+                error("serverPort"),
+                error("proxies[ssh].localPort"),
+                error("visitors[ssh-visitor].bindPort"),
+            ]
+        );
+    }
+
+    #[test]
+    fn subdomain_and_custom_domains() {
+        let mut cfg = valid();
+        cfg.proxies = vec![
+            // 选中子域名时 customDomains 被忽略
+            Proxy::Http(http("a", DomainType::Sub, "app", &["a.example.com"])),
+            // 选中自定义域名时 subdomain 被忽略
+            Proxy::Http(http("b", DomainType::Custom, "app", &["b.example.com"])),
+            Proxy::Http(http("c", DomainType::Sub, "x.y", &[])),
+            Proxy::Http(http("d", DomainType::Custom, "", &[" "])),
+        ];
+        assert_eq!(
+            issues(&cfg),
+            [
+                warning("proxies[a].customDomains"),
+                warning("proxies[b].subdomain"),
+                error("proxies[c].subdomain"),
+                error("proxies[d].customDomains"),
+            ]
+        );
+    }
+
+    #[test]
+    fn route_conflicts() {
+        let mut cfg = valid();
+        let mut api = http("api", DomainType::Custom, "", &["Example.com"]);
+        api.locations = vec!["/api".into()];
+        cfg.proxies = vec![
+            Proxy::Http(http("web", DomainType::Custom, "", &["example.com"])),
+            Proxy::Http(http("web2", DomainType::Custom, "", &["EXAMPLE.com "])),
+            // location 不同不冲突
+            Proxy::Http(api),
+            Proxy::Http(http("s1", DomainType::Sub, "app", &[])),
+            Proxy::Http(http("s2", DomainType::Sub, "app", &[])),
+        ];
+        let report = validate_config(&cfg);
+        let conflicts: Vec<(&str, &str)> = report
+            .issues
+            .iter()
+            .map(|i| (i.path.as_str(), i.message.as_str()))
+            .collect();
+        assert_eq!(
+            conflicts,
+            [
+                (
+                    "proxies[web2].customDomains",
+                    "example.com/ conflicts with proxy web"
+                ),
+                ("proxies[s2].subdomain", "app.*/ conflicts with proxy s1"),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_token() {
+        let mut cfg = valid();
+        cfg.switch.auth = true;
+        assert_eq!(issues(&cfg), [error("auth.token")]);
+        cfg.auth.method = AuthType::Oidc;
+        assert_eq!(
+            issues(&cfg),
+            [
+                error("auth.oidc.clientID"),
+                error("auth.oidc.tokenEndpointURL"),
+            ]
+        );
+        // 未开启鉴权时不检查
+        cfg.switch.auth = false;
+        assert!(issues(&cfg).is_empty());
+    }
+
+    #[test]
+    fn location_must_start_with_slash() {
+        let mut cfg = valid();
+        let mut web = http("web", DomainType::Sub, "app", &[]);
+        web.locations = vec!["/ok".into(), "api".into()];
+        cfg.proxies = vec![Proxy::Http(web)];
+        assert_eq!(issues(&cfg), [error("proxies[web].locations[1]")]);
+    }
+
+    #[test]
+    fn proxy_protocol_on_unsupported_type() {
+        let mut cfg = valid();
+        let mut ssh = tcp("ssh");
+        ssh.proxy_protocol = true;
+        let mut dns = Proxy::Udp(UdpProxy {
+            common: common("dns", 53),
+            remote_port: 5353,
+        });
+        dns.proxy_protocol = true;
+        cfg.proxies = vec![ssh, dns];
+        assert_eq!(issues(&cfg), [warning("proxies[dns].proxyProtocol")]);
+    }
+
+    #[test]
+    fn disabled_target_downgrades_to_warning() {
+        let mut cfg = valid();
+        cfg.proxies[0].local_port = 0;
+        cfg.proxies[0].enable = false;
+        let mut v = visitor("off", 0);
+        v.common_mut().enable = false;
+        cfg.visitors.push(v);
+        assert_eq!(
+            issues(&cfg),
+            [
+                warning("proxies[ssh].localPort"),
+                warning("visitors[off].bindPort"),
+            ]
+        );
+        assert_eq!(validate_config(&cfg).error_summary(), None);
+    }
+
+    #[test]
+    fn only_filters_by_target() {
+        let mut cfg = valid();
+        cfg.server_addr.clear();
+        cfg.proxies[0].local_port = 0;
+        cfg.visitors[0].bind_port = 0;
+
+        let paths = |target: Option<&str>| -> Vec<String> {
+            validate_config(&cfg)
+                .only(target)
+                .issues
+                .into_iter()
+                .map(|i| i.path)
+                .collect()
+        };
+        assert_eq!(paths(None), ["serverAddr"]);
+        assert_eq!(paths(Some("id-ssh")), ["proxies[ssh].localPort"]);
+        assert_eq!(
+            paths(Some("id-ssh-visitor")),
+            ["visitors[ssh-visitor].bindPort"]
+        );
+        assert!(paths(Some("missing")).is_empty());
+    }
+}
@@ -0,0 +1,154 @@
+use crate::domain::proxy::{empty_string_as_default_id, gen_id};
+use serde::{Deserialize, Serialize};
+use std::ops::{Deref, DerefMut};
+
+// 访问端：与 stcp / sudp / xtcp 代理配对，在本机监听 bindAddr:bindPort
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Visitor {
+    Stcp(VisitorCommon),
+    Sudp(VisitorCommon),
+    Xtcp(XtcpVisitor),
+}
+
+impl Visitor {
+    pub fn common(&self) -> &VisitorCommon {
+        match self {
+            Visitor::Stcp(v) | Visitor::Sudp(v) => v,
+            Visitor::Xtcp(v) => &v.common,
+        }
+    }
+    pub fn common_mut(&mut self) -> &mut VisitorCommon {
+        match self {
+            Visitor::Stcp(v) | Visitor::Sudp(v) => v,
+            Visitor::Xtcp(v) => &mut v.common,
+        }
+    }
+}
+
+impl Deref for Visitor {
+    type Target = VisitorCommon;
+    fn deref(&self) -> &Self::Target {
+        self.common()
+    }
+}
+impl DerefMut for Visitor {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.common_mut()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct VisitorCommon {
+    #[serde(default = "gen_id", deserialize_with = "empty_string_as_default_id")]
+    pub id: String,
+    pub name: String,
+    pub enable: bool,
+    // 代理所属用户，留空表示与当前客户端相同
+    #[serde(default)]
+    pub server_user: String,
+    pub server_name: String,
+    pub secret_key: String,
+    #[serde(default)]
+    pub bind_addr: String,
+    // -1 表示不监听，仅作为 xtcp 的 fallback 目标
+    #[serde(default)]
+    pub bind_port: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct XtcpVisitor {
+    #[serde(flatten)]
+    pub common: VisitorCommon,
+    // quic / kcp，留空用 frpc 默认
+    #[serde(default)]
+    pub protocol: String,
+    #[serde(default)]
+    pub keep_tunnel_open: bool,
+    #[serde(default)]
+    pub max_retries_an_hour: u32,
+    #[serde(default)]
+    pub min_retry_interval: u32,
+    // 打洞失败时回退到的 stcp 访问端名称
+    #[serde(default)]
+    pub fallback_to: String,
+    #[serde(default)]
+    pub fallback_timeout_ms: u32,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum VisitorExport {
+    Stcp(VisitorCommonExport),
+    Sudp(VisitorCommonExport),
+    Xtcp(XtcpVisitorExport),
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VisitorCommonExport {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    server_user: Option<String>,
+    server_name: String,
+    secret_key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bind_addr: Option<String>,
+    bind_port: i32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct XtcpVisitorExport {
+    #[serde(flatten)]
+    common: VisitorCommonExport,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    protocol: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    keep_tunnel_open: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_retries_an_hour: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min_retry_interval: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fallback_to: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fallback_timeout_ms: Option<u32>,
+}
+
+fn non_empty(s: &str) -> Option<String> {
+    Some(s.to_string()).filter(|s| !s.is_empty())
+}
+
+fn non_zero(n: u32) -> Option<u32> {
+    Some(n).filter(|n| *n != 0)
+}
+
+fn to_common_export(v: &VisitorCommon) -> VisitorCommonExport {
+    VisitorCommonExport {
+        name: v.name.clone(),
+        server_user: non_empty(&v.server_user),
+        server_name: v.server_name.clone(),
+        secret_key: v.secret_key.clone(),
+        bind_addr: non_empty(&v.bind_addr),
+        bind_port: v.bind_port,
+    }
+}
+
+pub fn to_visitor_export(visitor: &Visitor) -> VisitorExport {
+    match visitor {
+        Visitor::Stcp(v) => VisitorExport::Stcp(to_common_export(v)),
+        Visitor::Sudp(v) => VisitorExport::Sudp(to_common_export(v)),
+        Visitor::Xtcp(v) => VisitorExport::Xtcp(XtcpVisitorExport {
+            common: to_common_export(&v.common),
+            protocol: non_empty(&v.protocol),
+            keep_tunnel_open: v.keep_tunnel_open,
+            max_retries_an_hour: non_zero(v.max_retries_an_hour),
+            min_retry_interval: non_zero(v.min_retry_interval),
+            fallback_to: non_empty(&v.fallback_to),
+            fallback_timeout_ms: non_zero(v.fallback_timeout_ms),
+        }),
+    }
+}
@@ -14,6 +14,14 @@ pub enum AppError {
     Toml(#[from] toml::ser::Error),
     #[error("Serde error: {0}")]
     Serde(#[from] serde_json::Error),
+    #[error("Import error: {0}")]
+    Import(String),
+    #[error("{0}")]
+    Validation(String),
+    #[error("Database error: {0}")]
+    Db(#[from] rusqlite::Error),
+    #[error("Crypto error: {0}")]
+    Crypto(String),
     #[error("Other: {0}")]
     Other(String),
 }
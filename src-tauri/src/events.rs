@@ -1,7 +1,22 @@
 pub const EVT_LOG_STDOUT: &str = "frpc://stdout";
 pub const EVT_LOG_STDERR: &str = "frpc://stderr";
 pub const EVT_LOG_ERROR:  &str = "frpc://error";
+// 解析后的结构化日志行与识别出的关键事件
+pub const EVT_LOG_LINE:   &str = "frpc://log";
+pub const EVT_LOG_EVENT:  &str = "frpc://event";
 pub const EVT_CLOSE:      &str = "frpc://close";
+pub const EVT_RESTART:    &str = "frpc://restart";
+pub const EVT_STARTED:    &str = "frpc://started";
+pub const EVT_PROXY_STATUS: &str = "frpc://proxy-status";
 
 pub const EVT_DOWNLOAD_PROGRESS: &str = "frp_download_progress";
 pub const EVT_ACTIVATING_STATUS: &str = "frp_activating_status";
+// shim 每秒上报一次各实例的连接表
+pub const EVT_CONNECTIONS: &str = "frp:connections";
+// 代理配额的级别变化：接近限额、超出限额、周期重置
+pub const EVT_QUOTA: &str = "frp:quota";
+
+/// 每个实例另有独立通道，例如 frpc://stdout/<profile id>
+pub fn instance_event(base: &str, id: &str) -> String {
+    format!("{base}/{id}")
+}
@@ -0,0 +1,80 @@
+use crate::errors::{AppError, Result};
+use crate::state::{AppState, FrpcProcState};
+use serde::Serialize;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, Wry};
+use tauri_plugin_store::{Store, StoreExt};
+
+/// 一个 json 文件对应的键值存储
+pub trait KvStore: Send + Sync {
+    fn get(&self, key: &str) -> Option<Value>;
+    fn set(&self, key: &str, value: Value);
+    fn delete(&self, key: &str) -> bool;
+    fn save(&self) -> Result<()>;
+    /// 丢弃内存中的内容，重新读取磁盘上的文件
+    fn reload(&self) -> Result<()>;
+}
+
+/// 事件出口：图形界面里发给前端，守护进程里打印到 stdout
+pub trait EventSink {
+    fn emit<S: Serialize + Clone>(&self, event: &str, payload: S);
+}
+
+/// services 依赖的宿主能力；Tauri 的 AppHandle 与无界面的守护进程各实现一份
+pub trait Host: EventSink + Clone + Send + Sync + 'static {
+    fn config_dir(&self) -> PathBuf;
+    fn data_dir(&self) -> PathBuf;
+    fn store(&self, file: &str) -> Result<Arc<dyn KvStore>>;
+    fn app_state(&self) -> AppState;
+    /// 向 watchdog 发一行命令，例如 ADD PID 123
+    fn notify_watchdog(&self, msg: &str);
+}
+
+impl KvStore for Store<Wry> {
+    fn get(&self, key: &str) -> Option<Value> {
+        Store::get(self, key)
+    }
+    fn set(&self, key: &str, value: Value) {
+        Store::set(self, key, value)
+    }
+    fn delete(&self, key: &str) -> bool {
+        Store::delete(self, key)
+    }
+    fn save(&self) -> Result<()> {
+        Store::save(self).map_err(|e| AppError::Store(e.to_string()))
+    }
+    fn reload(&self) -> Result<()> {
+        Store::reload(self).map_err(|e| AppError::Store(e.to_string()))
+    }
+}
+
+impl EventSink for AppHandle {
+    fn emit<S: Serialize + Clone>(&self, event: &str, payload: S) {
+        let _ = Emitter::emit(self, event, payload);
+    }
+}
+
+impl Host for AppHandle {
+    fn config_dir(&self) -> PathBuf {
+        self.path().app_config_dir().expect("app_config_dir")
+    }
+    fn data_dir(&self) -> PathBuf {
+        self.path().app_data_dir().expect("app_data_dir")
+    }
+    fn store(&self, file: &str) -> Result<Arc<dyn KvStore>> {
+        let st = StoreExt::store(self, file).map_err(|e| AppError::Store(e.to_string()))?;
+        Ok(st)
+    }
+    fn app_state(&self) -> AppState {
+        self.state::<AppState>().inner().clone()
+    }
+    fn notify_watchdog(&self, msg: &str) {
+        let state = self.state::<FrpcProcState>();
+        let mut guard = state.watchdog.lock().unwrap();
+        if let Some(child) = guard.as_mut() {
+            let _ = child.write(format!("{msg}\n").as_bytes());
+        }
+    }
+}
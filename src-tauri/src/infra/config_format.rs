@@ -0,0 +1,347 @@
+use crate::errors::{AppError, Result};
+use ini::Ini;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigFormat {
+    Toml,
+    Ini,
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// 优先看扩展名，认不出再按内容猜
+    pub fn detect(path: &Path, content: &str) -> ConfigFormat {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        match ext.as_str() {
+            "toml" => return ConfigFormat::Toml,
+            "ini" => return ConfigFormat::Ini,
+            "yaml" | "yml" => return ConfigFormat::Yaml,
+            "json" => return ConfigFormat::Json,
+            _ => {}
+        }
+        let trimmed = content.trim_start();
+        if trimmed.starts_with('{') {
+            ConfigFormat::Json
+        } else if content.lines().any(|l| l.trim() == "[common]") {
+            ConfigFormat::Ini
+        } else if toml::from_str::<Value>(content).is_ok() {
+            ConfigFormat::Toml
+        } else {
+            ConfigFormat::Yaml
+        }
+    }
+}
+
+/// 序列化为指定格式；旧版 ini 只支持读取
+pub fn render_value<T: Serialize>(value: &T, format: ConfigFormat) -> Result<String> {
+    match format {
+        ConfigFormat::Toml => Ok(toml::to_string_pretty(value)?),
+        ConfigFormat::Yaml => {
+            serde_yaml::to_string(value).map_err(|e| AppError::Other(e.to_string()))
+        }
+        ConfigFormat::Json => Ok(serde_json::to_string_pretty(value)?),
+        ConfigFormat::Ini => Err(AppError::Other("ini export is not supported".into())),
+    }
+}
+
+/// 把 frpc 的各种配置格式统一解析成新版（v1 / camelCase）结构的 JSON 值
+pub fn parse_to_value(content: &str, format: ConfigFormat) -> Result<Value> {
+    let value = match format {
+        ConfigFormat::Toml => toml::from_str::<Value>(content)
+            .map_err(|e| AppError::Import(format!("toml: {e}")))?,
+        ConfigFormat::Yaml => serde_yaml::from_str::<Value>(content)
+            .map_err(|e| AppError::Import(format!("yaml: {e}")))?,
+        ConfigFormat::Json => serde_json::from_str::<Value>(content)
+            .map_err(|e| AppError::Import(format!("json: {e}")))?,
+        ConfigFormat::Ini => {
+            let ini =
+                Ini::load_from_str(content).map_err(|e| AppError::Import(format!("ini: {e}")))?;
+            legacy_ini_to_value(&ini)
+        }
+    };
+    if !value.is_object() {
+        return Err(AppError::Import("config root must be a table".into()));
+    }
+    Ok(value)
+}
+
+// ===================== 旧版 INI → 新版结构 =====================
+
+const INI_INT_KEYS: &[&str] = &[
+    "server_port",
+    "admin_port",
+    "local_port",
+    "remote_port",
+    "bind_port",
+    "pool_count",
+    "heartbeat_interval",
+    "heartbeat_timeout",
+    "dial_server_timeout",
+];
+
+fn ini_scalar(key: &str, raw: &str) -> Value {
+    if INI_INT_KEYS.contains(&key) {
+        if let Ok(n) = raw.trim().parse::<i64>() {
+            return Value::from(n);
+        }
+    }
+    match raw.trim() {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        s => Value::String(s.to_string()),
+    }
+}
+
+fn ini_list(raw: &str) -> Value {
+    Value::Array(
+        raw.split(',')
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| Value::String(s.to_string()))
+            .collect(),
+    )
+}
+
+fn object_at<'a>(map: &'a mut Map<String, Value>, key: &str) -> &'a mut Map<String, Value> {
+    let slot = map
+        .entry(key.to_string())
+        .or_insert_with(|| Value::Object(Map::new()));
+    if !slot.is_object() {
+        *slot = Value::Object(Map::new());
+    }
+    slot.as_object_mut().expect("object slot")
+}
+
+// 不认识的键原样保留（保持旧名），交给导入报告列为不支持
+fn legacy_common(props: &ini::Properties, root: &mut Map<String, Value>) {
+    for (k, v) in props.iter() {
+        match k {
+            "server_addr" => {
+                root.insert("serverAddr".into(), ini_scalar(k, v));
+            }
+            "server_port" => {
+                root.insert("serverPort".into(), ini_scalar(k, v));
+            }
+            "authentication_method" => {
+                object_at(root, "auth").insert("method".into(), ini_scalar(k, v));
+            }
+            "token" => {
+                object_at(root, "auth").insert("token".into(), ini_scalar(k, v));
+            }
+            "authenticate_heartbeats" | "authenticate_new_work_conns" => {
+                if v.trim() == "true" {
+                    let scope = match k {
+                        "authenticate_heartbeats" => "HeartBeats",
+                        _ => "NewWorkConns",
+                    };
+                    let auth = object_at(root, "auth");
+                    let scopes = auth
+                        .entry("additionalScopes")
+                        .or_insert_with(|| Value::Array(Vec::new()));
+                    if let Value::Array(items) = scopes {
+                        items.push(Value::String(scope.into()));
+                    }
+                }
+            }
+            "oidc_client_id" | "oidc_client_secret" | "oidc_audience" | "oidc_scope"
+            | "oidc_token_endpoint_url" => {
+                let key = match k {
+                    "oidc_client_id" => "clientID",
+                    "oidc_client_secret" => "clientSecret",
+                    "oidc_audience" => "audience",
+                    "oidc_scope" => "scope",
+                    _ => "tokenEndpointURL",
+                };
+                let auth = object_at(root, "auth");
+                object_at(auth, "oidc").insert(key.into(), ini_scalar(k, v));
+            }
+            _ if k.starts_with("oidc_additional_") => {
+                let auth = object_at(root, "auth");
+                let params = object_at(object_at(auth, "oidc"), "additionalEndpointParams");
+                params.insert(k["oidc_additional_".len()..].to_string(), ini_scalar(k, v));
+            }
+            "admin_addr" => {
+                object_at(root, "webServer").insert("addr".into(), ini_scalar(k, v));
+            }
+            "admin_port" => {
+                object_at(root, "webServer").insert("port".into(), ini_scalar(k, v));
+            }
+            "admin_user" => {
+                object_at(root, "webServer").insert("user".into(), ini_scalar(k, v));
+            }
+            "admin_pwd" => {
+                object_at(root, "webServer").insert("password".into(), ini_scalar(k, v));
+            }
+            "protocol" | "pool_count" | "tcp_mux" | "heartbeat_interval" | "heartbeat_timeout"
+            | "http_proxy" | "dial_server_timeout" => {
+                let key = match k {
+                    "protocol" => "protocol",
+                    "pool_count" => "poolCount",
+                    "tcp_mux" => "tcpMux",
+                    "heartbeat_interval" => "heartbeatInterval",
+                    "heartbeat_timeout" => "heartbeatTimeout",
+                    "http_proxy" => "proxyURL",
+                    _ => "dialServerTimeout",
+                };
+                object_at(root, "transport").insert(key.into(), ini_scalar(k, v));
+            }
+            "tls_enable" | "tls_cert_file" | "tls_key_file" | "tls_trusted_ca_file"
+            | "tls_server_name" => {
+                let key = match k {
+                    "tls_enable" => "enable",
+                    "tls_cert_file" => "certFile",
+                    "tls_key_file" => "keyFile",
+                    "tls_trusted_ca_file" => "trustedCaFile",
+                    _ => "serverName",
+                };
+                let transport = object_at(root, "transport");
+                object_at(transport, "tls").insert(key.into(), ini_scalar(k, v));
+            }
+            _ => {
+                root.insert(k.to_string(), ini_scalar(k, v));
+            }
+        }
+    }
+}
+
+fn legacy_section(name: &str, props: &ini::Properties) -> (bool, Value) {
+    let mut out = Map::new();
+    out.insert("name".into(), Value::String(name.to_string()));
+    let mut is_visitor = false;
+    for (k, v) in props.iter() {
+        let (key, value) = match k {
+            "role" => {
+                is_visitor = v.trim() == "visitor";
+                continue;
+            }
+            "type" => ("type", ini_scalar(k, v)),
+            "local_ip" => ("localIP", ini_scalar(k, v)),
+            "local_port" => ("localPort", ini_scalar(k, v)),
+            "remote_port" => ("remotePort", ini_scalar(k, v)),
+            "subdomain" => ("subdomain", ini_scalar(k, v)),
+            "custom_domains" => ("customDomains", ini_list(v)),
+            "locations" => ("locations", ini_list(v)),
+            "http_user" => ("httpUser", ini_scalar(k, v)),
+            "http_pwd" => ("httpPassword", ini_scalar(k, v)),
+            "sk" => ("secretKey", ini_scalar(k, v)),
+            "allow_users" => ("allowUsers", ini_list(v)),
+            "server_name" => ("serverName", ini_scalar(k, v)),
+            "server_user" => ("serverUser", ini_scalar(k, v)),
+            "bind_addr" => ("bindAddr", ini_scalar(k, v)),
+            "bind_port" => ("bindPort", ini_scalar(k, v)),
+            "plugin" => {
+                object_at(&mut out, "plugin").insert("type".into(), ini_scalar(k, v));
+                continue;
+            }
+            "plugin_local_addr" | "plugin_crt_path" | "plugin_key_path"
+            | "plugin_host_header_rewrite" => {
+                let key = match k {
+                    "plugin_local_addr" => "localAddr",
+                    "plugin_crt_path" => "crtPath",
+                    "plugin_key_path" => "keyPath",
+                    _ => "hostHeaderRewrite",
+                };
+                object_at(&mut out, "plugin").insert(key.into(), ini_scalar(k, v));
+                continue;
+            }
+            _ if k.starts_with("plugin_header_") => {
+                let plugin = object_at(&mut out, "plugin");
+                let headers = object_at(object_at(plugin, "requestHeaders"), "set");
+                headers.insert(k["plugin_header_".len()..].to_string(), ini_scalar(k, v));
+                continue;
+            }
+            _ => {
+                out.insert(k.to_string(), ini_scalar(k, v));
+                continue;
+            }
+        };
+        out.insert(key.into(), value);
+    }
+    (is_visitor, Value::Object(out))
+}
+
+fn legacy_ini_to_value(ini: &Ini) -> Value {
+    let mut root = Map::new();
+    let mut proxies = Vec::new();
+    let mut visitors = Vec::new();
+    for (section, props) in ini.iter() {
+        match section {
+            None if props.is_empty() => {}
+            None | Some("common") => legacy_common(props, &mut root),
+            Some(name) => {
+                let (is_visitor, v) = legacy_section(name, props);
+                if is_visitor {
+                    visitors.push(v);
+                } else {
+                    proxies.push(v);
+                }
+            }
+        }
+    }
+    if !proxies.is_empty() {
+        root.insert("proxies".into(), Value::Array(proxies));
+    }
+    if !visitors.is_empty() {
+        root.insert("visitors".into(), Value::Array(visitors));
+    }
+    Value::Object(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_format_by_content() {
+        let none = Path::new("frpc.conf");
+        let cases = [
+            ("{\"serverAddr\": \"a\"}", ConfigFormat::Json),
+            ("[common]\nserver_addr = a\n", ConfigFormat::Ini),
+            (
+                "serverAddr = \"a\"\n[[proxies]]\nname = \"ssh\"\n",
+                ConfigFormat::Toml,
+            ),
+            (
+                "serverAddr: a\nproxies:\n  - name: ssh\n",
+                ConfigFormat::Yaml,
+            ),
+        ];
+        for (content, want) in cases {
+            assert_eq!(ConfigFormat::detect(none, content), want, "{content}");
+        }
+        // 扩展名优先
+        assert_eq!(
+            ConfigFormat::detect(Path::new("frpc.YML"), "{}"),
+            ConfigFormat::Yaml
+        );
+    }
+
+    #[test]
+    fn legacy_ini_maps_to_v1_keys() {
+        let ini = "[common]\nserver_port = 7000\ntls_enable = true\noidc_additional_foo = bar\n\n\
+                   [web]\ntype = http\ncustom_domains = a.com, b.com\nplugin_header_X-From = frp\n";
+        let v = parse_to_value(ini, ConfigFormat::Ini).unwrap();
+        assert_eq!(v["serverPort"], 7000);
+        assert_eq!(v["transport"]["tls"]["enable"], true);
+        assert_eq!(v["auth"]["oidc"]["additionalEndpointParams"]["foo"], "bar");
+        let web = &v["proxies"][0];
+        assert_eq!(web["name"], "web");
+        assert_eq!(web["customDomains"], serde_json::json!(["a.com", "b.com"]));
+        assert_eq!(web["plugin"]["requestHeaders"]["set"]["X-From"], "frp");
+    }
+
+    #[test]
+    fn rejects_non_table_root() {
+        assert!(parse_to_value("[1, 2]", ConfigFormat::Json).is_err());
+        assert!(render_value(&serde_json::json!({}), ConfigFormat::Ini).is_err());
+    }
+}
@@ -0,0 +1,77 @@
+use crate::errors::{AppError, Result};
+use base64::engine::general_purpose::STANDARD as B64;
+use base64::Engine;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+
+// 密文前缀，带版本号便于以后换算法
+const SEALED_PREFIX: &str = "enc:v1:";
+const NONCE_LEN: usize = 24;
+pub const SALT_LEN: usize = 16;
+
+/// 由主密码派生的 256 位密钥，只保存在内存里
+#[derive(Clone)]
+pub struct SecretKey([u8; 32]);
+
+impl std::fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SecretKey(..)")
+    }
+}
+
+pub fn new_salt() -> [u8; SALT_LEN] {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    salt
+}
+
+pub fn is_sealed(s: &str) -> bool {
+    s.starts_with(SEALED_PREFIX)
+}
+
+impl SecretKey {
+    /// Argon2id 默认参数，派生一次约几十毫秒
+    pub fn derive(passphrase: &str, salt: &[u8]) -> Result<Self> {
+        let mut key = [0u8; 32];
+        argon2::Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|e| AppError::Crypto(e.to_string()))?;
+        Ok(Self(key))
+    }
+
+    fn cipher(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new(&self.0.into())
+    }
+
+    /// 输出 enc:v1:<base64(nonce || ciphertext)>
+    pub fn seal(&self, plain: &str) -> Result<String> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ct = self
+            .cipher()
+            .encrypt(&nonce, plain.as_bytes())
+            .map_err(|_| AppError::Crypto("encrypt failed".into()))?;
+        let mut buf = nonce.to_vec();
+        buf.extend_from_slice(&ct);
+        Ok(format!("{SEALED_PREFIX}{}", B64.encode(buf)))
+    }
+
+    pub fn open(&self, sealed: &str) -> Result<String> {
+        let body = sealed
+            .strip_prefix(SEALED_PREFIX)
+            .ok_or_else(|| AppError::Crypto("value is not encrypted".into()))?;
+        let buf = B64
+            .decode(body)
+            .map_err(|e| AppError::Crypto(e.to_string()))?;
+        if buf.len() < NONCE_LEN {
+            return Err(AppError::Crypto("ciphertext too short".into()));
+        }
+        let (nonce, ct) = buf.split_at(NONCE_LEN);
+        // 密码错误或密文被改动都会在这里失败
+        let plain = self
+            .cipher()
+            .decrypt(XNonce::from_slice(nonce), ct)
+            .map_err(|_| AppError::Crypto("wrong passphrase or corrupted data".into()))?;
+        String::from_utf8(plain).map_err(|e| AppError::Crypto(e.to_string()))
+    }
+}
@@ -0,0 +1,175 @@
+use crate::domain::config::FrpcConfig;
+use crate::errors::{AppError, Result};
+use crate::infra::http::client;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+// /api/status 返回 {"tcp": [...], "http": [...]}，按代理类型分组
+#[derive(Debug, Clone, Deserialize)]
+pub struct AdminProxyStatus {
+    pub name: String,
+    #[serde(default)]
+    pub status: String,
+    #[serde(default)]
+    pub err: String,
+    #[serde(default)]
+    pub remote_addr: String,
+}
+
+/// frpc webServer（admin API）的访问地址与凭据
+#[derive(Debug, Clone)]
+pub struct AdminEndpoint {
+    pub base_url: String,
+    pub user: String,
+    pub password: String,
+}
+
+impl AdminEndpoint {
+    /// 未开启 webServer 时返回 None
+    pub fn from_config(cfg: &FrpcConfig) -> Option<Self> {
+        if !cfg.switch.web_server || cfg.web_server.port == 0 {
+            return None;
+        }
+        // 监听在全部地址时从本机回环访问
+        let host = match cfg.web_server.addr.as_str() {
+            "" | "0.0.0.0" => "127.0.0.1",
+            "::" => "[::1]",
+            addr => addr,
+        };
+        Some(Self {
+            base_url: format!("http://{}:{}", host, cfg.web_server.port),
+            user: cfg.web_server.user.clone(),
+            password: cfg.web_server.password.clone(),
+        })
+    }
+
+    fn get(&self, path: &str) -> reqwest::RequestBuilder {
+        let req = client().get(format!("{}{}", self.base_url, path));
+        if self.user.is_empty() {
+            req
+        } else {
+            req.basic_auth(&self.user, Some(&self.password))
+        }
+    }
+
+    /// 让 frpc 重新读取配置文件，只增删有变化的代理
+    pub async fn reload(&self) -> Result<()> {
+        let resp = self.get("/api/reload").send().await?;
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            return Err(AppError::Other(format!(
+                "frpc reload failed: {status} {}",
+                body.trim()
+            )));
+        }
+        Ok(())
+    }
+
+    pub async fn status(&self) -> Result<Vec<AdminProxyStatus>> {
+        let resp = self.get("/api/status").send().await?;
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(AppError::Other(format!("frpc status failed: {status}")));
+        }
+        let groups: HashMap<String, Vec<AdminProxyStatus>> = resp.json().await?;
+        Ok(groups.into_values().flatten().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::http::stub;
+
+    const STATUS_BODY: &str = r#"{
+        "tcp": [{"name": "ssh", "type": "tcp", "status": "running", "err": "", "remote_addr": ":6000"}],
+        "http": [{"name": "web", "type": "http", "status": "start error", "err": "port unavailable"}]
+    }"#;
+
+    fn endpoint(server: &stub::Server, user: &str) -> AdminEndpoint {
+        AdminEndpoint {
+            base_url: server.url.clone(),
+            user: user.into(),
+            password: "pw".into(),
+        }
+    }
+
+    #[test]
+    fn endpoint_from_config() {
+        let mut cfg = FrpcConfig::default();
+        cfg.web_server.port = 7400;
+        assert!(AdminEndpoint::from_config(&cfg).is_none());
+
+        cfg.switch.web_server = true;
+        let cases = [
+            ("", "http://127.0.0.1:7400"),
+            ("0.0.0.0", "http://127.0.0.1:7400"),
+            ("::", "http://[::1]:7400"),
+            ("192.168.1.2", "http://192.168.1.2:7400"),
+        ];
+        for (addr, want) in cases {
+            cfg.web_server.addr = addr.into();
+            assert_eq!(AdminEndpoint::from_config(&cfg).unwrap().base_url, want);
+        }
+
+        cfg.web_server.port = 0;
+        assert!(AdminEndpoint::from_config(&cfg).is_none());
+    }
+
+    #[tokio::test]
+    async fn reload_and_status_send_basic_auth() {
+        let server = stub::serve(&[
+            ("/api/reload", "200 OK", ""),
+            ("/api/status", "200 OK", STATUS_BODY),
+        ])
+        .await;
+        let admin = endpoint(&server, "admin");
+        admin.reload().await.unwrap();
+        let mut list = admin.status().await.unwrap();
+        list.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].name, "ssh");
+        assert_eq!(list[0].status, "running");
+        assert_eq!(list[0].remote_addr, ":6000");
+        assert_eq!(list[1].name, "web");
+        assert_eq!(list[1].err, "port unavailable");
+
+        let reqs = server.requests();
+        let paths: Vec<&str> = reqs.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(paths, ["/api/reload", "/api/status"]);
+        // admin:pw
+        for r in &reqs {
+            assert_eq!(r.method, "GET");
+            assert_eq!(r.header("authorization"), Some("Basic YWRtaW46cHc="));
+        }
+    }
+
+    #[tokio::test]
+    async fn no_user_sends_no_auth() {
+        let server = stub::serve(&[("/api/reload", "200 OK", "")]).await;
+        endpoint(&server, "").reload().await.unwrap();
+        assert_eq!(server.requests()[0].header("authorization"), None);
+    }
+
+    #[tokio::test]
+    async fn maps_non_2xx_to_errors() {
+        let server = stub::serve(&[
+            (
+                "/api/reload",
+                "500 Internal Server Error",
+                "parse config error\n",
+            ),
+            ("/api/status", "401 Unauthorized", ""),
+        ])
+        .await;
+        let admin = endpoint(&server, "admin");
+        let err = admin.reload().await.unwrap_err().to_string();
+        assert!(
+            err.contains("frpc reload failed: 500 Internal Server Error parse config error"),
+            "{err}"
+        );
+        let err = admin.status().await.unwrap_err().to_string();
+        assert!(err.contains("frpc status failed: 401"), "{err}");
+    }
+}
@@ -0,0 +1,78 @@
+use crate::host::Host;
+use crate::infra::paths::GUI_LOCK_FILE;
+use std::path::PathBuf;
+
+fn lock_path<H: Host>(app: &H) -> PathBuf {
+    app.data_dir().join(GUI_LOCK_FILE)
+}
+
+/// 图形界面启动时写入自己的 pid；它在内存里持有整份配置并整体写回，
+/// frpc-cli 据此拒绝在它运行期间修改 frpc.json
+pub fn acquire<H: Host>(app: &H) {
+    let path = lock_path(app);
+    let result = std::fs::create_dir_all(app.data_dir())
+        .and_then(|_| std::fs::write(&path, std::process::id().to_string()));
+    if let Err(e) = result {
+        eprintln!("[gui] write {} failed: {e}", path.display());
+    }
+}
+
+/// 正常退出时删除；只删自己写的，避免误删新启动的实例留下的文件
+pub fn release<H: Host>(app: &H) {
+    let path = lock_path(app);
+    let ours =
+        std::fs::read_to_string(&path).is_ok_and(|s| s.trim() == std::process::id().to_string());
+    if ours {
+        let _ = std::fs::remove_file(&path);
+    }
+}
+
+/// 正在运行的图形界面的 pid；进程已不存在（例如崩溃后残留）时返回 None
+pub fn holder<H: Host>(app: &H) -> Option<u32> {
+    let pid: u32 = std::fs::read_to_string(lock_path(app))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    #[cfg(unix)]
+    {
+        // 0 与负数在 kill 里表示进程组
+        let raw = i32::try_from(pid).ok().filter(|p| *p > 0)?;
+        if unsafe { libc::kill(raw, 0) } != 0 {
+            return None;
+        }
+    }
+    Some(pid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::DaemonHost;
+
+    #[test]
+    fn acquire_release_and_stale_lock() {
+        let dir = std::env::temp_dir().join(format!("frpc-gui-lock-{}", uuid::Uuid::new_v4()));
+        let app = DaemonHost::new(dir.clone(), dir.clone());
+        assert_eq!(holder(&app), None);
+
+        acquire(&app);
+        assert_eq!(holder(&app), Some(std::process::id()));
+        release(&app);
+        assert_eq!(holder(&app), None);
+
+        // 别的进程写的文件不删
+        std::fs::write(lock_path(&app), "1").unwrap();
+        release(&app);
+        assert!(lock_path(&app).exists());
+
+        #[cfg(unix)]
+        {
+            std::fs::write(lock_path(&app), u32::MAX.to_string()).unwrap();
+            assert_eq!(holder(&app), None);
+            std::fs::write(lock_path(&app), i32::MAX.to_string()).unwrap();
+            assert_eq!(holder(&app), None);
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,58 @@
+use crate::errors::{AppError, Result};
+use crate::host::KvStore;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// 不依赖 Tauri 的 store，文件格式与 tauri-plugin-store 相同（顶层 json 对象）
+pub struct JsonStore {
+    path: PathBuf,
+    data: Mutex<Map<String, Value>>,
+}
+
+fn read_file(path: &Path) -> Result<Map<String, Value>> {
+    match std::fs::read(path) {
+        Ok(bytes) => serde_json::from_slice(&bytes)
+            .map_err(|e| AppError::Store(format!("{}: {e}", path.display()))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Map::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+impl JsonStore {
+    pub fn open(path: PathBuf) -> Result<Self> {
+        let data = read_file(&path)?;
+        Ok(Self {
+            path,
+            data: Mutex::new(data),
+        })
+    }
+}
+
+impl KvStore for JsonStore {
+    fn get(&self, key: &str) -> Option<Value> {
+        self.data.lock().unwrap().get(key).cloned()
+    }
+    fn set(&self, key: &str, value: Value) {
+        self.data.lock().unwrap().insert(key.to_string(), value);
+    }
+    fn delete(&self, key: &str) -> bool {
+        self.data.lock().unwrap().remove(key).is_some()
+    }
+    // 先写临时文件再改名，避免写到一半时崩溃留下损坏的配置
+    fn save(&self) -> Result<()> {
+        let bytes = serde_json::to_vec_pretty(&*self.data.lock().unwrap())?;
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, bytes)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+    fn reload(&self) -> Result<()> {
+        let data = read_file(&self.path)?;
+        *self.data.lock().unwrap() = data;
+        Ok(())
+    }
+}
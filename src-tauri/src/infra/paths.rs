@@ -1,18 +1,36 @@
+use crate::host::Host;
 use std::path::PathBuf;
-use tauri::{AppHandle, Manager};
 
 pub const STORE_FILE: &str = "frpc.json";
-pub const CONFIG_TOML_FILE: &str = "frpc.toml";
+// 配置历史单独存放，避免拖慢主配置的读写
+pub const HISTORY_FILE: &str = "history.json";
+// 守护进程的 pid 与所运行的 profile
+pub const DAEMON_FILE: &str = "daemon.json";
+// 图形界面运行期间存在，内容为其 pid
+pub const GUI_LOCK_FILE: &str = "gui.lock";
+// 按分钟 / 小时 / 天汇总的代理流量
+pub const TRAFFIC_DB: &str = "traffic.db";
 pub const DOWNLOAD_ROOT: &str = "downloads";
+pub const LOG_ROOT: &str = "logs";
 
-pub fn app_config_dir(app: &AppHandle) -> PathBuf {
-    app.path().app_config_dir().expect("app_config_dir")
+pub fn app_config_dir<H: Host>(app: &H) -> PathBuf {
+    app.config_dir()
 }
-pub fn app_data_dir(app: &AppHandle) -> PathBuf {
-    app.path().app_data_dir().expect("app_data_dir")
+pub fn app_data_dir<H: Host>(app: &H) -> PathBuf {
+    app.data_dir()
 }
 
-pub fn get_download_dir(app: &AppHandle) -> std::io::Result<PathBuf> {
+// 每个实例单独生成一份 toml，互不覆盖
+pub fn instance_toml_file(id: &str) -> String {
+    format!("frpc-{id}.toml")
+}
+
+// frpc 输出日志，按 profile 分目录
+pub fn log_dir<H: Host>(app: &H, profile_id: &str) -> PathBuf {
+    app_data_dir(app).join(LOG_ROOT).join(profile_id)
+}
+
+pub fn get_download_dir<H: Host>(app: &H) -> std::io::Result<PathBuf> {
     let dir = app_data_dir(app).join(DOWNLOAD_ROOT);
     if !dir.exists() {
         std::fs::create_dir_all(&dir)?;
@@ -34,7 +52,7 @@ pub fn archive_stem(name: &str) -> String {
     name.to_string()
 }
 
-pub fn unpack_dir_for(app: &AppHandle, name: &str) -> PathBuf {
+pub fn unpack_dir_for<H: Host>(app: &H, name: &str) -> PathBuf {
     app_data_dir(app)
         .join(DOWNLOAD_ROOT)
         .join(archive_stem(name))
@@ -1,11 +1,19 @@
+use crate::errors::Result;
+use crate::host::{Host, KvStore};
 use std::sync::Arc;
-use tauri::{AppHandle, Wry};
-use tauri_plugin_store::{Result as StoreResult, Store, StoreExt};
 
+// 旧版单配置，仅用于迁移到 profiles
 pub const CONFIG_KEY: &str = "config";
+pub const PROFILES_KEY: &str = "profiles";
+pub const ACTIVE_PROFILE_KEY: &str = "active_profile";
 pub const SETTINGS_KEY: &str = "settings";
+pub const HISTORY_KEY: &str = "snapshots";
+// 主密码的盐与校验值；存在即表示开启了加密
+pub const VAULT_KEY: &str = "vault";
 pub const LOADED_FLAG_KEY: &str = "__loaded_flag__";
+// settings 中的键：停止 frpc 时 SIGTERM 后等待的毫秒数
+pub const STOP_GRACE_KEY: &str = "stopGraceMs";
 
-pub fn store(app: &AppHandle, file: &str) -> StoreResult<Arc<Store<Wry>>> {
+pub fn store<H: Host>(app: &H, file: &str) -> Result<Arc<dyn KvStore>> {
     app.store(file)
-}
\ No newline at end of file
+}
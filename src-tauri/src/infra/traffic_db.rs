@@ -0,0 +1,315 @@
+use crate::domain::traffic::{format_time, ProxyUsage, TrafficPoint, TrafficSpan};
+use crate::errors::Result;
+use rusqlite::{params, Connection};
+use std::path::Path;
+use std::time::Duration;
+
+// 图形界面与守护进程可能同时写入
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS traffic (
+    span    TEXT    NOT NULL,
+    bucket  INTEGER NOT NULL,
+    profile TEXT    NOT NULL,
+    proxy   TEXT    NOT NULL,
+    name    TEXT    NOT NULL DEFAULT '',
+    up      INTEGER NOT NULL DEFAULT 0,
+    down    INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (span, profile, proxy, bucket)
+) WITHOUT ROWID;
+";
+
+/// 一个代理自上次写入以来新增的字节数
+pub struct TrafficDelta {
+    pub proxy_id: String,
+    pub proxy_name: String,
+    pub up: u64,
+    pub down: u64,
+}
+
+/// 按 分钟 / 小时 / 天 汇总的流量，存放在 data_dir 下的 sqlite 文件
+pub struct TrafficDb {
+    conn: Connection,
+}
+
+impl TrafficDb {
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let conn = Connection::open(path)?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    #[cfg(test)]
+    pub(crate) fn open_in_memory() -> Result<Self> {
+        let conn = Connection::open_in_memory()?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// 把增量同时累加到三种粒度的桶里
+    pub fn add(
+        &mut self,
+        profile_id: &str,
+        buckets: &[(TrafficSpan, i64)],
+        deltas: &[TrafficDelta],
+    ) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO traffic (span, bucket, profile, proxy, name, up, down)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                 ON CONFLICT (span, profile, proxy, bucket) DO UPDATE SET
+                     name = excluded.name,
+                     up = up + excluded.up,
+                     down = down + excluded.down",
+            )?;
+            for d in deltas {
+                for (span, bucket) in buckets {
+                    stmt.execute(params![
+                        span.as_str(),
+                        bucket,
+                        profile_id,
+                        d.proxy_id,
+                        d.proxy_name,
+                        d.up as i64,
+                        d.down as i64
+                    ])?;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 删除 before 之前的桶
+    pub fn prune(&self, span: TrafficSpan, before: i64) -> Result<usize> {
+        Ok(self.conn.execute(
+            "DELETE FROM traffic WHERE span = ?1 AND bucket < ?2",
+            params![span.as_str(), before],
+        )?)
+    }
+
+    /// [from, to) 内的桶，按时间再按代理排序
+    pub fn points(
+        &self,
+        profile_id: &str,
+        proxy_id: Option<&str>,
+        span: TrafficSpan,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<TrafficPoint>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT proxy, name, bucket, up, down FROM traffic
+             WHERE span = ?1 AND profile = ?2 AND (?3 IS NULL OR proxy = ?3)
+               AND bucket >= ?4 AND bucket < ?5
+             ORDER BY bucket, proxy",
+        )?;
+        let rows = stmt.query_map(
+            params![span.as_str(), profile_id, proxy_id, from, to],
+            |r| {
+                Ok(TrafficPoint {
+                    proxy_id: r.get(0)?,
+                    proxy_name: r.get(1)?,
+                    bucket: format_time(r.get(2)?),
+                    up: r.get::<_, i64>(3)? as u64,
+                    down: r.get::<_, i64>(4)? as u64,
+                })
+            },
+        )?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// 单个代理在 [from, to) 内的上行加下行，from 需对齐到天
+    pub fn proxy_total(&self, profile_id: &str, proxy_id: &str, from: i64, to: i64) -> Result<u64> {
+        let total: i64 = self.conn.query_row(
+            "SELECT COALESCE(SUM(up + down), 0) FROM traffic
+             WHERE span = ?1 AND profile = ?2 AND proxy = ?3 AND bucket >= ?4 AND bucket < ?5",
+            params![TrafficSpan::Day.as_str(), profile_id, proxy_id, from, to],
+            |r| r.get(0),
+        )?;
+        Ok(total as u64)
+    }
+
+    /// 按天的桶合计出每个代理在 [from, to) 内的用量
+    pub fn usage(&self, profile_id: &str, from: i64, to: i64) -> Result<Vec<ProxyUsage>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT proxy, name, SUM(up), SUM(down) FROM (
+                 SELECT proxy, up, down,
+                        FIRST_VALUE(name) OVER (PARTITION BY proxy ORDER BY bucket DESC) AS name
+                 FROM traffic
+                 WHERE span = ?1 AND profile = ?2 AND bucket >= ?3 AND bucket < ?4
+             )
+             GROUP BY proxy
+             ORDER BY SUM(up) + SUM(down) DESC",
+        )?;
+        let rows = stmt.query_map(
+            params![TrafficSpan::Day.as_str(), profile_id, from, to],
+            |r| {
+                let up = r.get::<_, i64>(2)? as u64;
+                let down = r.get::<_, i64>(3)? as u64;
+                Ok(ProxyUsage {
+                    proxy_id: r.get(0)?,
+                    proxy_name: r.get(1)?,
+                    up,
+                    down,
+                    total: up + down,
+                })
+            },
+        )?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 86_400;
+
+    fn delta(id: &str, name: &str, up: u64, down: u64) -> TrafficDelta {
+        TrafficDelta {
+            proxy_id: id.into(),
+            proxy_name: name.into(),
+            up,
+            down,
+        }
+    }
+
+    fn buckets(minute: i64, hour: i64, day: i64) -> Vec<(TrafficSpan, i64)> {
+        vec![
+            (TrafficSpan::Minute, minute),
+            (TrafficSpan::Hour, hour),
+            (TrafficSpan::Day, day),
+        ]
+    }
+
+    fn rows(db: &TrafficDb, span: TrafficSpan) -> Vec<(String, String, u64, u64)> {
+        db.points("p", None, span, 0, i64::MAX)
+            .unwrap()
+            .into_iter()
+            .map(|p| (p.proxy_id, p.proxy_name, p.up, p.down))
+            .collect()
+    }
+
+    #[test]
+    fn add_accumulates_into_every_span() {
+        let mut db = TrafficDb::open_in_memory().unwrap();
+        db.add("p", &buckets(60, 3600, DAY), &[delta("a", "ssh", 10, 20)])
+            .unwrap();
+        // 同一小时与天，新的一分钟；名称取最近一次写入
+        db.add("p", &buckets(120, 3600, DAY), &[delta("a", "ssh2", 1, 2)])
+            .unwrap();
+
+        assert_eq!(
+            rows(&db, TrafficSpan::Minute),
+            [
+                ("a".into(), "ssh".into(), 10, 20),
+                ("a".into(), "ssh2".into(), 1, 2)
+            ]
+        );
+        for span in [TrafficSpan::Hour, TrafficSpan::Day] {
+            assert_eq!(rows(&db, span), [("a".into(), "ssh2".into(), 11, 22)]);
+        }
+        // 其它 profile 不受影响
+        assert!(db
+            .points("q", None, TrafficSpan::Day, 0, i64::MAX)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn points_filter_by_proxy_and_range() {
+        let mut db = TrafficDb::open_in_memory().unwrap();
+        db.add(
+            "p",
+            &buckets(60, 3600, DAY),
+            &[delta("a", "ssh", 1, 1), delta("b", "web", 2, 2)],
+        )
+        .unwrap();
+        db.add(
+            "p",
+            &buckets(120, 7200, 2 * DAY),
+            &[delta("a", "ssh", 3, 3)],
+        )
+        .unwrap();
+
+        let ids = |proxy: Option<&str>, from: i64, to: i64| -> Vec<(String, u64)> {
+            db.points("p", proxy, TrafficSpan::Minute, from, to)
+                .unwrap()
+                .into_iter()
+                .map(|p| (p.proxy_id, p.up))
+                .collect()
+        };
+        assert_eq!(
+            ids(None, 0, i64::MAX),
+            [("a".into(), 1), ("b".into(), 2), ("a".into(), 3)]
+        );
+        assert_eq!(
+            ids(Some("a"), 0, i64::MAX),
+            [("a".into(), 1), ("a".into(), 3)]
+        );
+        // 区间左闭右开
+        assert_eq!(ids(None, 60, 120), [("a".into(), 1), ("b".into(), 2)]);
+        assert_eq!(ids(None, 120, 121), [("a".into(), 3)]);
+    }
+
+    #[test]
+    fn usage_sums_day_buckets_by_total() {
+        let mut db = TrafficDb::open_in_memory().unwrap();
+        db.add(
+            "p",
+            &buckets(60, 3600, DAY),
+            &[delta("a", "old-name", 5, 5), delta("b", "web", 1, 1)],
+        )
+        .unwrap();
+        db.add(
+            "p",
+            &buckets(DAY + 60, DAY + 3600, 2 * DAY),
+            &[delta("a", "ssh", 1, 0), delta("b", "web", 100, 0)],
+        )
+        .unwrap();
+
+        let usage: Vec<_> = db
+            .usage("p", 0, 3 * DAY)
+            .unwrap()
+            .into_iter()
+            .map(|u| (u.proxy_id, u.proxy_name, u.up, u.down, u.total))
+            .collect();
+        assert_eq!(
+            usage,
+            [
+                ("b".into(), "web".into(), 101, 1, 102),
+                ("a".into(), "ssh".into(), 6, 5, 11)
+            ]
+        );
+        // 只算区间内的天
+        let usage = db.usage("p", 2 * DAY, 3 * DAY).unwrap();
+        assert_eq!(usage[0].total, 100);
+        assert_eq!(usage[1].total, 1);
+
+        assert_eq!(db.proxy_total("p", "a", 0, 3 * DAY).unwrap(), 11);
+        assert_eq!(db.proxy_total("p", "a", DAY + 1, 3 * DAY).unwrap(), 1);
+        assert_eq!(db.proxy_total("p", "missing", 0, 3 * DAY).unwrap(), 0);
+    }
+
+    #[test]
+    fn prune_only_touches_one_span() {
+        let mut db = TrafficDb::open_in_memory().unwrap();
+        db.add("p", &buckets(60, 60, 60), &[delta("a", "ssh", 1, 1)])
+            .unwrap();
+        db.add("p", &buckets(120, 120, 120), &[delta("a", "ssh", 1, 1)])
+            .unwrap();
+
+        assert_eq!(db.prune(TrafficSpan::Minute, 120).unwrap(), 1);
+        assert_eq!(rows(&db, TrafficSpan::Minute).len(), 1);
+        assert_eq!(rows(&db, TrafficSpan::Hour).len(), 2);
+        assert_eq!(rows(&db, TrafficSpan::Day).len(), 2);
+        assert_eq!(db.prune(TrafficSpan::Minute, 120).unwrap(), 0);
+    }
+}
@@ -5,46 +5,81 @@ use tauri::tray::{MouseButton, TrayIconBuilder, TrayIconEvent};
 use tauri::{Manager, RunEvent, State, WebviewUrl, WebviewWindowBuilder, WindowEvent};
 use tauri_plugin_shell::ShellExt;
 
+pub mod cli;
+pub mod daemon;
 mod errors;
 mod events;
+mod host;
 mod state;
 mod domain {
     pub mod active_frp;
     pub mod config;
+    pub mod frpc_log;
+    pub mod history;
+    pub mod import;
+    pub mod profile;
     pub mod progress_payload;
     pub mod proxy;
+    pub mod proxy_status;
+    pub mod restart;
+    pub mod traffic;
     pub mod types;
+    pub mod validation;
     pub mod version;
+    pub mod visitor;
 }
 mod infra {
     pub mod archive;
+    pub mod config_format;
+    pub mod crypto;
+    pub mod frpc_admin;
+    pub mod gui_lock;
     pub mod http;
+    pub mod json_store;
     pub mod paths;
     pub mod store;
+    pub mod traffic_db;
 }
 pub mod services {
+    pub mod auth_service;
     pub mod config_service;
+    pub mod history_service;
     pub mod local_proxy;
+    pub mod log_store;
+    pub mod profile_service;
+    pub mod proxy_service;
+    pub mod quota_service;
     pub mod runner;
+    pub mod status_poller;
+    pub mod traffic_service;
+    pub mod vault_service;
+    pub mod verify_service;
     pub mod version_service;
 }
 mod api {
     pub mod config_api;
+    pub mod connections_api;
+    pub mod history_api;
+    pub mod logs_api;
+    pub mod profiles_api;
     pub mod proxies_api;
     pub mod runner_api;
     pub mod settings_api;
+    pub mod traffic_api;
+    pub mod vault_api;
     pub mod versions_api;
+    pub mod visitors_api;
 }
 #[cfg(target_os = "macos")]
 use tauri::ActivationPolicy;
 
-fn kill_child_if_any(st: &State<'_, FrpcProcState>) {
-    if let Ok(mut g) = st.child.lock() {
-        if let Some(ch) = g.as_mut() {
-            let _ = ch.kill();
-            let _ = ch.wait();
-        }
-        *g = None;
+// 退出前优雅停止所有实例（超时后强杀）
+fn stop_all_blocking(app: &tauri::AppHandle) {
+    if let (Some(st), Some(proc_state)) = (
+        app.try_state::<AppState>(),
+        app.try_state::<FrpcProcState>(),
+    ) {
+        tauri::async_runtime::block_on(services::runner::stop_all(app, &st, &proc_state));
     }
 }
 
@@ -102,7 +137,8 @@ pub fn run() {
         })
         .setup(|app| {
             let state: State<AppState> = app.handle().state();
-            services::config_service::loaded_from_store(&app.handle(), &state)?;
+            services::config_service::loaded_from_store(app.handle(), &state)?;
+            infra::gui_lock::acquire(app.handle());
 
             let show = MenuItem::with_id(app, "show", "显示主窗口", true, None::<&str>)?;
             let quit = MenuItem::with_id(app, "quit", "退出", true, None::<&str>)?;
@@ -121,9 +157,7 @@ pub fn run() {
                 .on_menu_event(|app, event| match event.id.as_ref() {
                     "show" => show_window(app),
                     "quit" => {
-                        if let Some(st) = app.try_state::<FrpcProcState>() {
-                            kill_child_if_any(&st);
-                        }
+                        stop_all_blocking(app);
                         ALLOW_EXIT.store(true, Ordering::SeqCst);
                         app.exit(0)
                     }
@@ -144,11 +178,25 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             api::config_api::load_config,
             api::config_api::save_server,
+            api::config_api::validate_config,
+            api::config_api::verify_config,
             api::config_api::save_now,
+            api::config_api::import_config,
+            api::config_api::probe_oidc,
+            api::profiles_api::list_profiles,
+            api::profiles_api::create_profile,
+            api::profiles_api::clone_profile,
+            api::profiles_api::rename_profile,
+            api::profiles_api::delete_profile,
+            api::profiles_api::activate_profile,
             api::proxies_api::save_proxy,
             api::proxies_api::remove_proxy,
+            api::proxies_api::set_proxy_rate_limit,
             api::proxies_api::load_proxies,
             api::proxies_api::get_proxy,
+            api::visitors_api::save_visitor,
+            api::visitors_api::remove_visitor,
+            api::visitors_api::load_visitors,
             api::versions_api::get_versions,
             api::versions_api::get_active_version,
             api::versions_api::activate_version,
@@ -158,17 +206,37 @@ pub fn run() {
             api::runner_api::start_frpc,
             api::runner_api::stop_frpc,
             api::runner_api::frpc_status,
+            api::runner_api::list_instances,
+            api::runner_api::proxy_status,
+            api::logs_api::list_log_sessions,
+            api::logs_api::read_log,
+            api::connections_api::list_connections,
+            api::connections_api::kill_connection,
+            api::connections_api::kill_proxy_connections,
+            api::history_api::list_snapshots,
+            api::history_api::get_snapshot,
+            api::history_api::diff_snapshots,
+            api::history_api::rollback_snapshot,
+            api::traffic_api::query_traffic,
+            api::traffic_api::traffic_usage,
+            api::traffic_api::export_traffic,
+            api::traffic_api::quota_status,
             api::settings_api::set_setting,
             api::settings_api::get_setting,
+            api::vault_api::vault_status,
+            api::vault_api::enable_vault,
+            api::vault_api::unlock_vault,
+            api::vault_api::disable_vault,
         ])
         .build(tauri::generate_context!())
         .expect("failed to build frpc app")
-        .run(|_app, event| match event {
+        .run(|app, event| match event {
             RunEvent::ExitRequested { api, .. } => {
                 if !ALLOW_EXIT.load(Ordering::SeqCst) {
                     api.prevent_exit();
                 }
             }
+            RunEvent::Exit => infra::gui_lock::release(app),
             _ => {}
         });
 }
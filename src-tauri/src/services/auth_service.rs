@@ -0,0 +1,134 @@
+use crate::domain::config::AuthOidc;
+use crate::errors::{AppError, Result};
+use crate::infra::http::client;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OidcProbe {
+    pub token_type: String,
+    pub expires_in: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    #[serde(default)]
+    access_token: String,
+    #[serde(default)]
+    token_type: String,
+    expires_in: Option<u64>,
+}
+
+/// 按 frpc 的方式走一次 client_credentials，确认 OIDC 参数能换到 token
+pub async fn probe_oidc(oidc: &AuthOidc) -> Result<OidcProbe> {
+    if oidc.token_endpoint_url.is_empty() {
+        return Err(AppError::Other("oidc tokenEndpointURL is empty".into()));
+    }
+    let mut form: Vec<(&str, &str)> = vec![
+        ("grant_type", "client_credentials"),
+        ("client_id", &oidc.client_id),
+        ("client_secret", &oidc.client_secret),
+    ];
+    if !oidc.audience.is_empty() {
+        form.push(("audience", &oidc.audience));
+    }
+    if !oidc.scope.is_empty() {
+        form.push(("scope", &oidc.scope));
+    }
+    for (k, v) in &oidc.additional_endpoint_params {
+        form.push((k, v));
+    }
+
+    let resp = client()
+        .post(&oidc.token_endpoint_url)
+        .form(&form)
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        return Err(AppError::Other(format!(
+            "oidc token endpoint error: {}",
+            resp.status()
+        )));
+    }
+    let body: TokenResponse = resp.json().await?;
+    if body.access_token.is_empty() {
+        return Err(AppError::Other(
+            "oidc token endpoint returned no access_token".into(),
+        ));
+    }
+    Ok(OidcProbe {
+        token_type: body.token_type,
+        expires_in: body.expires_in,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::http::stub;
+    use std::collections::BTreeMap;
+
+    fn oidc(url: String) -> AuthOidc {
+        AuthOidc {
+            client_id: "frpc".into(),
+            client_secret: "shh".into(),
+            audience: "frps".into(),
+            scope: "openid".into(),
+            token_endpoint_url: format!("{url}/token"),
+            additional_endpoint_params: BTreeMap::from([("resource".into(), "frp".into())]),
+        }
+    }
+
+    #[tokio::test]
+    async fn probe_posts_client_credentials() {
+        let server = stub::serve(&[(
+            "/token",
+            "200 OK",
+            r#"{"access_token":"abc","token_type":"Bearer","expires_in":3600}"#,
+        )])
+        .await;
+        let probe = probe_oidc(&oidc(server.url.clone())).await.unwrap();
+        assert_eq!(probe.token_type, "Bearer");
+        assert_eq!(probe.expires_in, Some(3600));
+
+        let reqs = server.requests();
+        assert_eq!(reqs.len(), 1);
+        assert_eq!(reqs[0].method, "POST");
+        let mut form: Vec<&str> = reqs[0].body.split('&').collect();
+        form.sort();
+        assert_eq!(
+            form,
+            [
+                "audience=frps",
+                "client_id=frpc",
+                "client_secret=shh",
+                "grant_type=client_credentials",
+                "resource=frp",
+                "scope=openid",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn probe_reports_endpoint_errors() {
+        let server = stub::serve(&[
+            (
+                "/token",
+                "401 Unauthorized",
+                r#"{"error":"invalid_client"}"#,
+            ),
+            ("/empty/token", "200 OK", r#"{"token_type":"Bearer"}"#),
+        ])
+        .await;
+        let err = probe_oidc(&oidc(server.url.clone())).await.unwrap_err();
+        assert!(err.to_string().contains("401"), "{err}");
+
+        let err = probe_oidc(&oidc(format!("{}/empty", server.url)))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("no access_token"), "{err}");
+
+        let err = probe_oidc(&AuthOidc::default()).await.unwrap_err();
+        assert!(err.to_string().contains("tokenEndpointURL"), "{err}");
+    }
+}
@@ -1,16 +1,27 @@
 use crate::domain::config::FrpcConfig;
-use crate::state::{AppState, FrpcProcState};
+use crate::domain::import::{self, ImportReport};
+use crate::domain::profile::{Profile, DEFAULT_PROFILE_NAME};
+use crate::domain::validation::validate_config;
+use crate::host::Host;
+use crate::infra::config_format::{parse_to_value, render_value, ConfigFormat};
+use crate::services::{history_service, vault_service};
+use crate::state::{AppState, FrpcInstance, VaultState};
 use crate::{
-    errors::Result,
+    errors::{AppError, Result},
     infra::{
-        paths::{app_config_dir, CONFIG_TOML_FILE, STORE_FILE},
-        store::{store, CONFIG_KEY, LOADED_FLAG_KEY, SETTINGS_KEY},
+        paths::{app_config_dir, instance_toml_file, STORE_FILE},
+        store::{
+            store, ACTIVE_PROFILE_KEY, CONFIG_KEY, LOADED_FLAG_KEY, PROFILES_KEY, SETTINGS_KEY,
+            VAULT_KEY,
+        },
     },
 };
 use serde_json::Value;
-use tauri::AppHandle;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
 
-pub fn loaded_from_store(app: &AppHandle, state: &AppState) -> Result<()> {
+pub fn loaded_from_store<H: Host>(app: &H, state: &AppState) -> Result<()> {
     // 先用读锁看是否已加载
     if state
         .read()
@@ -23,11 +34,26 @@ pub fn loaded_from_store(app: &AppHandle, state: &AppState) -> Result<()> {
     }
 
     // 未加载：先做 IO（不持锁）
-    let st = store(app, STORE_FILE).map_err(|e| crate::errors::AppError::Store(e.to_string()))?;
-    let cfg_val: Option<Value> = st.get(CONFIG_KEY);
-    let cfg: FrpcConfig = cfg_val
-        .map(|json| serde_json::from_value(json).unwrap_or_default())
+    let st = store(app, STORE_FILE)?;
+    let mut profiles: Vec<Profile> = st
+        .get(PROFILES_KEY)
+        .and_then(|json| serde_json::from_value(json).ok())
         .unwrap_or_default();
+    if profiles.is_empty() {
+        // 旧版只有一份 config：迁移为默认 profile
+        let cfg: FrpcConfig = st
+            .get(CONFIG_KEY)
+            .map(|json| serde_json::from_value(json).unwrap_or_default())
+            .unwrap_or_default();
+        profiles.push(Profile::new(DEFAULT_PROFILE_NAME, cfg));
+    }
+    let active_profile = st
+        .get(ACTIVE_PROFILE_KEY)
+        .and_then(|v| v.as_str().map(str::to_string))
+        .filter(|id| profiles.iter().any(|p| &p.id == id))
+        .unwrap_or_else(|| profiles[0].id.clone());
+
+    let vault_enabled = st.get(VAULT_KEY).is_some();
 
     let settings_obj = st
         .get(SETTINGS_KEY)
@@ -37,51 +63,541 @@ pub fn loaded_from_store(app: &AppHandle, state: &AppState) -> Result<()> {
     // 再拿写锁，只做内存赋值与打标
     {
         let mut g = state.write();
-        g.config = cfg;
+        g.profiles = profiles;
+        g.active_profile = active_profile;
         g.settings = settings_obj;
+        // 开启了主密码时先处于锁定状态，敏感字段保持密文直到解锁
+        g.vault = VaultState {
+            enabled: vault_enabled,
+            key: None,
+        };
         g.settings.insert(LOADED_FLAG_KEY.into(), Value::Bool(true));
     }
+    // 首次运行或配置文件被外部改过时补一份基线快照
+    if let Err(e) = history_service::record(app, state, "loaded") {
+        eprintln!("[history] record failed: {e}");
+    }
+    Ok(())
+}
+
+/// 重新读取磁盘上的 store，用于配置文件被外部改动之后；已解锁的密钥继续沿用
+pub fn reload_from_store<H: Host>(app: &H, state: &AppState) -> Result<()> {
+    store(app, STORE_FILE)?.reload()?;
+    let key = state.read().vault.key.clone();
+    state.write().settings.remove(LOADED_FLAG_KEY);
+    loaded_from_store(app, state)?;
+    if let Some(key) = key {
+        if state.read().vault.locked() {
+            vault_service::unlock_with_key(state, key)?;
+            if let Err(e) = history_service::record(app, state, "loaded") {
+                eprintln!("[history] record failed: {e}");
+            }
+        }
+    }
     Ok(())
 }
 
-pub fn save_now(app: &AppHandle, state: &AppState) -> Result<()> {
-    let st = store(app, STORE_FILE).map_err(|e| crate::errors::AppError::Store(e.to_string()))?;
+pub fn save_now<H: Host>(app: &H, state: &AppState) -> Result<()> {
+    save_with_reason(app, state, "save")
+}
+
+/// 保存后为配置有变化的 profile 记一份历史快照，reason 会显示在历史列表里
+pub fn save_with_reason<H: Host>(app: &H, state: &AppState, reason: &str) -> Result<()> {
+    let st = store(app, STORE_FILE)?;
     let g = state.read();
-    st.set(CONFIG_KEY, serde_json::to_value(&g.config)?);
+    let profiles = vault_service::profiles_for_store(&g.vault, &g.profiles)?;
+    st.set(PROFILES_KEY, serde_json::to_value(&profiles)?);
+    st.set(ACTIVE_PROFILE_KEY, Value::String(g.active_profile.clone()));
+    st.delete(CONFIG_KEY);
     st.set(SETTINGS_KEY, Value::Object(g.settings.clone()));
-    st.save()
-        .map_err(|e| crate::errors::AppError::Store(e.to_string()))?;
+    st.save()?;
+    drop(g);
+    // 历史只是辅助信息，记录失败不影响保存结果
+    if let Err(e) = history_service::record(app, state, reason) {
+        eprintln!("[history] record failed: {e}");
+    }
     Ok(())
 }
 
-pub fn export_toml_to_file(
-    app: &AppHandle,
+pub fn render_toml(cfg: &FrpcConfig, instance: Option<&FrpcInstance>) -> Result<String> {
+    Ok(toml::to_string_pretty(&cfg.to_export(instance))?)
+}
+
+/// 按真实本地地址导出某个 profile 的 frpc 配置，供用户另存或交给其他机器使用
+pub fn export_config(state: &AppState, profile_id: &str, format: ConfigFormat) -> Result<String> {
+    vault_service::ensure_unlocked(state)?;
+    let g = state.read();
+    let cfg = &g
+        .profile(profile_id)
+        .ok_or_else(|| AppError::Other(format!("profile not found: {profile_id}")))?
+        .config;
+    render_value(&cfg.to_export(None), format)
+}
+
+pub fn export_toml_to_file<H: Host>(
+    app: &H,
     state: &AppState,
-    proc_state: &FrpcProcState,
+    profile_id: &str,
+    instance: &FrpcInstance,
 ) -> Result<String> {
-    let cfg = state.read().config.clone();
-    let dto = cfg.to_export(proc_state);
-    let toml_str = toml::to_string_pretty(&dto)?;
+    vault_service::ensure_unlocked(state)?;
+    let cfg = state
+        .read()
+        .profile(profile_id)
+        .map(|p| p.config.clone())
+        .ok_or_else(|| AppError::Other(format!("profile not found: {profile_id}")))?;
+    // 丢弃上次导出后未被 shim 取走的 specs
+    if let Ok(mut g) = instance.proxy_specs.lock() {
+        g.clear();
+    }
+    let toml_str = render_toml(&cfg, Some(instance))?;
     let dir = app_config_dir(app);
     std::fs::create_dir_all(&dir)?;
-    let path = dir.join(CONFIG_TOML_FILE);
-    std::fs::write(&path, toml_str)?;
+    let path = dir.join(instance_toml_file(profile_id));
+    write_private(&path, &toml_str)?;
     Ok(path.display().to_string())
 }
 
-pub fn save_server_config(
-    app: &AppHandle,
+/// toml 里是明文的 token / 密码：只允许当前用户读写
+pub fn write_private(path: &Path, content: &str) -> Result<()> {
+    let mut opts = OpenOptions::new();
+    opts.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut opts, 0o600);
+    let mut file = opts.open(path)?;
+    // 文件已存在时 mode 不生效，需要再收紧一次
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(content.as_bytes())?;
+    Ok(())
+}
+
+/// frpc 退出后删除生成的 toml，不在磁盘上留下明文凭据
+pub fn remove_toml_file<H: Host>(app: &H, profile_id: &str) {
+    let path = app_config_dir(app).join(instance_toml_file(profile_id));
+    if let Err(e) = std::fs::remove_file(&path) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            eprintln!("[config] remove {} failed: {e}", path.display());
+        }
+    }
+}
+
+pub fn save_server_config<H: Host>(
+    app: &H,
     state: &AppState,
+    profile_id: &str,
     frpc_config: FrpcConfig,
 ) -> Result<()> {
     {
         let mut g = state.write();
-        let old_proxies = std::mem::take(&mut g.config.proxies);
-        g.config = FrpcConfig {
-            proxies: old_proxies,
+        let cfg = &mut g
+            .profile_mut(profile_id)
+            .ok_or_else(|| AppError::Other(format!("profile not found: {profile_id}")))?
+            .config;
+        let candidate = FrpcConfig {
+            proxies: std::mem::take(&mut cfg.proxies),
+            visitors: std::mem::take(&mut cfg.visitors),
             ..frpc_config
         };
+        // 只拦服务端设置本身的错误，已有代理的问题不影响保存
+        let report = validate_config(&candidate).only(None);
+        if let Some(msg) = report.error_summary() {
+            cfg.proxies = candidate.proxies;
+            cfg.visitors = candidate.visitors;
+            return Err(AppError::Validation(msg));
+        }
+        *cfg = candidate;
     };
-    save_now(app, state)?;
+    save_with_reason(app, state, "save server settings")?;
     Ok(())
 }
+
+/// 解析已有的 frpc 配置文件（toml / ini / yaml / json）。
+/// apply 为 true 时：服务端设置整体替换，代理与访问端按名称合并（同名覆盖、保留原 id）。
+pub fn import_config<H: Host>(
+    app: &H,
+    state: &AppState,
+    path: &str,
+    apply: bool,
+) -> Result<ImportReport> {
+    let path = std::path::Path::new(path);
+    let content = std::fs::read_to_string(path)?;
+    let format = ConfigFormat::detect(path, &content);
+    let value = parse_to_value(&content, format)?;
+    let report = import::import_config(value, format);
+    if !apply {
+        return Ok(report);
+    }
+    // 未解锁时内存里是密文，合并进明文后再保存会混在一起
+    vault_service::ensure_unlocked(state)?;
+
+    {
+        let mut g = state.write();
+        let cfg = g.config_mut();
+        let imported = report.config.clone();
+        let mut proxies = std::mem::take(&mut cfg.proxies);
+        for mut p in imported.proxies {
+            if let Some(idx) = proxies.iter().position(|x| x.name == p.name) {
+                p.id = proxies[idx].id.clone();
+                proxies[idx] = p;
+            } else {
+                proxies.push(p);
+            }
+        }
+        let mut visitors = std::mem::take(&mut cfg.visitors);
+        for mut v in imported.visitors {
+            if let Some(idx) = visitors.iter().position(|x| x.name == v.name) {
+                v.id = visitors[idx].id.clone();
+                visitors[idx] = v;
+            } else {
+                visitors.push(v);
+            }
+        }
+        *cfg = FrpcConfig {
+            proxies,
+            visitors,
+            restart: cfg.restart.clone(),
+            ..imported
+        };
+    }
+    let file = path.file_name().unwrap_or_default().to_string_lossy();
+    save_with_reason(app, state, &format!("import {file}"))?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::DaemonHost;
+    use crate::domain::proxy::{
+        HttpSwitch, HttpsPlugin, HttpsPluginOptions, HttpsProxy, Proxy, ProxyCommon, SecretProxy,
+        TcpProxy, UdpProxy,
+    };
+    use crate::domain::types::DomainType;
+    use crate::domain::visitor::{Visitor, VisitorCommon, XtcpVisitor};
+    use crate::services::local_proxy::ShimListener;
+
+    fn base() -> FrpcConfig {
+        FrpcConfig {
+            server_addr: "frp.example.com".into(),
+            server_port: 7000,
+            ..Default::default()
+        }
+    }
+
+    fn common(name: &str, local_port: u16) -> ProxyCommon {
+        ProxyCommon {
+            id: format!("id-{name}"),
+            name: name.into(),
+            enable: true,
+            local_ip: "10.0.0.5".into(),
+            local_port,
+            ..Default::default()
+        }
+    }
+
+    // 渲染后再解析回来，按键比较，不依赖输出顺序
+    fn render(cfg: &FrpcConfig, instance: Option<&FrpcInstance>) -> toml::Table {
+        render_toml(cfg, instance).unwrap().parse().unwrap()
+    }
+
+    fn table(s: &str) -> toml::Table {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn import_into_locked_vault_leaves_profile_unchanged() {
+        let dir = std::env::temp_dir().join(format!("frpc-import-{}", uuid::Uuid::new_v4()));
+        let app = DaemonHost::new(dir.clone(), dir.clone());
+        let state = app.app_state();
+        state.write().vault.enabled = true;
+        let before = serde_json::to_value(state.read().config()).unwrap();
+
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("frpc.toml");
+        std::fs::write(&path, include_str!("../../tests/fixtures/import/frpc.toml")).unwrap();
+        let path = path.to_string_lossy();
+
+        // 只预览不受影响
+        let report = import_config(&app, &state, &path, false).unwrap();
+        assert!(!report.config.proxies.is_empty());
+
+        let err = import_config(&app, &state, &path, true).unwrap_err();
+        assert!(err.to_string().contains("locked"), "{err}");
+        assert_eq!(serde_json::to_value(state.read().config()).unwrap(), before);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn renders_secret_proxies() {
+        let mut cfg = base();
+        let secret = |name: &str, port: u16, allow: &[&str]| SecretProxy {
+            common: common(name, port),
+            secret_key: format!("{name}-key"),
+            allow_users: allow.iter().map(|s| s.to_string()).collect(),
+        };
+        cfg.proxies = vec![
+            Proxy::Stcp(secret("ssh", 22, &["alice", "bob"])),
+            Proxy::Sudp(secret("dns", 53, &[])),
+            Proxy::Xtcp(secret("rdp", 3389, &["*"])),
+        ];
+        let expected = table(
+            r#"
+            [[proxies]]
+            name = "ssh"
+            type = "stcp"
+            localIP = "10.0.0.5"
+            localPort = 22
+            secretKey = "ssh-key"
+            allowUsers = ["alice", "bob"]
+
+            [[proxies]]
+            name = "dns"
+            type = "sudp"
+            localIP = "10.0.0.5"
+            localPort = 53
+            secretKey = "dns-key"
+
+            [[proxies]]
+            name = "rdp"
+            type = "xtcp"
+            localIP = "10.0.0.5"
+            localPort = 3389
+            secretKey = "rdp-key"
+            allowUsers = ["*"]
+            "#,
+        );
+        assert_eq!(render(&cfg, None)["proxies"], expected["proxies"]);
+    }
+
+    #[test]
+    fn renders_visitors() {
+        let mut cfg = base();
+        let visitor = |name: &str, server_name: &str, bind_port: i32| VisitorCommon {
+            id: format!("id-{name}"),
+            name: name.into(),
+            enable: true,
+            server_user: String::new(),
+            server_name: server_name.into(),
+            secret_key: "k".into(),
+            bind_addr: "127.0.0.1".into(),
+            bind_port,
+        };
+        let mut dns = visitor("dns-visitor", "dns", 6053);
+        dns.server_user = "bob".into();
+        dns.bind_addr.clear();
+        let mut off = visitor("off", "ssh", 6001);
+        off.enable = false;
+        cfg.visitors = vec![
+            Visitor::Stcp(visitor("ssh-visitor", "ssh", 6000)),
+            Visitor::Sudp(dns),
+            Visitor::Xtcp(XtcpVisitor {
+                common: visitor("rdp-visitor", "rdp", 6389),
+                protocol: "quic".into(),
+                keep_tunnel_open: true,
+                max_retries_an_hour: 8,
+                min_retry_interval: 90,
+                fallback_to: "ssh-visitor".into(),
+                fallback_timeout_ms: 1000,
+            }),
+            // 只作为 fallback 目标、不监听的 xtcp，可选项全部留空
+            Visitor::Xtcp(XtcpVisitor {
+                common: visitor("rdp-raw", "rdp", -1),
+                ..Default::default()
+            }),
+            Visitor::Stcp(off),
+        ];
+        let expected = table(
+            r#"
+            [[visitors]]
+            name = "ssh-visitor"
+            type = "stcp"
+            serverName = "ssh"
+            secretKey = "k"
+            bindAddr = "127.0.0.1"
+            bindPort = 6000
+
+            [[visitors]]
+            name = "dns-visitor"
+            type = "sudp"
+            serverUser = "bob"
+            serverName = "dns"
+            secretKey = "k"
+            bindPort = 6053
+
+            [[visitors]]
+            name = "rdp-visitor"
+            type = "xtcp"
+            serverName = "rdp"
+            secretKey = "k"
+            bindAddr = "127.0.0.1"
+            bindPort = 6389
+            protocol = "quic"
+            keepTunnelOpen = true
+            maxRetriesAnHour = 8
+            minRetryInterval = 90
+            fallbackTo = "ssh-visitor"
+            fallbackTimeoutMs = 1000
+
+            [[visitors]]
+            name = "rdp-raw"
+            type = "xtcp"
+            serverName = "rdp"
+            secretKey = "k"
+            bindAddr = "127.0.0.1"
+            bindPort = -1
+            "#,
+        );
+        let rendered = render(&cfg, None);
+        assert_eq!(rendered["visitors"], expected["visitors"]);
+        assert!(!rendered.contains_key("proxies"));
+    }
+
+    #[test]
+    fn renders_https_domains_and_plugins() {
+        let mut cfg = base();
+        let https = |name: &str, domain: DomainType, plugin: Option<HttpsPlugin>| {
+            Proxy::Https(HttpsProxy {
+                common: common(name, 8443),
+                subdomain: format!("{name}-sub"),
+                custom_domains: vec![format!("{name}.example.com")],
+                switch: HttpSwitch {
+                    domain,
+                    auth: false,
+                },
+                plugin,
+            })
+        };
+        let headers = [("x-from-where".to_string(), "frp".to_string())].into();
+        cfg.proxies = vec![
+            https("plain", DomainType::Sub, None),
+            https(
+                "to-http",
+                DomainType::Custom,
+                Some(HttpsPlugin::Https2http(HttpsPluginOptions {
+                    crt_path: "./server.crt".into(),
+                    key_path: "./server.key".into(),
+                    host_header_rewrite: "127.0.0.1".into(),
+                    request_headers: headers,
+                })),
+            ),
+            https(
+                "to-https",
+                DomainType::Sub,
+                Some(HttpsPlugin::Https2https(HttpsPluginOptions::default())),
+            ),
+        ];
+        // 启用插件后本地地址只出现在 plugin.localAddr 里
+        let expected = table(
+            r#"
+            [[proxies]]
+            name = "plain"
+            type = "https"
+            localIP = "10.0.0.5"
+            localPort = 8443
+            subdomain = "plain-sub"
+
+            [[proxies]]
+            name = "to-http"
+            type = "https"
+            customDomains = ["to-http.example.com"]
+            [proxies.plugin]
+            type = "https2http"
+            localAddr = "10.0.0.5:8443"
+            crtPath = "./server.crt"
+            keyPath = "./server.key"
+            hostHeaderRewrite = "127.0.0.1"
+            requestHeaders.set.x-from-where = "frp"
+
+            [[proxies]]
+            name = "to-https"
+            type = "https"
+            subdomain = "to-https-sub"
+            [proxies.plugin]
+            type = "https2https"
+            localAddr = "10.0.0.5:8443"
+            "#,
+        );
+        assert_eq!(render(&cfg, None)["proxies"], expected["proxies"]);
+    }
+
+    fn tcp_and_udp() -> FrpcConfig {
+        let mut cfg = base();
+        let mut ssh = common("ssh", 22);
+        ssh.proxy_protocol = true;
+        cfg.proxies = vec![
+            Proxy::Tcp(TcpProxy {
+                common: ssh,
+                remote_port: 6000,
+            }),
+            Proxy::Udp(UdpProxy {
+                common: common("dns", 53),
+                remote_port: 6053,
+            }),
+        ];
+        cfg
+    }
+
+    #[test]
+    fn renders_remote_port_for_direct_proxies() {
+        let expected = table(
+            r#"
+            [[proxies]]
+            name = "ssh"
+            type = "tcp"
+            localIP = "10.0.0.5"
+            localPort = 22
+            remotePort = 6000
+
+            [[proxies]]
+            name = "dns"
+            type = "udp"
+            localIP = "10.0.0.5"
+            localPort = 53
+            remotePort = 6053
+            "#,
+        );
+        assert_eq!(render(&tcp_and_udp(), None)["proxies"], expected["proxies"]);
+    }
+
+    // 预留 shim 端口要在 tokio 运行时里注册监听
+    #[tokio::test]
+    async fn renders_remote_port_through_shim() {
+        let instance = FrpcInstance::default();
+        let rendered = render(&tcp_and_udp(), Some(&instance));
+        let proxies = rendered["proxies"].as_array().unwrap();
+        let specs = instance.proxy_specs.lock().unwrap();
+        assert_eq!(proxies.len(), 2);
+        assert_eq!(specs.len(), 2);
+
+        for (proxy, spec) in proxies.iter().zip(specs.iter()) {
+            let listen = match spec.listener.as_ref().unwrap() {
+                ShimListener::Tcp(l) => l.local_addr().unwrap(),
+                ShimListener::Udp(s) => s.local_addr().unwrap(),
+            };
+            assert_eq!(proxy["localIP"].as_str(), Some("127.0.0.1"));
+            assert_eq!(proxy["localPort"].as_integer(), Some(listen.port() as i64));
+        }
+
+        let ssh = proxies[0].as_table().unwrap();
+        assert_eq!(ssh["remotePort"].as_integer(), Some(6000));
+        assert_eq!(
+            ssh["transport"]["proxyProtocolVersion"].as_str(),
+            Some("v2")
+        );
+        assert!(matches!(specs[0].listener, Some(ShimListener::Tcp(_))));
+        assert_eq!(specs[0].target, "10.0.0.5:22".parse().unwrap());
+        assert!(specs[0].proxy_protocol);
+
+        // UDP 不经 TCP 转发，不带 PROXY protocol
+        let dns = proxies[1].as_table().unwrap();
+        assert_eq!(dns["remotePort"].as_integer(), Some(6053));
+        assert!(!dns.contains_key("transport"));
+        assert!(matches!(specs[1].listener, Some(ShimListener::Udp(_))));
+        assert_eq!(specs[1].target, "10.0.0.5:53".parse().unwrap());
+        assert!(!specs[1].proxy_protocol);
+    }
+}
@@ -0,0 +1,177 @@
+use crate::domain::config::FrpcConfig;
+use crate::domain::history::{diff_configs, ConfigChange, ConfigSnapshot, SnapshotSummary};
+use crate::domain::proxy::gen_id;
+use crate::errors::{AppError, Result};
+use crate::host::{Host, KvStore};
+use crate::infra::crypto::SecretKey;
+use crate::infra::paths::HISTORY_FILE;
+use crate::infra::store::{store, HISTORY_KEY};
+use crate::services::config_service::save_with_reason;
+use crate::services::vault_service::{locked_error, open_config, seal_config};
+use crate::state::AppState;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// 每个 profile 最多保留的快照数，超出后丢弃最旧的
+const MAX_SNAPSHOTS: usize = 50;
+
+fn history_store<H: Host>(app: &H) -> Result<Arc<dyn KvStore>> {
+    store(app, HISTORY_FILE)
+}
+
+// 按时间正序；开启加密时敏感字段仍是密文
+fn load_raw(st: &dyn KvStore) -> Vec<ConfigSnapshot> {
+    st.get(HISTORY_KEY)
+        .and_then(|json| serde_json::from_value(json).ok())
+        .unwrap_or_default()
+}
+
+fn load(st: &dyn KvStore, key: Option<&SecretKey>) -> Result<Vec<ConfigSnapshot>> {
+    let mut snapshots = load_raw(st);
+    for s in snapshots.iter_mut() {
+        open_config(&mut s.config, key)?;
+    }
+    Ok(snapshots)
+}
+
+fn write(
+    st: &dyn KvStore,
+    mut snapshots: Vec<ConfigSnapshot>,
+    key: Option<&SecretKey>,
+) -> Result<()> {
+    if let Some(key) = key {
+        for s in snapshots.iter_mut() {
+            seal_config(&mut s.config, key)?;
+        }
+    }
+    st.set(HISTORY_KEY, serde_json::to_value(&snapshots)?);
+    st.save()
+}
+
+// 未解锁时返回未解锁错误
+fn unlocked_key(state: &AppState) -> Result<Option<SecretKey>> {
+    let g = state.read();
+    if g.vault.locked() {
+        return Err(locked_error());
+    }
+    Ok(g.vault.key.clone())
+}
+
+/// 开启 / 关闭加密时把全部快照从旧密钥转到新密钥（None 为明文）
+pub fn reseal<H: Host>(app: &H, from: Option<&SecretKey>, to: Option<&SecretKey>) -> Result<()> {
+    let st = history_store(app)?;
+    let snapshots = load(&*st, from)?;
+    if snapshots.is_empty() {
+        return Ok(());
+    }
+    write(&*st, snapshots, to)
+}
+
+fn same_config(a: &FrpcConfig, b: &FrpcConfig) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+fn prune(snapshots: &mut Vec<ConfigSnapshot>) {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let mut keep = vec![false; snapshots.len()];
+    for (i, s) in snapshots.iter().enumerate().rev() {
+        let n = counts.entry(s.profile_id.clone()).or_default();
+        *n += 1;
+        keep[i] = *n <= MAX_SNAPSHOTS;
+    }
+    let mut keep = keep.into_iter();
+    snapshots.retain(|_| keep.next().unwrap_or(false));
+}
+
+/// 给配置与上一份快照不同的 profile 各记一份快照；没有变化时不写盘
+pub fn record<H: Host>(app: &H, state: &AppState, reason: &str) -> Result<()> {
+    // 未解锁时无法与历史中的密文比较，等解锁后再补记
+    if state.read().vault.locked() {
+        return Ok(());
+    }
+    let key = state.read().vault.key.clone();
+    let profiles: Vec<(String, FrpcConfig)> = state
+        .read()
+        .profiles
+        .iter()
+        .map(|p| (p.id.clone(), p.config.clone()))
+        .collect();
+    let st = history_store(app)?;
+    let mut snapshots = load(&*st, key.as_ref())?;
+    let created_at = chrono::Utc::now().to_rfc3339();
+    let mut changed = false;
+    for (profile_id, config) in profiles {
+        let latest = snapshots.iter().rev().find(|s| s.profile_id == profile_id);
+        if latest.is_some_and(|s| same_config(&s.config, &config)) {
+            continue;
+        }
+        snapshots.push(ConfigSnapshot {
+            id: gen_id(),
+            profile_id,
+            created_at: created_at.clone(),
+            reason: reason.to_string(),
+            config,
+        });
+        changed = true;
+    }
+    if !changed {
+        return Ok(());
+    }
+    prune(&mut snapshots);
+    write(&*st, snapshots, key.as_ref())
+}
+
+/// 按时间倒序列出某个 profile 的快照
+pub fn list<H: Host>(app: &H, profile_id: &str) -> Result<Vec<SnapshotSummary>> {
+    let st = history_store(app)?;
+    Ok(load_raw(&*st)
+        .iter()
+        .rev()
+        .filter(|s| s.profile_id == profile_id)
+        .map(SnapshotSummary::from)
+        .collect())
+}
+
+pub fn get<H: Host>(app: &H, state: &AppState, id: &str) -> Result<ConfigSnapshot> {
+    let key = unlocked_key(state)?;
+    let st = history_store(app)?;
+    let mut snapshot = load_raw(&*st)
+        .into_iter()
+        .find(|s| s.id == id)
+        .ok_or_else(|| AppError::Other(format!("snapshot not found: {id}")))?;
+    open_config(&mut snapshot.config, key.as_ref())?;
+    Ok(snapshot)
+}
+
+/// to 为空时与该 profile 当前的配置比较
+pub fn diff<H: Host>(
+    app: &H,
+    state: &AppState,
+    from: &str,
+    to: Option<&str>,
+) -> Result<Vec<ConfigChange>> {
+    let from = get(app, state, from)?;
+    let to = match to {
+        Some(id) => get(app, state, id)?.config,
+        None => state
+            .read()
+            .profile(&from.profile_id)
+            .map(|p| p.config.clone())
+            .ok_or_else(|| AppError::Other(format!("profile not found: {}", from.profile_id)))?,
+    };
+    Ok(diff_configs(&from.config, &to))
+}
+
+/// 用快照整体替换所属 profile 的配置，返回 profile id；回滚本身也会记一份快照
+pub fn rollback<H: Host>(app: &H, state: &AppState, id: &str) -> Result<String> {
+    let snapshot = get(app, state, id)?;
+    {
+        let mut g = state.write();
+        let profile = g.profile_mut(&snapshot.profile_id).ok_or_else(|| {
+            AppError::Other(format!("profile not found: {}", snapshot.profile_id))
+        })?;
+        profile.config = snapshot.config;
+    }
+    save_with_reason(app, state, &format!("rollback to {}", snapshot.created_at))?;
+    Ok(snapshot.profile_id)
+}
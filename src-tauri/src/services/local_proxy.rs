@@ -1,34 +1,260 @@
-use crate::state::FrpcProcState;
+use crate::domain::traffic::RateLimit;
+use crate::events::{EVT_CONNECTIONS, EVT_QUOTA};
+use crate::host::Host;
+use crate::infra::traffic_db::TrafficDb;
+use crate::services::quota_service::{self, QuotaEvent, QuotaWatch};
+use crate::services::traffic_service;
+use crate::state::FrpcInstance;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
 use serde_json::json;
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
+    future::Future,
     io::{Error, ErrorKind, Result},
     net::SocketAddr,
     sync::{
-        atomic::{AtomicU64, Ordering},
-        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
-use tauri::{AppHandle, Emitter};
 use tokio::task::JoinHandle;
 use tokio::{
     io,
-    io::{AsyncRead, AsyncWrite, ReadBuf},
-    net::{TcpListener, TcpStream},
-    time::{interval, MissedTickBehavior},
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, ReadBuf},
+    net::{TcpListener, TcpStream, UdpSocket},
+    time::{interval, sleep, timeout, MissedTickBehavior, Sleep},
 };
 
+// UDP 会话超过该时长两个方向都没有数据即回收，与 frpc 自身的 UDP 超时一致
+const UDP_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+const UDP_BUF_SIZE: usize = 64 * 1024;
+// 等待 frpc 发来完整 PROXY protocol 头的上限，超时即断开连接
+const PROXY_HEADER_WAIT: Duration = Duration::from_secs(1);
+const PROXY_V2_SIG: &[u8; 12] = b"\r\n\r\n\0\r\nQUIT\n";
+// 采样多少次写一次流量历史；停止或替换监听时会立即写入剩余部分
+const PERSIST_EVERY: u32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShimProtocol {
+    Tcp,
+    Udp,
+}
+
+pub enum ShimListener {
+    Tcp(TcpListener),
+    Udp(UdpSocket),
+}
+
+impl ShimListener {
+    fn protocol(&self) -> ShimProtocol {
+        match self {
+            ShimListener::Tcp(_) => ShimProtocol::Tcp,
+            ShimListener::Udp(_) => ShimProtocol::Udp,
+        }
+    }
+    fn local_addr(&self) -> Result<SocketAddr> {
+        match self {
+            ShimListener::Tcp(l) => l.local_addr(),
+            ShimListener::Udp(s) => s.local_addr(),
+        }
+    }
+}
+
 pub struct ProxySpec {
     pub id: String,
-    pub listener: TcpListener,
+    // None：该代理已有同目标的监听，沿用其端口
+    pub listener: Option<ShimListener>,
     pub target: SocketAddr,
+    // frpc 会在每个连接前发 PROXY protocol v2 头，shim 从中取真实来源地址后剥掉
+    pub proxy_protocol: bool,
+    pub rate_limit: RateLimit,
 }
 
-#[derive(Clone, Default)]
+/// 正在运行的一条 shim 转发
+pub struct ShimRoute {
+    pub protocol: ShimProtocol,
+    pub proxy_protocol: bool,
+    pub listen: SocketAddr,
+    pub target: SocketAddr,
+    pub stats: Arc<ProxyStats>,
+    pub conns: Arc<ConnTable>,
+    pub task: JoinHandle<()>,
+}
+
+/// 一条经过 shim 的 TCP 连接
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnInfo {
+    pub id: u64,
+    pub proxy: String,
+    // 拿到 PROXY protocol 头时为真实访问者地址，否则是 frpc 的本地地址
+    pub peer: SocketAddr,
+    pub target: SocketAddr,
+    // rfc3339
+    pub started_at: String,
+    pub up: u64,
+    pub down: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionsPayload {
+    pub instance: String,
+    pub connections: Vec<ConnInfo>,
+}
+
+struct ConnEntry {
+    peer: SocketAddr,
+    target: SocketAddr,
+    started_at: DateTime<Utc>,
+    up: Arc<AtomicU64>,
+    down: Arc<AtomicU64>,
+    abort: tokio::task::AbortHandle,
+}
+
+/// 某个代理当前的连接；连接任务结束时自行移除
+#[derive(Default)]
+pub struct ConnTable {
+    next_id: AtomicU64,
+    conns: Mutex<HashMap<u64, ConnEntry>>,
+}
+
+impl ConnTable {
+    pub fn snapshot(&self, proxy: &str) -> Vec<ConnInfo> {
+        let Ok(g) = self.conns.lock() else {
+            return Vec::new();
+        };
+        let mut list: Vec<ConnInfo> = g
+            .iter()
+            .map(|(id, c)| ConnInfo {
+                id: *id,
+                proxy: proxy.to_string(),
+                peer: c.peer,
+                target: c.target,
+                started_at: c.started_at.to_rfc3339(),
+                up: c.up.load(Ordering::Relaxed),
+                down: c.down.load(Ordering::Relaxed),
+            })
+            .collect();
+        list.sort_by_key(|c| c.id);
+        list
+    }
+
+    /// 断开连接；conn_id 为 None 时断开全部，返回断开的数量
+    pub fn kill(&self, conn_id: Option<u64>) -> usize {
+        let Ok(mut g) = self.conns.lock() else {
+            return 0;
+        };
+        let ids: Vec<u64> = match conn_id {
+            Some(id) => g.contains_key(&id).then_some(id).into_iter().collect(),
+            None => g.keys().copied().collect(),
+        };
+        for id in &ids {
+            if let Some(c) = g.remove(id) {
+                c.abort.abort();
+            }
+        }
+        ids.len()
+    }
+
+    /// 登记一条连接。spawn 在持锁期间启动连接任务并返回其 abort 句柄：
+    /// 任务结束时的移除要等登记完成，kill 拿到的条目也总带着句柄
+    fn insert(
+        &self,
+        peer: SocketAddr,
+        target: SocketAddr,
+        spawn: impl FnOnce(u64, Arc<AtomicU64>, Arc<AtomicU64>) -> tokio::task::AbortHandle,
+    ) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let (up, down) = (Arc::new(AtomicU64::new(0)), Arc::new(AtomicU64::new(0)));
+        let Ok(mut g) = self.conns.lock() else {
+            return;
+        };
+        let abort = spawn(id, up.clone(), down.clone());
+        g.insert(
+            id,
+            ConnEntry {
+                peer,
+                target,
+                started_at: Utc::now(),
+                up,
+                down,
+                abort,
+            },
+        );
+    }
+
+    fn update(&self, id: u64, f: impl FnOnce(&mut ConnEntry)) {
+        if let Some(c) = self.conns.lock().ok().as_mut().and_then(|g| g.get_mut(&id)) {
+            f(c);
+        }
+    }
+
+    fn remove(&self, id: u64) {
+        if let Ok(mut g) = self.conns.lock() {
+            g.remove(&id);
+        }
+    }
+}
+
+// 连接任务结束（含被 abort）时从表中移除
+struct ConnGuard {
+    table: Arc<ConnTable>,
+    id: u64,
+}
+
+impl Drop for ConnGuard {
+    fn drop(&mut self) {
+        self.table.remove(self.id);
+    }
+}
+
+/// 列出某个实例的连接，proxy_id 为 None 时列出全部代理的
+pub fn list_connections(instance: &FrpcInstance, proxy_id: Option<&str>) -> Vec<ConnInfo> {
+    let Ok(routes) = instance.shim_routes.lock() else {
+        return Vec::new();
+    };
+    routes
+        .iter()
+        .filter(|(id, _)| proxy_id.is_none_or(|p| p == id.as_str()))
+        .flat_map(|(id, r)| r.conns.snapshot(id))
+        .collect()
+}
+
+/// 立即调整某个代理的限速，返回该代理是否有正在运行的监听
+pub fn set_rate_limit(instance: &FrpcInstance, proxy_id: &str, limit: &RateLimit) -> bool {
+    let Ok(routes) = instance.shim_routes.lock() else {
+        return false;
+    };
+    match routes.get(proxy_id) {
+        Some(r) => {
+            r.stats.limit.set(limit);
+            true
+        }
+        None => false,
+    }
+}
+
+/// 断开某个代理的一条或全部连接，返回断开的数量
+pub fn kill_connections(instance: &FrpcInstance, proxy_id: &str, conn_id: Option<u64>) -> usize {
+    let conns = match instance.shim_routes.lock() {
+        Ok(routes) => routes.get(proxy_id).map(|r| r.conns.clone()),
+        Err(_) => None,
+    };
+    conns.map_or(0, |c| c.kill(conn_id))
+}
+
+#[derive(Default)]
 pub struct ProxyStats {
     up_total: Arc<AtomicU64>,
     down_total: Arc<AtomicU64>,
+    // 已写入流量历史的部分
+    up_saved: AtomicU64,
+    down_saved: AtomicU64,
+    // 超出配额后拒绝新连接，直到周期重置
+    blocked: AtomicBool,
+    limit: ProxyLimiter,
 }
 
 impl ProxyStats {
@@ -36,17 +262,266 @@ impl ProxyStats {
     fn new() -> Self {
         Self::default()
     }
+
+    /// 取出上次调用以来新增的字节数；swap 保证并发调用时不会重复计入
+    fn take_unsaved(&self) -> (u64, u64) {
+        let up = self.up_total.load(Ordering::Relaxed);
+        let down = self.down_total.load(Ordering::Relaxed);
+        (
+            up.saturating_sub(self.up_saved.swap(up, Ordering::Relaxed)),
+            down.saturating_sub(self.down_saved.swap(down, Ordering::Relaxed)),
+        )
+    }
+
+    // 尚未写入流量历史的上行加下行，不改变已保存的位置
+    fn unsaved_bytes(&self) -> u64 {
+        let up = self.up_total.load(Ordering::Relaxed);
+        let down = self.down_total.load(Ordering::Relaxed);
+        up.saturating_sub(self.up_saved.load(Ordering::Relaxed))
+            .saturating_add(down.saturating_sub(self.down_saved.load(Ordering::Relaxed)))
+    }
+}
+
+fn pending_usage(routes: &HashMap<String, ShimRoute>) -> HashMap<String, u64> {
+    routes
+        .iter()
+        .map(|(id, r)| (id.clone(), r.stats.unsaved_bytes()))
+        .collect()
+}
+
+/// 各代理尚未写入流量历史的字节数，查询配额用量时需要加上
+pub fn unsaved_usage(instance: &FrpcInstance) -> HashMap<String, u64> {
+    instance
+        .shim_routes
+        .lock()
+        .map(|g| pending_usage(&g))
+        .unwrap_or_default()
+}
+
+fn unsaved<'a>(
+    routes: impl Iterator<Item = (&'a String, &'a ShimRoute)>,
+) -> Vec<(String, u64, u64)> {
+    routes
+        .map(|(id, r)| {
+            let (up, down) = r.stats.take_unsaved();
+            (id.clone(), up, down)
+        })
+        .collect()
+}
+
+// 超出配额的代理拒绝新连接并断开已有连接，周期重置后恢复
+fn apply_quota(routes: &Mutex<HashMap<String, ShimRoute>>, exceeded: &HashSet<String>) {
+    let Ok(g) = routes.lock() else {
+        return;
+    };
+    for (id, r) in g.iter() {
+        let block = exceeded.contains(id);
+        if r.stats.blocked.swap(block, Ordering::Relaxed) == block {
+            continue;
+        }
+        if block {
+            let n = r.conns.kill(None);
+            eprintln!("[shim:{id}] quota exceeded, blocked ({n} connections closed)");
+        } else {
+            eprintln!("[shim:{id}] quota reset, accepting connections");
+        }
+    }
+}
+
+/// 采样任务复用的流量库连接：第一次用到时打开，打开失败下次再试
+#[derive(Clone, Default)]
+struct SharedDb(Arc<Mutex<Option<TrafficDb>>>);
+
+impl SharedDb {
+    fn with<H: Host, T>(
+        &self,
+        app: &H,
+        f: impl FnOnce(&mut TrafficDb) -> crate::errors::Result<T>,
+    ) -> crate::errors::Result<T> {
+        let mut g = self.0.lock().expect("poisoned");
+        if g.is_none() {
+            *g = Some(traffic_service::open(app)?);
+        }
+        f(g.as_mut().expect("opened above"))
+    }
 }
 
-/// 只在读侧做计数，避免双计数
+async fn check_quota<H: Host>(
+    app: &H,
+    db: &SharedDb,
+    instance_id: &str,
+    routes: &Mutex<HashMap<String, ShimRoute>>,
+    watch: &mut QuotaWatch,
+) {
+    let pending = match routes.lock() {
+        Ok(g) => pending_usage(&g),
+        Err(_) => return,
+    };
+    let app2 = app.clone();
+    let db = db.clone();
+    let id = instance_id.to_string();
+    let statuses = match tokio::task::spawn_blocking(move || {
+        db.with(&app2, |db| {
+            quota_service::statuses(&app2, db, &id, &pending)
+        })
+    })
+    .await
+    {
+        Ok(Ok(s)) => s,
+        Ok(Err(e)) => return eprintln!("[quota:{instance_id}] check failed: {e}"),
+        Err(e) => return eprintln!("[quota:{instance_id}] check failed: {e}"),
+    };
+    apply_quota(routes, &quota_service::exceeded(&statuses));
+    for status in watch.changes(&statuses) {
+        app.emit(
+            EVT_QUOTA,
+            QuotaEvent {
+                instance: instance_id.to_string(),
+                status,
+            },
+        );
+    }
+}
+
+// sqlite 写入放到阻塞线程里；失败只记录日志，不影响转发
+async fn persist_traffic<H: Host>(
+    app: &H,
+    db: &SharedDb,
+    instance_id: &str,
+    deltas: Vec<(String, u64, u64)>,
+) {
+    if deltas.iter().all(|(_, up, down)| *up == 0 && *down == 0) {
+        return;
+    }
+    let app = app.clone();
+    let db = db.clone();
+    let id = instance_id.to_string();
+    let r = tokio::task::spawn_blocking(move || {
+        db.with(&app, |db| traffic_service::record(&app, db, &id, deltas))
+    })
+    .await;
+    match r {
+        Ok(Err(e)) => eprintln!("[traffic:{instance_id}] persist failed: {e}"),
+        Err(e) => eprintln!("[traffic:{instance_id}] persist failed: {e}"),
+        Ok(Ok(())) => {}
+    }
+}
+
+/// ===================== 限速：令牌桶 =====================
+struct BucketState {
+    rate: u64,
+    burst: u64,
+    // 可以为负，表示已经透支、需要等待补足
+    tokens: f64,
+    last: Instant,
+}
+
+/// 速率为 0 表示不限；速率与容量可随时调整，已建立的连接立即按新值限速
+pub struct TokenBucket {
+    state: Mutex<BucketState>,
+}
+
+impl Default for TokenBucket {
+    fn default() -> Self {
+        Self {
+            state: Mutex::new(BucketState {
+                rate: 0,
+                burst: 0,
+                tokens: 0.0,
+                last: Instant::now(),
+            }),
+        }
+    }
+}
+
+impl TokenBucket {
+    fn set(&self, rate: u64, burst: u64) {
+        let Ok(mut st) = self.state.lock() else {
+            return;
+        };
+        // 从不限速改为限速时以满桶开始
+        if st.rate == 0 {
+            st.tokens = burst as f64;
+            st.last = Instant::now();
+        }
+        st.rate = rate;
+        st.burst = burst;
+        st.tokens = st.tokens.min(burst as f64);
+    }
+
+    fn refill(st: &mut BucketState) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(st.last).as_secs_f64();
+        st.last = now;
+        st.tokens = (st.tokens + elapsed * st.rate as f64).min(st.burst as f64);
+    }
+
+    /// 已经读到 n 字节后扣除令牌，返回还清透支需要等待的时长
+    fn consume(&self, n: usize) -> Option<Duration> {
+        let mut st = self.state.lock().ok()?;
+        if st.rate == 0 {
+            return None;
+        }
+        Self::refill(&mut st);
+        st.tokens -= n as f64;
+        (st.tokens < 0.0).then(|| Duration::from_secs_f64(-st.tokens / st.rate as f64))
+    }
+
+    /// UDP 不排队：还有余额就放行并允许透支，已透支时直接丢包。
+    /// 与 consume 一样按平均速率限速，比容量大的数据报也不会被一直丢弃
+    fn try_take(&self, n: usize) -> bool {
+        let Ok(mut st) = self.state.lock() else {
+            return true;
+        };
+        if st.rate == 0 {
+            return true;
+        }
+        Self::refill(&mut st);
+        if st.tokens <= 0.0 {
+            return false;
+        }
+        st.tokens -= n as f64;
+        true
+    }
+}
+
+/// 一个代理两个方向各一个桶，该代理的全部连接共用
+#[derive(Default)]
+pub struct ProxyLimiter {
+    up: Arc<TokenBucket>,
+    down: Arc<TokenBucket>,
+}
+
+impl ProxyLimiter {
+    pub fn set(&self, limit: &RateLimit) {
+        self.up.set(
+            limit.up_bytes_per_sec,
+            limit.burst_for(limit.up_bytes_per_sec),
+        );
+        self.down.set(
+            limit.down_bytes_per_sec,
+            limit.burst_for(limit.down_bytes_per_sec),
+        );
+    }
+}
+
+/// 只在读侧做计数，避免双计数；同一份字节数累加到每个计数器（代理合计与单个连接）。
+/// 限速也在读侧：读到数据后扣令牌，透支时下一次读之前先等待
 pub struct CountRead<T> {
     inner: T,
-    counter: Arc<AtomicU64>,
+    counters: [Arc<AtomicU64>; 2],
+    bucket: Arc<TokenBucket>,
+    delay: Option<std::pin::Pin<Box<Sleep>>>,
 }
 impl<T> CountRead<T> {
     #[inline]
-    pub fn new(inner: T, counter: Arc<AtomicU64>) -> Self {
-        Self { inner, counter }
+    pub fn new(inner: T, counters: [Arc<AtomicU64>; 2], bucket: Arc<TokenBucket>) -> Self {
+        Self {
+            inner,
+            counters,
+            bucket,
+            delay: None,
+        }
     }
 }
 impl<T: AsyncRead + Unpin> AsyncRead for CountRead<T> {
@@ -55,13 +530,23 @@ impl<T: AsyncRead + Unpin> AsyncRead for CountRead<T> {
         cx: &mut std::task::Context<'_>,
         buf: &mut ReadBuf<'_>,
     ) -> std::task::Poll<Result<()>> {
+        if let Some(delay) = self.delay.as_mut() {
+            if delay.as_mut().poll(cx).is_pending() {
+                return std::task::Poll::Pending;
+            }
+            self.delay = None;
+        }
         let before = buf.filled().len();
         let r = std::pin::Pin::new(&mut self.inner).poll_read(cx, buf);
         if let std::task::Poll::Ready(Ok(())) = &r {
             let now = buf.filled().len();
             if now > before {
-                self.counter
-                    .fetch_add((now - before) as u64, Ordering::Relaxed);
+                for c in &self.counters {
+                    c.fetch_add((now - before) as u64, Ordering::Relaxed);
+                }
+                if let Some(wait) = self.bucket.consume(now - before) {
+                    self.delay = Some(Box::pin(sleep(wait)));
+                }
             }
         }
         r
@@ -93,96 +578,223 @@ impl<T: AsyncWrite + Unpin> AsyncWrite for CountRead<T> {
 }
 
 /// ===================== 启动 shim + 采样 =====================
-
-pub async fn run_tcp_shim(app: AppHandle, proc_state: &FrpcProcState) -> Result<()> {
-    {
-        let mut g = proc_state
-            .shim_tasks
-            .lock()
-            .map_err(|_| Error::new(ErrorKind::Other, "lock shim_tasks"))?;
-        for h in g.drain(..) {
-            h.abort(); // 立即取消任务；监听 socket 会被 drop，从而释放端口
-        }
-    }
+/// 按本次导出的 specs 对已有监听做增量调整：
+/// 不再出现的代理关闭监听，新代理开始监听，端口与目标都没变的原样保留（已建立的连接不受影响）
+pub async fn run_shim<H: Host>(app: H, instance_id: &str, instance: &FrpcInstance) -> Result<()> {
     // 取出 specs 所有权（短锁，不跨 await）
-    let mut specs: Vec<ProxySpec> = {
-        let mut g = proc_state
+    let specs: Vec<ProxySpec> = {
+        let mut g = instance
             .proxy_specs
             .lock()
             .map_err(|_| Error::new(ErrorKind::Other, "lock proxy_specs"))?;
         std::mem::take(&mut *g)
     };
-    if specs.is_empty() {
-        eprintln!("[shim] no proxy specs");
-        return Ok(());
-    }
-
-    let mut new_handles: Vec<JoinHandle<()>> = Vec::with_capacity(specs.len() + 1);
-    // 记录 (id, stats) 供采样线程使用；同时为每个 spec 启动监听任务
-    let mut view: Vec<(String, Arc<ProxyStats>)> = Vec::with_capacity(specs.len());
 
-    for spec in specs.drain(..) {
-        let stats = Arc::new(ProxyStats::new());
-        view.push((spec.id.clone(), stats.clone()));
-        let app2 = app.clone();
-        let id_for_log = spec.id.clone();
-        let handle = tokio::spawn(async move {
-            if let Err(e) = serve_one_proxy(app2, spec, stats).await {
-                eprintln!("[shim:{}] serve error: {e}", id_for_log);
+    // 被关闭或替换的监听，其计数随之丢弃，先记下未写入的部分
+    let mut dropped: Vec<(String, u64, u64)> = Vec::new();
+    {
+        let mut routes = instance
+            .shim_routes
+            .lock()
+            .map_err(|_| Error::new(ErrorKind::Other, "lock shim_routes"))?;
+        let wanted: HashSet<&str> = specs.iter().map(|s| s.id.as_str()).collect();
+        routes.retain(|id, r| {
+            let keep = wanted.contains(id.as_str());
+            if !keep {
+                r.task.abort(); // 监听 socket 随任务 drop 而释放端口
+                dropped.extend(unsaved(std::iter::once((id, &*r))));
             }
+            keep
         });
-        new_handles.push(handle);
-    }
-
-    // 固定 200ms 上报：仅读原子计数，无锁
-    let sampler = tokio::spawn({
-        let app = app.clone();
-        let view = Arc::new(view);
-        async move {
-            const MS: u64 = 1000;
-            let dt = MS as f64 / 1000.0;
-            let mut tick = interval(Duration::from_millis(MS));
-            tick.set_missed_tick_behavior(MissedTickBehavior::Skip);
-
-            let mut last: HashMap<&str, (u64, u64)> = HashMap::with_capacity(view.len());
-
-            loop {
-                tick.tick().await;
-
-                let mut payload = Vec::with_capacity(view.len());
-                for (id, st) in view.iter() {
-                    let up = st.up_total.load(Ordering::Relaxed);
-                    let down = st.down_total.load(Ordering::Relaxed);
-                    let (lu, ld) = last.get::<str>(id.as_str()).copied().unwrap_or((up, down));
-                    last.insert(id.as_str(), (up, down));
-
-                    payload.push(json!({
-                        "proxy": id,
-                        "up_bps":   (up - lu) as f64 / dt,
-                        "down_bps": (down - ld) as f64 / dt,
-                        "up_total": up,
-                        "down_total": down
-                    }));
-                }
 
-                let _ = app.emit("frp:traffic", json!(payload));
+        for spec in specs {
+            let ProxySpec {
+                id,
+                listener,
+                target,
+                proxy_protocol,
+                rate_limit,
+            } = spec;
+            let Some(listener) = listener else {
+                // 沿用已有监听，只更新限速
+                if let Some(r) = routes.get(&id) {
+                    r.stats.limit.set(&rate_limit);
+                }
+                continue;
+            };
+            let replaced = routes.remove(&id);
+            if let Some(old) = &replaced {
+                old.task.abort();
+                dropped.extend(unsaved(std::iter::once((&id, old))));
+            }
+            let protocol = listener.protocol();
+            let listen = listener.local_addr()?;
+            let stats = Arc::new(ProxyStats::new());
+            stats.limit.set(&rate_limit);
+            // 沿用旧监听的封禁状态，不必等下一次配额检查
+            if let Some(old) = &replaced {
+                stats
+                    .blocked
+                    .store(old.stats.blocked.load(Ordering::Relaxed), Ordering::Relaxed);
             }
+            let conns = Arc::new(ConnTable::default());
+            let app2 = app.clone();
+            let id2 = id.clone();
+            let stats2 = stats.clone();
+            let conns2 = conns.clone();
+            let task = tokio::spawn(async move {
+                let r = match listener {
+                    ShimListener::Tcp(l) => {
+                        let route = TcpRoute {
+                            target,
+                            proxy_protocol,
+                            stats: stats2,
+                            conns: conns2,
+                        };
+                        serve_one_proxy(app2, &id2, l, route).await
+                    }
+                    ShimListener::Udp(s) => serve_udp_proxy(&id2, s, target, stats2).await,
+                };
+                if let Err(e) = r {
+                    eprintln!("[shim:{}] serve error: {e}", id2);
+                }
+            });
+            routes.insert(
+                id,
+                ShimRoute {
+                    protocol,
+                    proxy_protocol,
+                    listen,
+                    target,
+                    stats,
+                    conns,
+                    task,
+                },
+            );
         }
-    });
+    }
 
-    new_handles.push(sampler);
+    persist_traffic(&app, &SharedDb::default(), instance_id, dropped).await;
 
-    {
-        let mut g = proc_state
-            .shim_tasks
-            .lock()
-            .map_err(|_| Error::new(ErrorKind::Other, "lock shim_tasks"))?;
-        *g = new_handles;
+    let mut sampler = instance
+        .shim_sampler
+        .lock()
+        .map_err(|_| Error::new(ErrorKind::Other, "lock shim_sampler"))?;
+    if sampler.as_ref().is_none_or(|h| h.is_finished()) {
+        *sampler = Some(spawn_sampler(
+            app,
+            instance_id.to_string(),
+            instance.shim_routes.clone(),
+        ));
     }
 
     Ok(())
 }
 
+/// 关闭全部监听与采样任务，并等待它们结束；尚未写入的流量在此补写
+pub async fn stop_shim<H: Host>(app: &H, instance_id: &str, instance: &FrpcInstance) {
+    let mut handles: Vec<JoinHandle<()>> = Vec::new();
+    let mut pending = Vec::new();
+    if let Ok(mut routes) = instance.shim_routes.lock() {
+        let drained: Vec<(String, ShimRoute)> = routes.drain().collect();
+        pending = unsaved(drained.iter().map(|(id, r)| (id, r)));
+        handles.extend(drained.into_iter().map(|(_, r)| r.task));
+    }
+    if let Ok(mut g) = instance.shim_sampler.lock() {
+        handles.extend(g.take());
+    }
+    for h in handles {
+        h.abort();
+        let _ = h.await;
+    }
+    // 任务结束后计数不再变化，取最后一次
+    persist_traffic(app, &SharedDb::default(), instance_id, pending).await;
+}
+
+// 固定周期上报：每次只短暂持锁读取原子计数
+fn spawn_sampler<H: Host>(
+    app: H,
+    instance_id: String,
+    routes: Arc<Mutex<HashMap<String, ShimRoute>>>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        const MS: u64 = 1000;
+        let dt = MS as f64 / 1000.0;
+        let mut tick = interval(Duration::from_millis(MS));
+        tick.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+        let mut last: HashMap<String, (u64, u64)> = HashMap::new();
+        let mut had_conns = false;
+        let mut ticks: u32 = 0;
+        let mut quota = QuotaWatch::default();
+        let db = SharedDb::default();
+
+        loop {
+            tick.tick().await;
+
+            // 第一次采样就写入，之后每隔几次；配额每次都查，未落盘的部分按内存计数补上
+            ticks = ticks.wrapping_add(1);
+            if ticks % PERSIST_EVERY == 1 {
+                let deltas = match routes.lock() {
+                    Ok(g) => unsaved(g.iter()),
+                    Err(_) => break,
+                };
+                persist_traffic(&app, &db, &instance_id, deltas).await;
+            }
+            check_quota(&app, &db, &instance_id, &routes, &mut quota).await;
+
+            let (totals, conns): (Vec<(String, u64, u64)>, Vec<ConnInfo>) = match routes.lock() {
+                Ok(g) => (
+                    g.iter()
+                        .map(|(id, r)| {
+                            (
+                                id.clone(),
+                                r.stats.up_total.load(Ordering::Relaxed),
+                                r.stats.down_total.load(Ordering::Relaxed),
+                            )
+                        })
+                        .collect(),
+                    g.iter().flat_map(|(id, r)| r.conns.snapshot(id)).collect(),
+                ),
+                Err(_) => break,
+            };
+            // 连接表有内容时每秒刷新，清空后再发一次空表
+            if had_conns || !conns.is_empty() {
+                had_conns = !conns.is_empty();
+                app.emit(
+                    EVT_CONNECTIONS,
+                    ConnectionsPayload {
+                        instance: instance_id.clone(),
+                        connections: conns,
+                    },
+                );
+            }
+            last.retain(|id, _| totals.iter().any(|(x, _, _)| x == id));
+            if totals.is_empty() {
+                continue;
+            }
+
+            let mut payload = Vec::with_capacity(totals.len());
+            for (id, up, down) in totals {
+                let (lu, ld) = last.get(&id).copied().unwrap_or((up, down));
+                last.insert(id.clone(), (up, down));
+
+                // 监听被替换后计数从 0 重新开始
+                payload.push(json!({
+                    "instance": instance_id,
+                    "proxy": id,
+                    "up_bps":   up.saturating_sub(lu) as f64 / dt,
+                    "down_bps": down.saturating_sub(ld) as f64 / dt,
+                    "up_total": up,
+                    "down_total": down
+                }));
+            }
+
+            app.emit("frp:traffic", json!(payload));
+        }
+    })
+}
+
 /// ===================== 稳定转发：copy_bidirectional =====================
 
 #[inline]
@@ -190,47 +802,548 @@ fn set_socket_opts(s: &TcpStream) {
     let _ = s.set_nodelay(true);
 }
 
-pub async fn serve_one_proxy(
-    _app: AppHandle,
-    spec: ProxySpec,
-    stats: Arc<ProxyStats>,
+pub struct TcpRoute {
+    pub target: SocketAddr,
+    pub proxy_protocol: bool,
+    pub stats: Arc<ProxyStats>,
+    pub conns: Arc<ConnTable>,
+}
+
+pub async fn serve_one_proxy<H: Host>(
+    _app: H,
+    id: &str,
+    listener: TcpListener,
+    route: TcpRoute,
 ) -> Result<()> {
-    let ProxySpec {
-        id,
-        listener,
-        target,
-    } = spec;
     eprintln!("[shim:{id}] listen {}", listener.local_addr()?);
     loop {
         let (cli, peer) = listener.accept().await?;
-        let up = stats.up_total.clone();
-        let down = stats.down_total.clone();
-        let id__ = id.clone();
-        tokio::spawn(async move {
-            match handle_conn(cli, target, up, down).await {
-                Ok(()) => { /* 正常结束 */ }
-                Err(e) => eprintln!("[shim:{id__}] conn {peer} error: {e}"),
-            }
-        });
+        if route.stats.blocked.load(Ordering::Relaxed) {
+            drop(cli);
+            continue;
+        }
+        let target = route.target;
+        let proxy_protocol = route.proxy_protocol;
+        let stats = route.stats.clone();
+        let table = route.conns.clone();
+        let id__ = id.to_string();
+        route
+            .conns
+            .insert(peer, target, |conn_id, conn_up, conn_down| {
+                let guard = ConnGuard { table, id: conn_id };
+                let counters = Counters {
+                    up: [stats.up_total.clone(), conn_up],
+                    down: [stats.down_total.clone(), conn_down],
+                    up_bucket: stats.limit.up.clone(),
+                    down_bucket: stats.limit.down.clone(),
+                };
+                tokio::spawn(async move {
+                    match handle_conn(cli, target, proxy_protocol, counters, &guard).await {
+                        Ok(()) => { /* 正常结束 */ }
+                        Err(e) => eprintln!("[shim:{id__}] conn {peer} error: {e}"),
+                    }
+                })
+                .abort_handle()
+            });
+    }
+}
+
+// 连接级与代理级计数同时累加，限速按代理共用
+struct Counters {
+    up: [Arc<AtomicU64>; 2],
+    down: [Arc<AtomicU64>; 2],
+    up_bucket: Arc<TokenBucket>,
+    down_bucket: Arc<TokenBucket>,
+}
+
+/// 读取并剥掉 PROXY protocol v2 头，返回其中的来源地址（LOCAL 命令等无地址时为 None）。
+/// 开启后 frpc 必定先发头，开头不是 v2 签名或超时都按错误断开
+async fn read_proxy_header<R: AsyncRead + Unpin>(cli: &mut R) -> Result<Option<SocketAddr>> {
+    let read = async {
+        let mut header = vec![0u8; 16];
+        cli.read_exact(&mut header).await?;
+        if header[..12] != PROXY_V2_SIG[..] || header[12] >> 4 != 2 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "missing proxy protocol header",
+            ));
+        }
+        let len = u16::from_be_bytes([header[14], header[15]]) as usize;
+        header.resize(16 + len, 0);
+        cli.read_exact(&mut header[16..]).await?;
+        Ok(parse_proxy_v2(&header))
+    };
+    timeout(PROXY_HEADER_WAIT, read)
+        .await
+        .map_err(|_| Error::new(ErrorKind::TimedOut, "proxy protocol header"))?
+}
+
+// 只解析 PROXY 命令下的 TCP over IPv4 / IPv6，LOCAL 命令、其它地址族或不完整的头返回 None
+fn parse_proxy_v2(h: &[u8]) -> Option<SocketAddr> {
+    if h.len() < 16 || h[..12] != PROXY_V2_SIG[..] || h[12] != 0x21 {
+        return None;
+    }
+    let len = u16::from_be_bytes([h[14], h[15]]) as usize;
+    let addr = h.get(16..16 + len)?;
+    let port = |at: usize| u16::from_be_bytes([addr[at], addr[at + 1]]);
+    match h[13] {
+        0x11 if addr.len() >= 12 => {
+            let ip: [u8; 4] = addr[0..4].try_into().ok()?;
+            Some(SocketAddr::from((ip, port(8))))
+        }
+        0x21 if addr.len() >= 36 => {
+            let ip: [u8; 16] = addr[0..16].try_into().ok()?;
+            Some(SocketAddr::from((ip, port(32))))
+        }
+        _ => None,
     }
 }
 
 async fn handle_conn(
-    cli: TcpStream,
+    mut cli: TcpStream,
     target: SocketAddr,
-    up_counter: Arc<AtomicU64>,
-    down_counter: Arc<AtomicU64>,
+    proxy_protocol: bool,
+    counters: Counters,
+    guard: &ConnGuard,
 ) -> Result<()> {
-    let cli = cli;
+    if proxy_protocol {
+        if let Some(src) = read_proxy_header(&mut cli).await? {
+            guard.table.update(guard.id, |c| c.peer = src);
+        }
+    }
     let svr = TcpStream::connect(target).await?;
 
     set_socket_opts(&cli);
     set_socket_opts(&svr);
 
     // 读侧包一层实现计数（client->server 计入 up，server->client 计入 down）
-    let mut cli_r = CountRead::new(cli, up_counter);
-    let mut svr_r = CountRead::new(svr, down_counter);
+    let mut cli_r = CountRead::new(cli, counters.up, counters.up_bucket);
+    let mut svr_r = CountRead::new(svr, counters.down, counters.down_bucket);
 
     let _ = io::copy_bidirectional(&mut cli_r, &mut svr_r).await?;
     Ok(())
 }
+
+/// ===================== UDP 转发：按对端地址维护会话 =====================
+
+// 每个对端（frpc 为每个远端用户单独开一个本地 UDP 连接）对应一个连到目标的 socket
+struct UdpSession {
+    upstream: Arc<UdpSocket>,
+    last_seen: Arc<Mutex<Instant>>,
+    task: JoinHandle<()>,
+}
+
+// 会话被移除或整个转发任务被 abort 时，回程任务随之结束
+impl Drop for UdpSession {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn touch(last_seen: &Mutex<Instant>) {
+    if let Ok(mut g) = last_seen.lock() {
+        *g = Instant::now();
+    }
+}
+
+fn idle_for(last_seen: &Mutex<Instant>) -> Duration {
+    last_seen.lock().map(|g| g.elapsed()).unwrap_or_default()
+}
+
+async fn open_udp_session(
+    id: &str,
+    listen: Arc<UdpSocket>,
+    peer: SocketAddr,
+    target: SocketAddr,
+    stats: Arc<ProxyStats>,
+    idle: Duration,
+) -> Result<UdpSession> {
+    let bind: SocketAddr = if target.is_ipv4() {
+        "0.0.0.0:0".parse().unwrap()
+    } else {
+        "[::]:0".parse().unwrap()
+    };
+    let upstream = Arc::new(UdpSocket::bind(bind).await?);
+    upstream.connect(target).await?;
+    let last_seen = Arc::new(Mutex::new(Instant::now()));
+
+    let up2 = upstream.clone();
+    let seen2 = last_seen.clone();
+    let id2 = id.to_string();
+    // 回程：目标 -> frpc，计入 down
+    let task = tokio::spawn(async move {
+        let mut buf = vec![0u8; UDP_BUF_SIZE];
+        loop {
+            match timeout(idle, up2.recv(&mut buf)).await {
+                Ok(Ok(n)) => {
+                    touch(&seen2);
+                    if !stats.limit.down.try_take(n) {
+                        continue;
+                    }
+                    stats.down_total.fetch_add(n as u64, Ordering::Relaxed);
+                    if let Err(e) = listen.send_to(&buf[..n], peer).await {
+                        eprintln!("[shim:{id2}] udp reply to {peer} error: {e}");
+                    }
+                }
+                // 目标端口不可达等错误只影响当前包
+                Ok(Err(e)) => eprintln!("[shim:{id2}] udp recv from {target} error: {e}"),
+                Err(_) if idle_for(&seen2) >= idle => break,
+                Err(_) => {}
+            }
+        }
+    });
+    Ok(UdpSession {
+        upstream,
+        last_seen,
+        task,
+    })
+}
+
+pub async fn serve_udp_proxy(
+    id: &str,
+    socket: UdpSocket,
+    target: SocketAddr,
+    stats: Arc<ProxyStats>,
+) -> Result<()> {
+    eprintln!("[shim:{id}] listen udp {}", socket.local_addr()?);
+    relay_udp(id, socket, target, stats, UDP_IDLE_TIMEOUT).await
+}
+
+// 每个来源地址一个会话，idle 内两个方向都没有数据即回收
+async fn relay_udp(
+    id: &str,
+    socket: UdpSocket,
+    target: SocketAddr,
+    stats: Arc<ProxyStats>,
+    idle: Duration,
+) -> Result<()> {
+    let socket = Arc::new(socket);
+    let mut sessions: HashMap<SocketAddr, UdpSession> = HashMap::new();
+    let mut buf = vec![0u8; UDP_BUF_SIZE];
+    let mut sweep = interval(idle / 2);
+    sweep.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+    loop {
+        tokio::select! {
+            r = socket.recv_from(&mut buf) => {
+                let (n, peer) = match r {
+                    Ok(x) => x,
+                    // Windows 上对端关闭会让 recv_from 报 ConnectionReset，忽略即可
+                    Err(e) if e.kind() == ErrorKind::ConnectionReset => continue,
+                    Err(e) => return Err(e),
+                };
+                if stats.blocked.load(Ordering::Relaxed) {
+                    sessions.clear();
+                    continue;
+                }
+                // 超出限速的包直接丢弃，不计入流量
+                if !stats.limit.up.try_take(n) {
+                    continue;
+                }
+                stats.up_total.fetch_add(n as u64, Ordering::Relaxed);
+                if sessions.get(&peer).is_none_or(|s| s.task.is_finished()) {
+                    match open_udp_session(id, socket.clone(), peer, target, stats.clone(), idle).await {
+                        Ok(s) => {
+                            sessions.insert(peer, s);
+                        }
+                        Err(e) => {
+                            eprintln!("[shim:{id}] udp session {peer} error: {e}");
+                            continue;
+                        }
+                    }
+                }
+                let Some(session) = sessions.get(&peer) else {
+                    continue;
+                };
+                touch(&session.last_seen);
+                if let Err(e) = session.upstream.send(&buf[..n]).await {
+                    eprintln!("[shim:{id}] udp send to {target} error: {e}");
+                }
+            }
+            _ = sweep.tick() => {
+                sessions.retain(|_, s| !s.task.is_finished() && idle_for(&s.last_seen) < idle);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn udp() -> UdpSocket {
+        UdpSocket::bind("127.0.0.1:0").await.unwrap()
+    }
+
+    async fn recv(sock: &UdpSocket, wait: Duration) -> Option<(Vec<u8>, SocketAddr)> {
+        let mut buf = [0u8; 1500];
+        let (n, from) = timeout(wait, sock.recv_from(&mut buf)).await.ok()?.unwrap();
+        Some((buf[..n].to_vec(), from))
+    }
+
+    const WAIT: Duration = Duration::from_secs(2);
+
+    #[tokio::test]
+    async fn udp_relay_keeps_a_session_per_peer() {
+        let target = udp().await;
+        let shim = udp().await;
+        let shim_addr = shim.local_addr().unwrap();
+        let stats = Arc::new(ProxyStats::new());
+        let idle = Duration::from_millis(300);
+        let relay = tokio::spawn(relay_udp(
+            "udp",
+            shim,
+            target.local_addr().unwrap(),
+            stats.clone(),
+            idle,
+        ));
+
+        // 不同来源各开一个会话，目标看到的源端口不同
+        let a = udp().await;
+        let b = udp().await;
+        a.send_to(b"ping-a", shim_addr).await.unwrap();
+        let (data, a_upstream) = recv(&target, WAIT).await.unwrap();
+        assert_eq!(data, b"ping-a");
+        b.send_to(b"ping-b", shim_addr).await.unwrap();
+        let (data, b_upstream) = recv(&target, WAIT).await.unwrap();
+        assert_eq!(data, b"ping-b");
+        assert_ne!(a_upstream, b_upstream);
+
+        // 同一来源沿用已有会话
+        a.send_to(b"again", shim_addr).await.unwrap();
+        let (_, from) = recv(&target, WAIT).await.unwrap();
+        assert_eq!(from, a_upstream);
+
+        // 回程只发给对应的来源
+        target.send_to(b"pong-b!", b_upstream).await.unwrap();
+        let (data, from) = recv(&b, WAIT).await.unwrap();
+        assert_eq!(data, b"pong-b!");
+        assert_eq!(from, shim_addr);
+        assert!(recv(&a, Duration::from_millis(100)).await.is_none());
+
+        assert_eq!(stats.up_total.load(Ordering::Relaxed), 17);
+        assert_eq!(stats.down_total.load(Ordering::Relaxed), 7);
+        assert_eq!(stats.take_unsaved(), (17, 7));
+        assert_eq!(stats.take_unsaved(), (0, 0));
+
+        // 空闲超时后会话被回收，再发包换一个新的上游端口
+        sleep(idle * 3).await;
+        a.send_to(b"late", shim_addr).await.unwrap();
+        let (data, from) = recv(&target, WAIT).await.unwrap();
+        assert_eq!(data, b"late");
+        assert_ne!(from, a_upstream);
+
+        // 超出配额时丢弃且不计数
+        stats.blocked.store(true, Ordering::Relaxed);
+        a.send_to(b"blocked", shim_addr).await.unwrap();
+        assert!(recv(&target, Duration::from_millis(100)).await.is_none());
+        assert_eq!(stats.up_total.load(Ordering::Relaxed), 21);
+
+        relay.abort();
+    }
+
+    fn v2_header(command: u8, family: u8, addr: &[u8]) -> Vec<u8> {
+        let mut h = PROXY_V2_SIG.to_vec();
+        h.push(command);
+        h.push(family);
+        h.extend((addr.len() as u16).to_be_bytes());
+        h.extend(addr);
+        h
+    }
+
+    fn ipv4_header() -> Vec<u8> {
+        // 源 203.0.113.7:40000，目标 10.0.0.1:6000
+        let mut addr = vec![203, 0, 113, 7, 10, 0, 0, 1];
+        addr.extend(40000u16.to_be_bytes());
+        addr.extend(6000u16.to_be_bytes());
+        v2_header(0x21, 0x11, &addr)
+    }
+
+    #[test]
+    fn parses_ipv4_source() {
+        assert_eq!(
+            parse_proxy_v2(&ipv4_header()),
+            Some("203.0.113.7:40000".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn parses_ipv6_source() {
+        let src: std::net::Ipv6Addr = "2001:db8::7".parse().unwrap();
+        let mut addr = src.octets().to_vec();
+        addr.extend(std::net::Ipv6Addr::LOCALHOST.octets());
+        addr.extend(40000u16.to_be_bytes());
+        addr.extend(6000u16.to_be_bytes());
+        assert_eq!(
+            parse_proxy_v2(&v2_header(0x21, 0x21, &addr)),
+            Some("[2001:db8::7]:40000".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn truncated_header_has_no_source() {
+        let h = ipv4_header();
+        for n in [0, 12, 15, 16, h.len() - 1] {
+            assert_eq!(parse_proxy_v2(&h[..n]), None, "len {n}");
+        }
+        // 长度字段声称的地址比实际短
+        let mut short = v2_header(0x21, 0x11, &[203, 0, 113, 7]);
+        short.extend([0; 8]);
+        assert_eq!(parse_proxy_v2(&short), None);
+    }
+
+    #[test]
+    fn local_command_has_no_source() {
+        assert_eq!(parse_proxy_v2(&v2_header(0x20, 0x00, &[])), None);
+    }
+
+    #[tokio::test]
+    async fn reads_header_and_leaves_payload() {
+        let (mut frpc, mut shim) = io::duplex(256);
+        let mut sent = ipv4_header();
+        sent.extend(b"GET /");
+        tokio::io::AsyncWriteExt::write_all(&mut frpc, &sent)
+            .await
+            .unwrap();
+        let src = read_proxy_header(&mut shim).await.unwrap();
+        assert_eq!(src, Some("203.0.113.7:40000".parse().unwrap()));
+        let mut rest = [0u8; 5];
+        shim.read_exact(&mut rest).await.unwrap();
+        assert_eq!(&rest, b"GET /");
+    }
+
+    #[tokio::test]
+    async fn missing_header_is_an_error() {
+        let (mut frpc, mut shim) = io::duplex(256);
+        tokio::io::AsyncWriteExt::write_all(&mut frpc, b"GET / HTTP/1.1\r\n\r\n")
+            .await
+            .unwrap();
+        let err = read_proxy_header(&mut shim).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        // 头没发完就停住，超时断开
+        let (mut frpc, mut shim) = io::duplex(256);
+        tokio::io::AsyncWriteExt::write_all(&mut frpc, &ipv4_header()[..10])
+            .await
+            .unwrap();
+        let err = read_proxy_header(&mut shim).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::TimedOut);
+    }
+
+    // 把上次补充的时间往前拨，模拟经过了 d
+    fn elapse(b: &TokenBucket, d: Duration) {
+        let mut st = b.state.lock().unwrap();
+        st.last = st.last.checked_sub(d).unwrap();
+    }
+
+    fn tokens(b: &TokenBucket) -> f64 {
+        b.state.lock().unwrap().tokens
+    }
+
+    #[test]
+    fn zero_rate_is_unlimited() {
+        let b = TokenBucket::default();
+        assert_eq!(b.consume(1 << 30), None);
+        assert!(b.try_take(1 << 30));
+
+        // 限速后再改回 0
+        b.set(100, 100);
+        b.set(0, 0);
+        assert_eq!(b.consume(1 << 30), None);
+        assert!(b.try_take(1 << 30));
+    }
+
+    #[test]
+    fn set_starts_full_and_clamps_to_burst() {
+        let b = TokenBucket::default();
+        b.set(1000, 4000);
+        assert_eq!(tokens(&b), 4000.0);
+        // 调小容量时余额跟着收紧，调大时不凭空补满
+        b.set(1000, 500);
+        assert_eq!(tokens(&b), 500.0);
+        b.set(1000, 8000);
+        assert_eq!(tokens(&b), 500.0);
+
+        // 未设置 burst 时取一秒的量
+        let limit = RateLimit {
+            up_bytes_per_sec: 2000,
+            down_bytes_per_sec: 0,
+            burst_bytes: 0,
+        };
+        let l = ProxyLimiter::default();
+        l.set(&limit);
+        assert_eq!(tokens(&l.up), 2000.0);
+        assert!(l.down.try_take(1 << 30));
+    }
+
+    #[test]
+    fn consume_overdraws_and_reports_wait() {
+        let b = TokenBucket::default();
+        b.set(1000, 1000);
+        assert_eq!(b.consume(600), None);
+        // 欠 100 字节，按 1000 B/s 需要 100ms
+        let wait = b.consume(500).unwrap();
+        assert!((wait.as_secs_f64() - 0.1).abs() < 0.01, "{wait:?}");
+
+        // 补充不超过容量
+        elapse(&b, Duration::from_secs(10));
+        assert_eq!(b.consume(0), None);
+        assert_eq!(tokens(&b), 1000.0);
+    }
+
+    #[test]
+    fn try_take_allows_datagrams_larger_than_burst() {
+        let b = TokenBucket::default();
+        b.set(100, 100);
+        // 有余额时放行并透支，之后丢包直到还清
+        assert!(b.try_take(1500));
+        assert!(!b.try_take(1));
+        elapse(&b, Duration::from_secs(10));
+        assert!(!b.try_take(1));
+        elapse(&b, Duration::from_secs(5));
+        assert!(b.try_take(1500));
+
+        // 平均速率不超过限制：60 秒内放行的数据约为 rate * 60 + 一个数据报
+        let b = TokenBucket::default();
+        b.set(1000, 1000);
+        let mut passed = 0;
+        for _ in 0..600 {
+            if b.try_take(1200) {
+                passed += 1200;
+            }
+            elapse(&b, Duration::from_millis(100));
+        }
+        assert!((60_000..=62_400).contains(&passed), "{passed}");
+    }
+
+    #[tokio::test]
+    async fn kill_aborts_registered_connections() {
+        let table = Arc::new(ConnTable::default());
+        let peer: SocketAddr = "203.0.113.7:40000".parse().unwrap();
+        let target: SocketAddr = "127.0.0.1:22".parse().unwrap();
+        let mut tasks = Vec::new();
+        for _ in 0..2 {
+            let t = table.clone();
+            table.insert(peer, target, |id, _, _| {
+                let guard = ConnGuard { table: t, id };
+                let task = tokio::spawn(async move {
+                    let _guard = guard;
+                    std::future::pending::<()>().await
+                });
+                let abort = task.abort_handle();
+                tasks.push(task);
+                abort
+            });
+        }
+        assert_eq!(table.snapshot("ssh").len(), 2);
+
+        let first = table.snapshot("ssh")[0].id;
+        assert_eq!(table.kill(Some(first)), 1);
+        assert!(tasks.remove(0).await.unwrap_err().is_cancelled());
+        assert_eq!(table.kill(Some(first)), 0);
+
+        assert_eq!(table.kill(None), 1);
+        assert!(tasks.remove(0).await.unwrap_err().is_cancelled());
+        assert!(table.snapshot("ssh").is_empty());
+    }
+}
@@ -0,0 +1,439 @@
+use crate::domain::frpc_log::{parse_line, LogLevel, LogLine};
+use crate::errors::{AppError, Result};
+use crate::host::Host;
+use crate::infra::paths::log_dir;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+// 单个文件超过大小或时长就切到下一个分片
+const MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+const MAX_FILE_AGE: Duration = Duration::from_secs(24 * 3600);
+// 每个 profile 最多保留的文件数与天数
+const MAX_FILES: usize = 50;
+const RETENTION: Duration = Duration::from_secs(14 * 24 * 3600);
+
+/// 一次 frpc 运行对应一个会话：<session>.log、<session>.1.log、<session>.2.log ...
+pub struct SessionLog {
+    dir: PathBuf,
+    session: String,
+    part: u32,
+    file: File,
+    written: u64,
+    opened_at: Instant,
+}
+
+fn part_file(session: &str, part: u32) -> String {
+    if part == 0 {
+        format!("{session}.log")
+    } else {
+        format!("{session}.{part}.log")
+    }
+}
+
+// 文件名 → (会话名, 分片号)
+fn split_file_name(name: &str) -> Option<(&str, u32)> {
+    let stem = name.strip_suffix(".log")?;
+    match stem.split_once('.') {
+        Some((session, part)) => Some((session, part.parse().ok()?)),
+        None => Some((stem, 0)),
+    }
+}
+
+fn valid_session(session: &str) -> bool {
+    !session.is_empty() && session.chars().all(|c| c.is_ascii_digit() || c == '-')
+}
+
+impl SessionLog {
+    pub fn open<H: Host>(app: &H, profile_id: &str) -> Result<Self> {
+        let dir = log_dir(app, profile_id);
+        fs::create_dir_all(&dir)?;
+        prune(&dir, None);
+        let session = chrono::Local::now().format("%Y%m%d-%H%M%S-%3f").to_string();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(part_file(&session, 0)))?;
+        Ok(Self {
+            dir,
+            session,
+            part: 0,
+            file,
+            written: 0,
+            opened_at: Instant::now(),
+        })
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.part += 1;
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.dir.join(part_file(&self.session, self.part)))?;
+        self.written = 0;
+        self.opened_at = Instant::now();
+        // 长时间运行的实例也要受保留策略约束
+        prune(&self.dir, Some((&self.session, self.part)));
+        Ok(())
+    }
+
+    /// 逐行直接写盘，进程崩溃时不丢已输出的日志
+    pub fn write_line(&mut self, line: &str) {
+        if self.written >= MAX_FILE_BYTES || self.opened_at.elapsed() >= MAX_FILE_AGE {
+            if let Err(e) = self.rotate() {
+                eprintln!("[log] rotate failed: {e}");
+                return;
+            }
+        }
+        let mut buf = Vec::with_capacity(line.len() + 1);
+        buf.extend_from_slice(line.as_bytes());
+        buf.push(b'\n');
+        if self.file.write_all(&buf).is_ok() {
+            self.written += buf.len() as u64;
+        }
+    }
+}
+
+// 一个会话的分片：(分片号, 修改时间, 路径)
+type Parts = Vec<(u32, SystemTime, PathBuf)>;
+
+// 按会话整体清理：会话最后写入超过保留期即删除，超出 MAX_FILES 时从最旧的会话删起。
+// active 为正在写的会话与分片，它只从最早的分片删起，当前分片始终保留
+fn prune(dir: &Path, active: Option<(&str, u32)>) {
+    let Ok(rd) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut sessions: BTreeMap<String, Parts> = BTreeMap::new();
+    for entry in rd.flatten() {
+        let name = entry.file_name();
+        let Some((session, part)) = name.to_str().and_then(split_file_name) else {
+            continue;
+        };
+        let Some(mtime) = entry.metadata().ok().and_then(|m| m.modified().ok()) else {
+            continue;
+        };
+        sessions
+            .entry(session.to_string())
+            .or_default()
+            .push((part, mtime, entry.path()));
+    }
+    let now = SystemTime::now();
+    let expired = |t: SystemTime| now.duration_since(t).unwrap_or_default() > RETENTION;
+    let mut kept = 0;
+
+    if let Some((session, current)) = active {
+        let mut parts = sessions.remove(session).unwrap_or_default();
+        parts.sort_by_key(|(part, _, _)| std::cmp::Reverse(*part));
+        // 删掉一片后更早的也一并删，留下的分片保持连续
+        let mut dropping = false;
+        for (part, mtime, path) in parts {
+            if part != current && (dropping || kept >= MAX_FILES || expired(mtime)) {
+                dropping = true;
+                let _ = fs::remove_file(path);
+            } else {
+                kept += 1;
+            }
+        }
+    }
+
+    let mut rest: Vec<(SystemTime, Parts)> = sessions
+        .into_values()
+        .map(|parts| {
+            let newest = parts.iter().map(|(_, t, _)| *t).max().unwrap_or(now);
+            (newest, parts)
+        })
+        .collect();
+    rest.sort_by_key(|(newest, _)| std::cmp::Reverse(*newest));
+    for (newest, parts) in rest {
+        if expired(newest) || kept + parts.len() > MAX_FILES {
+            kept = MAX_FILES;
+            for (_, _, path) in parts {
+                let _ = fs::remove_file(path);
+            }
+        } else {
+            kept += parts.len();
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogSession {
+    pub id: String,
+    pub parts: u32,
+    pub size: u64,
+    // 最后一次写入时间（unix 毫秒）
+    pub updated_at: i64,
+}
+
+/// 按时间倒序列出某个 profile 的历史会话
+pub fn list_sessions<H: Host>(app: &H, profile_id: &str) -> Result<Vec<LogSession>> {
+    let dir = log_dir(app, profile_id);
+    let Ok(rd) = fs::read_dir(&dir) else {
+        return Ok(Vec::new());
+    };
+    let mut map: BTreeMap<String, LogSession> = BTreeMap::new();
+    for entry in rd.flatten() {
+        let name = entry.file_name();
+        let Some((session, _)) = name.to_str().and_then(split_file_name) else {
+            continue;
+        };
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        let mtime = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or(0);
+        let item = map
+            .entry(session.to_string())
+            .or_insert_with(|| LogSession {
+                id: session.to_string(),
+                parts: 0,
+                size: 0,
+                updated_at: 0,
+            });
+        item.parts += 1;
+        item.size += meta.len();
+        item.updated_at = item.updated_at.max(mtime);
+    }
+    Ok(map.into_values().rev().collect())
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogPage {
+    // 过滤后的总行数，用于分页
+    pub total: usize,
+    pub offset: usize,
+    pub lines: Vec<LogLine>,
+}
+
+/// 读取一个会话的全部分片；min_level 为空时不过滤
+pub fn read_session<H: Host>(
+    app: &H,
+    profile_id: &str,
+    session: &str,
+    offset: usize,
+    limit: usize,
+    min_level: Option<LogLevel>,
+) -> Result<LogPage> {
+    if !valid_session(session) {
+        return Err(AppError::Other(format!("invalid log session: {session}")));
+    }
+    let dir = log_dir(app, profile_id);
+    // 长会话最早的分片可能已被清理，按现存的分片依次读
+    let mut parts: Vec<u32> = fs::read_dir(&dir)?
+        .flatten()
+        .filter_map(|e| {
+            let name = e.file_name();
+            let (s, part) = name.to_str().and_then(split_file_name)?;
+            (s == session).then_some(part)
+        })
+        .collect();
+    if parts.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("log session not found: {session}"),
+        )
+        .into());
+    }
+    parts.sort_unstable();
+    let mut total = 0;
+    let mut lines = Vec::new();
+    for part in parts {
+        let file = File::open(dir.join(part_file(session, part)))?;
+        for raw in BufReader::new(file).lines() {
+            let line = parse_line(&raw?);
+            if min_level.is_some_and(|min| line.level < min) {
+                continue;
+            }
+            if total >= offset && lines.len() < limit {
+                lines.push(line);
+            }
+            total += 1;
+        }
+    }
+    Ok(LogPage {
+        total,
+        offset,
+        lines,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::DaemonHost;
+
+    const PROFILE: &str = "p";
+    const DAY: Duration = Duration::from_secs(24 * 3600);
+
+    fn host() -> (DaemonHost, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("frpc-log-{}", uuid::Uuid::new_v4()));
+        (DaemonHost::new(dir.clone(), dir.clone()), dir)
+    }
+
+    fn line(level: &str, msg: &str) -> String {
+        format!("2024-05-01 12:00:00.000 [{level}] [client/service.go:1] {msg}")
+    }
+
+    // 直接造一个分片文件，修改时间为 age 之前
+    fn touch(dir: &Path, session: &str, part: u32, age: Duration) {
+        let path = dir.join(part_file(session, part));
+        fs::write(&path, line("I", &format!("{session} part {part}")) + "\n").unwrap();
+        let file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() - age).unwrap();
+    }
+
+    fn parts_on_disk(dir: &Path, session: &str) -> Vec<u32> {
+        let mut parts: Vec<u32> = fs::read_dir(dir)
+            .unwrap()
+            .flatten()
+            .filter_map(|e| {
+                let name = e.file_name();
+                let (s, part) = name.to_str().and_then(split_file_name)?;
+                (s == session).then_some(part)
+            })
+            .collect();
+        parts.sort_unstable();
+        parts
+    }
+
+    fn messages(page: &LogPage) -> Vec<&str> {
+        page.lines.iter().map(|l| l.message.as_str()).collect()
+    }
+
+    #[test]
+    fn rotates_by_size_and_age() {
+        let (app, dir) = host();
+        let mut log = SessionLog::open(&app, PROFILE).unwrap();
+        log.write_line(&line("I", "first"));
+        log.written = MAX_FILE_BYTES;
+        log.write_line(&line("I", "second"));
+        log.opened_at = Instant::now().checked_sub(MAX_FILE_AGE).unwrap();
+        log.write_line(&line("I", "third"));
+        log.write_line(&line("I", "fourth"));
+
+        let logs = log_dir(&app, PROFILE);
+        assert_eq!(parts_on_disk(&logs, &log.session), [0, 1, 2]);
+        let sessions = list_sessions(&app, PROFILE).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(
+            (sessions[0].id.as_str(), sessions[0].parts),
+            (log.session.as_str(), 3)
+        );
+
+        let page = read_session(&app, PROFILE, &log.session, 0, 10, None).unwrap();
+        assert_eq!(messages(&page), ["first", "second", "third", "fourth"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn pages_across_parts_and_filters_level() {
+        let (app, dir) = host();
+        let mut log = SessionLog::open(&app, PROFILE).unwrap();
+        for (i, level) in ["I", "W", "E", "I", "D", "W"].iter().enumerate() {
+            if i % 2 == 0 && i > 0 {
+                log.written = MAX_FILE_BYTES;
+            }
+            log.write_line(&line(level, &format!("m{i}")));
+        }
+        let session = log.session.clone();
+        assert_eq!(parts_on_disk(&log_dir(&app, PROFILE), &session), [0, 1, 2]);
+
+        let page = read_session(&app, PROFILE, &session, 1, 3, None).unwrap();
+        assert_eq!((page.total, page.offset), (6, 1));
+        assert_eq!(messages(&page), ["m1", "m2", "m3"]);
+
+        // total 是过滤后的行数
+        let page = read_session(&app, PROFILE, &session, 0, 10, Some(LogLevel::Warn)).unwrap();
+        assert_eq!(page.total, 3);
+        assert_eq!(messages(&page), ["m1", "m2", "m5"]);
+        let page = read_session(&app, PROFILE, &session, 2, 10, Some(LogLevel::Warn)).unwrap();
+        assert_eq!(messages(&page), ["m5"]);
+
+        let page = read_session(&app, PROFILE, &session, 10, 10, None).unwrap();
+        assert_eq!((page.total, page.lines.len()), (6, 0));
+
+        assert!(read_session(&app, PROFILE, "../x", 0, 10, None).is_err());
+        assert!(read_session(&app, PROFILE, "20000101-000000-000", 0, 10, None).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn prunes_whole_sessions() {
+        let dir = std::env::temp_dir().join(format!("frpc-log-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        // 全部过期
+        touch(&dir, "20240101-000000-000", 0, 20 * DAY);
+        touch(&dir, "20240101-000000-000", 1, 20 * DAY);
+        // 第 0 片过期，但会话最近还在写，整体保留
+        touch(&dir, "20240102-000000-000", 0, 20 * DAY);
+        touch(&dir, "20240102-000000-000", 1, DAY);
+        prune(&dir, None);
+        assert!(parts_on_disk(&dir, "20240101-000000-000").is_empty());
+        assert_eq!(parts_on_disk(&dir, "20240102-000000-000"), [0, 1]);
+
+        // 超出文件数时从最旧的会话整体删起
+        for i in 0..30u64 {
+            let session = format!("20240201-{i:06}-000");
+            touch(&dir, &session, 0, Duration::from_secs(3600 + i * 60));
+            touch(&dir, &session, 1, Duration::from_secs(3600 + i * 60));
+        }
+        prune(&dir, None);
+        for i in 0..30u64 {
+            let parts = parts_on_disk(&dir, &format!("20240201-{i:06}-000"));
+            let expected: &[u32] = if i < 25 { &[0, 1] } else { &[] };
+            assert_eq!(parts, expected, "session {i}");
+        }
+        assert!(parts_on_disk(&dir, "20240102-000000-000").is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn rotation_prunes_but_keeps_the_current_part() {
+        let (app, dir) = host();
+        let mut log = SessionLog::open(&app, PROFILE).unwrap();
+        let logs = log_dir(&app, PROFILE);
+        let session = log.session.clone();
+        // 另一个较早的会话
+        touch(&logs, "20240101-000000-000", 0, DAY);
+
+        // 当前会话已写了很多分片，最早的几片会被删掉
+        for part in 1..=MAX_FILES as u32 + 4 {
+            touch(
+                &logs,
+                &session,
+                part,
+                Duration::from_secs(600 - u64::from(part)),
+            );
+        }
+        log.part = MAX_FILES as u32 + 4;
+        log.written = MAX_FILE_BYTES;
+        log.write_line(&line("I", "latest"));
+
+        let current = MAX_FILES as u32 + 5;
+        let parts = parts_on_disk(&logs, &session);
+        assert_eq!(parts.len(), MAX_FILES);
+        assert_eq!(parts.first(), Some(&(current + 1 - MAX_FILES as u32)));
+        assert_eq!(parts.last(), Some(&current));
+        assert!(parts_on_disk(&logs, "20240101-000000-000").is_empty());
+
+        // 缺了开头的分片仍能读，只是从现存的第一片开始
+        let page = read_session(&app, PROFILE, &session, 0, 1, None).unwrap();
+        assert_eq!(page.total, MAX_FILES);
+        assert_eq!(
+            messages(&page),
+            [format!("{session} part {}", current + 1 - MAX_FILES as u32)]
+        );
+        let last = read_session(&app, PROFILE, &session, MAX_FILES - 1, 1, None).unwrap();
+        assert_eq!(messages(&last), ["latest"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,89 @@
+use crate::domain::config::FrpcConfig;
+use crate::domain::profile::{Profile, ProfileSummary};
+use crate::domain::proxy::gen_id;
+use crate::errors::{AppError, Result};
+use crate::host::Host;
+use crate::services::config_service::{save_now, save_with_reason};
+use crate::state::AppState;
+
+fn not_found(id: &str) -> AppError {
+    AppError::Other(format!("profile not found: {id}"))
+}
+
+pub fn list(state: &AppState) -> Vec<ProfileSummary> {
+    let g = state.read();
+    g.profiles
+        .iter()
+        .map(|p| ProfileSummary {
+            id: p.id.clone(),
+            name: p.name.clone(),
+            server_addr: p.config.server_addr.clone(),
+            server_port: p.config.server_port,
+            proxy_count: p.config.proxies.len(),
+            active: p.id == g.active_profile,
+        })
+        .collect()
+}
+
+pub fn create<H: Host>(app: &H, state: &AppState, name: &str) -> Result<Profile> {
+    let profile = Profile::new(name, FrpcConfig::default());
+    state.write().profiles.push(profile.clone());
+    save_with_reason(app, state, "create profile")?;
+    Ok(profile)
+}
+
+/// 复制一份 profile；代理与访问端重新分配 id，避免与源 profile 的流量统计混在一起
+pub fn clone<H: Host>(app: &H, state: &AppState, id: &str, name: &str) -> Result<Profile> {
+    let profile = {
+        let mut g = state.write();
+        let mut config = g.profile(id).ok_or_else(|| not_found(id))?.config.clone();
+        for p in config.proxies.iter_mut() {
+            p.id = gen_id();
+        }
+        for v in config.visitors.iter_mut() {
+            v.id = gen_id();
+        }
+        let profile = Profile::new(name, config);
+        g.profiles.push(profile.clone());
+        profile
+    };
+    save_with_reason(app, state, "clone profile")?;
+    Ok(profile)
+}
+
+pub fn rename<H: Host>(app: &H, state: &AppState, id: &str, name: &str) -> Result<()> {
+    {
+        let mut g = state.write();
+        let profile = g.profile_mut(id).ok_or_else(|| not_found(id))?;
+        profile.name = name.to_string();
+    }
+    save_now(app, state)
+}
+
+pub fn delete<H: Host>(app: &H, state: &AppState, id: &str) -> Result<()> {
+    {
+        let mut g = state.write();
+        if g.profile(id).is_none() {
+            return Err(not_found(id));
+        }
+        if g.profiles.len() == 1 {
+            return Err(AppError::Other("cannot delete the last profile".into()));
+        }
+        g.profiles.retain(|p| p.id != id);
+        if g.active_profile == id {
+            g.active_profile = g.profiles[0].id.clone();
+        }
+    }
+    save_now(app, state)
+}
+
+pub fn activate<H: Host>(app: &H, state: &AppState, id: &str) -> Result<()> {
+    {
+        let mut g = state.write();
+        if g.profile(id).is_none() {
+            return Err(not_found(id));
+        }
+        g.active_profile = id.to_string();
+    }
+    save_now(app, state)
+}
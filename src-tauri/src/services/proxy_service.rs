@@ -0,0 +1,84 @@
+use crate::domain::proxy::Proxy;
+use crate::domain::traffic::RateLimit;
+use crate::domain::validation::validate_config;
+use crate::errors::{AppError, Result};
+use crate::host::Host;
+use crate::services::config_service::save_with_reason;
+use crate::state::AppState;
+
+fn not_found(profile_id: &str) -> AppError {
+    AppError::Other(format!("profile not found: {profile_id}"))
+}
+
+/// 按 id 或名称查找代理
+pub fn find(state: &AppState, profile_id: &str, key: &str) -> Option<Proxy> {
+    state
+        .read()
+        .profile(profile_id)?
+        .config
+        .proxies
+        .iter()
+        .find(|p| p.id == key || p.name == key)
+        .cloned()
+}
+
+/// 新增或按 id 覆盖代理；只拦与本代理相关的错误（含与其它代理的冲突）
+pub fn save<H: Host>(app: &H, state: &AppState, profile_id: &str, proxy: Proxy) -> Result<()> {
+    let name = {
+        let mut g = state.write();
+        let profile = g
+            .profile_mut(profile_id)
+            .ok_or_else(|| not_found(profile_id))?;
+        let mut candidate = profile.config.clone();
+        let id = proxy.id.clone();
+        let name = proxy.name.clone();
+        let list = &mut candidate.proxies;
+        if let Some(idx) = list.iter().position(|p| p.id == proxy.id) {
+            list[idx] = proxy;
+        } else {
+            list.push(proxy);
+        }
+        let report = validate_config(&candidate).only(Some(&id));
+        if let Some(msg) = report.error_summary() {
+            return Err(AppError::Validation(msg));
+        }
+        profile.config = candidate;
+        name
+    };
+    save_with_reason(app, state, &format!("save proxy {name}"))
+}
+
+/// 只改限速并保存，返回更新后的代理；限速不写入 frpc 配置，无需重载 frpc
+pub fn set_rate_limit<H: Host>(
+    app: &H,
+    state: &AppState,
+    profile_id: &str,
+    key: &str,
+    limit: RateLimit,
+) -> Result<Proxy> {
+    let mut proxy = find(state, profile_id, key)
+        .ok_or_else(|| AppError::Other(format!("proxy not found: {key}")))?;
+    proxy.rate_limit = limit;
+    save(app, state, profile_id, proxy.clone())?;
+    Ok(proxy)
+}
+
+/// 返回是否确实删除了代理
+pub fn remove<H: Host>(app: &H, state: &AppState, profile_id: &str, id: &str) -> Result<bool> {
+    let removed = {
+        let mut g = state.write();
+        let profile = g
+            .profile_mut(profile_id)
+            .ok_or_else(|| not_found(profile_id))?;
+        let list = &mut profile.config.proxies;
+        let idx = list.iter().position(|p| p.id == id);
+        idx.map(|i| list.remove(i).name.clone())
+    };
+
+    // 没有删掉任何东西时不留快照
+    let Some(name) = removed else {
+        return Ok(false);
+    };
+    save_with_reason(app, state, &format!("remove proxy {name}"))?;
+    Ok(true)
+}
@@ -0,0 +1,223 @@
+use crate::domain::traffic::{QuotaLevel, QuotaPeriod, QuotaStatus, TrafficQuota};
+use crate::errors::Result;
+use crate::host::Host;
+use crate::infra::traffic_db::TrafficDb;
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaEvent {
+    pub instance: String,
+    #[serde(flatten)]
+    pub status: QuotaStatus,
+}
+
+/// 该 profile 中设置了配额的代理在当前周期内的用量；
+/// pending 为各代理尚未写入流量历史的字节数，运行中的 shim 每隔几秒才落盘一次
+pub fn statuses<H: Host>(
+    app: &H,
+    db: &TrafficDb,
+    profile_id: &str,
+    pending: &HashMap<String, u64>,
+) -> Result<Vec<QuotaStatus>> {
+    let proxies: Vec<_> = {
+        let state = app.app_state();
+        let g = state.read();
+        g.profile(profile_id)
+            .map(|p| {
+                p.config
+                    .proxies
+                    .iter()
+                    .filter(|x| x.quota.is_set())
+                    .map(|x| (x.id.clone(), x.name.clone(), x.quota.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    if proxies.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let now = Local::now();
+    collect(proxies, now, pending, |id, since| {
+        db.proxy_total(profile_id, id, since, now.timestamp() + 1)
+    })
+}
+
+// stored 返回某个代理自 since 起已写入的用量
+fn collect(
+    proxies: Vec<(String, String, TrafficQuota)>,
+    now: DateTime<Local>,
+    pending: &HashMap<String, u64>,
+    mut stored: impl FnMut(&str, i64) -> Result<u64>,
+) -> Result<Vec<QuotaStatus>> {
+    let mut out = Vec::new();
+    for (id, name, quota) in proxies {
+        let unsaved = pending.get(&id).copied().unwrap_or(0);
+        for (period, limit, since, resets_at) in quota.windows(now) {
+            let used = stored(&id, since.timestamp())?.saturating_add(unsaved);
+            out.push(QuotaStatus {
+                proxy_id: id.clone(),
+                proxy_name: name.clone(),
+                period,
+                used,
+                limit,
+                level: quota.level(used, limit),
+                since: since.to_rfc3339(),
+                resets_at: resets_at.to_rfc3339(),
+            });
+        }
+    }
+    Ok(out)
+}
+
+/// 任一周期超出限额的代理
+pub fn exceeded(statuses: &[QuotaStatus]) -> HashSet<String> {
+    statuses
+        .iter()
+        .filter(|s| s.level == QuotaLevel::Exceeded)
+        .map(|s| s.proxy_id.clone())
+        .collect()
+}
+
+/// 记住每个代理每个周期上次的级别，只在级别变化或周期重置时上报
+#[derive(Default)]
+pub struct QuotaWatch {
+    levels: HashMap<(String, QuotaPeriod), (String, QuotaLevel)>,
+}
+
+impl QuotaWatch {
+    pub fn changes(&mut self, statuses: &[QuotaStatus]) -> Vec<QuotaStatus> {
+        let mut out = Vec::new();
+        let mut seen = HashSet::new();
+        for s in statuses {
+            let key = (s.proxy_id.clone(), s.period);
+            let prev = self.levels.insert(key.clone(), (s.since.clone(), s.level));
+            let changed = match prev {
+                Some((since, level)) => since != s.since || level != s.level,
+                // 第一次看到时只有已接近或超出才上报
+                None => s.level != QuotaLevel::Ok,
+            };
+            if changed {
+                out.push(s.clone());
+            }
+            seen.insert(key);
+        }
+        // 配额被取消或代理被删除
+        self.levels.retain(|k, _| seen.contains(k));
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn status(proxy_id: &str, period: QuotaPeriod, level: QuotaLevel, since: &str) -> QuotaStatus {
+        QuotaStatus {
+            proxy_id: proxy_id.into(),
+            proxy_name: proxy_id.into(),
+            period,
+            used: 0,
+            limit: 100,
+            level,
+            since: since.into(),
+            resets_at: String::new(),
+        }
+    }
+
+    fn levels(changes: &[QuotaStatus]) -> Vec<(&str, QuotaLevel)> {
+        changes
+            .iter()
+            .map(|s| (s.proxy_id.as_str(), s.level))
+            .collect()
+    }
+
+    #[test]
+    fn watch_reports_level_changes() {
+        use QuotaLevel::*;
+        use QuotaPeriod::*;
+        let mut watch = QuotaWatch::default();
+
+        // 第一次只报已接近或超出的
+        let first = [
+            status("a", Daily, Ok, "d1"),
+            status("b", Daily, Warning, "d1"),
+        ];
+        assert_eq!(levels(&watch.changes(&first)), [("b", Warning)]);
+        assert!(watch.changes(&first).is_empty());
+
+        let second = [
+            status("a", Daily, Warning, "d1"),
+            status("b", Daily, Exceeded, "d1"),
+        ];
+        assert_eq!(
+            levels(&watch.changes(&second)),
+            [("a", Warning), ("b", Exceeded)]
+        );
+
+        // 周期重置后即使级别回到 Ok 也上报一次
+        let reset = [
+            status("a", Daily, Warning, "d1"),
+            status("b", Daily, Ok, "d2"),
+        ];
+        assert_eq!(levels(&watch.changes(&reset)), [("b", Ok)]);
+
+        // 同一代理的日、月配额分开记
+        let monthly = [
+            status("a", Daily, Warning, "d1"),
+            status("a", Monthly, Warning, "m1"),
+        ];
+        let changes = watch.changes(&monthly);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].period, Monthly);
+
+        // 配额取消后忘掉旧级别，再次出现按第一次处理
+        assert!(watch.changes(&[]).is_empty());
+        assert_eq!(
+            levels(&watch.changes(&[status("a", Daily, Warning, "d1")])),
+            [("a", Warning)]
+        );
+    }
+
+    #[test]
+    fn adds_unsaved_bytes_to_stored_usage() {
+        let now = Local.with_ymd_and_hms(2024, 6, 15, 13, 0, 0).unwrap();
+        let quota = TrafficQuota {
+            daily_bytes: 100,
+            monthly_bytes: 1000,
+            ..Default::default()
+        };
+        let proxies = vec![
+            ("a".to_string(), "ssh".to_string(), quota.clone()),
+            ("b".to_string(), "web".to_string(), quota),
+        ];
+        let pending = HashMap::from([("a".to_string(), 30)]);
+        let day = Local.with_ymd_and_hms(2024, 6, 15, 0, 0, 0).unwrap();
+        let out = collect(proxies, now, &pending, |id, since| {
+            Ok(match (id, since == day.timestamp()) {
+                ("a", true) => 60,
+                ("a", false) => 990,
+                _ => 10,
+            })
+        })
+        .unwrap();
+
+        let got: Vec<_> = out
+            .iter()
+            .map(|s| (s.proxy_id.as_str(), s.period, s.used, s.level))
+            .collect();
+        assert_eq!(
+            got,
+            [
+                ("a", QuotaPeriod::Daily, 90, QuotaLevel::Warning),
+                ("a", QuotaPeriod::Monthly, 1020, QuotaLevel::Exceeded),
+                ("b", QuotaPeriod::Daily, 10, QuotaLevel::Ok),
+                ("b", QuotaPeriod::Monthly, 10, QuotaLevel::Ok),
+            ]
+        );
+        assert_eq!(exceeded(&out), HashSet::from(["a".to_string()]));
+    }
+}
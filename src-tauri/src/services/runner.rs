@@ -1,42 +1,235 @@
+use crate::host::Host;
 use crate::{
-    events::{EVT_CLOSE, EVT_LOG_ERROR, EVT_LOG_STDERR, EVT_LOG_STDOUT},
-    state::FrpcProcState,
+    domain::frpc_log::{self, LogEventKind, LogLevel, LogLine},
+    domain::proxy_status::ProxyStatus,
+    domain::restart::{RestartMode, RestartState},
+    domain::validation::validate_config,
+    events::{
+        instance_event, EVT_CLOSE, EVT_LOG_ERROR, EVT_LOG_EVENT, EVT_LOG_LINE, EVT_LOG_STDERR,
+        EVT_LOG_STDOUT, EVT_RESTART, EVT_STARTED,
+    },
+    state::{FrpcInstance, FrpcProcState},
 };
 use serde::Serialize;
 use std::{
-    io::{BufRead, BufReader},
+    io::{BufRead, BufReader, Read},
     process::{Command, Stdio},
+    sync::{atomic::Ordering, Arc, Mutex},
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
-use tauri::{AppHandle, Emitter};
 
 #[derive(Serialize, Clone, Debug)]
 pub struct ClosePayload {
+    pub instance: String,
     pub code: Option<i32>,
 }
 
-use crate::services::config_service::export_toml_to_file;
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LogLinePayload {
+    pub instance: String,
+    pub stream: &'static str,
+    #[serde(flatten)]
+    pub line: LogLine,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEventPayload {
+    pub instance: String,
+    pub kind: LogEventKind,
+    pub proxy: Option<String>,
+    pub level: LogLevel,
+    pub ts: Option<String>,
+    pub message: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct StartedPayload {
+    pub instance: String,
+    pub pid: u32,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceStatus {
+    pub id: String,
+    pub name: String,
+    pub running: bool,
+    pub pid: Option<u32>,
+}
+
+use crate::infra::frpc_admin::AdminEndpoint;
+use crate::infra::store::STOP_GRACE_KEY;
+use crate::services::config_service::{export_toml_to_file, remove_toml_file};
+use crate::services::local_proxy::{run_shim, stop_shim};
+use crate::services::log_store::SessionLog;
+use crate::services::status_poller;
+use crate::services::vault_service;
 use crate::services::version_service::get_active;
-use crate::state::{notify_watchdog, AppState};
+use crate::state::AppState;
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
-use crate::services::local_proxy::run_tcp_shim;
 
 #[cfg(windows)]
 const CREATE_NO_WINDOW: u32 = 0x0800_0000;
 
-pub async fn start(app: &AppHandle, state: &AppState, proc_state: &FrpcProcState) -> Result<u32, String> {
+const DEFAULT_STOP_GRACE_MS: u64 = 5_000;
+
+// 同时发到全局通道与实例自己的通道
+fn emit_both<S: Serialize + Clone, H: Host>(app: &H, base: &str, id: &str, payload: S) {
+    app.emit(&instance_event(base, id), payload.clone());
+    app.emit(base, payload);
+}
+
+fn emit_parsed<H: Host>(app: &H, id: &str, stream: &'static str, raw: &str) {
+    let line = frpc_log::parse_line(raw);
+    if let Some(kind) = frpc_log::classify(&line) {
+        emit_both(
+            app,
+            EVT_LOG_EVENT,
+            id,
+            LogEventPayload {
+                instance: id.to_string(),
+                kind,
+                proxy: line.proxy.clone(),
+                level: line.level,
+                ts: line.ts.clone(),
+                message: line.message.clone(),
+            },
+        );
+    }
+    emit_both(
+        app,
+        EVT_LOG_LINE,
+        id,
+        LogLinePayload {
+            instance: id.to_string(),
+            stream,
+            line,
+        },
+    );
+}
+
+type SharedLog = Arc<Mutex<SessionLog>>;
+
+fn write_log(log: &Option<SharedLog>, line: &str) {
+    if let Some(log) = log {
+        if let Ok(mut g) = log.lock() {
+            g.write_line(line);
+        }
+    }
+}
+
+// 逐行读取管道并转成事件：原始文本保持不变，另发一份解析结果
+fn spawn_line_forwarder<R: Read + Send + 'static, H: Host>(
+    app: H,
+    id: String,
+    pipe: R,
+    log: Option<SharedLog>,
+    evt: &'static str,
+    name: &'static str,
+) {
+    thread::spawn(move || {
+        let reader = BufReader::new(pipe);
+        for line in reader.lines() {
+            match line {
+                Ok(s) => {
+                    write_log(&log, &s);
+                    emit_parsed(&app, &id, name, &s);
+                    emit_both(&app, evt, &id, s);
+                }
+                Err(e) => {
+                    emit_both(&app, EVT_LOG_ERROR, &id, format!("read {name} error: {e}"));
+                    break;
+                }
+            }
+        }
+    });
+}
+
+// 进程持续运行超过该时长视为恢复正常，重试计数清零
+const STABLE_RUN: Duration = Duration::from_secs(60);
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RestartPayload {
+    pub instance: String,
+    pub attempt: u32,
+    pub max_retries: u32,
+    pub delay_ms: u64,
+    pub code: Option<i32>,
+    pub gave_up: bool,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FrpcStatus {
+    pub running: bool,
+    pub pid: Option<u32>,
+    pub policy: RestartMode,
+    pub restart: RestartState,
+}
+
+/// 手动启动：重置重启状态，并使之前排队中的重试失效
+pub async fn start<H: Host>(
+    app: &H,
+    state: &AppState,
+    proc_state: &FrpcProcState,
+    profile_id: &str,
+) -> Result<u32, String> {
+    let instance = proc_state.instance(profile_id);
+    {
+        let g = instance.child.lock().map_err(|e| e.to_string())?;
+        if g.is_some() {
+            return Err("frpc is already running".into());
+        }
+    }
+    vault_service::ensure_unlocked(state)?;
+    {
+        let g = state.read();
+        let profile = g
+            .profile(profile_id)
+            .ok_or_else(|| format!("profile not found: {profile_id}"))?;
+        if let Some(msg) = validate_config(&profile.config).error_summary() {
+            return Err(msg);
+        }
+    }
+    let generation = instance.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    if let Ok(mut r) = instance.restart.lock() {
+        *r = RestartState::default();
+    }
+    spawn_instance(app, state, &instance, profile_id, generation).await
+}
+
+// generation 是调用方看到的代数；期间被 stop() 取消时不再启动进程
+async fn spawn_instance<H: Host>(
+    app: &H,
+    state: &AppState,
+    instance: &Arc<FrpcInstance>,
+    profile_id: &str,
+    generation: u64,
+) -> Result<u32, String> {
     // 防重复
     {
-        let g = proc_state.child.lock().map_err(|e| e.to_string())?;
+        let g = instance.child.lock().map_err(|e| e.to_string())?;
         if g.is_some() {
             return Err("frpc is already running".into());
         }
     }
 
-    let exe_path = get_active(state).unwrap().exe_path;
-    let cfg_path = export_toml_to_file(app, state, proc_state)?;
+    let exe_path = get_active(state)
+        .ok_or_else(|| "no active frpc version".to_string())?
+        .exe_path;
+    let cfg_path = export_toml_to_file(app, state, profile_id, instance)?;
+
+    // 先起本地中转，frpc 一启动就能连上；失败时不留下 frpc 进程
+    if let Err(e) = run_shim(app.clone(), profile_id, instance).await {
+        stop_shim(app, profile_id, instance).await;
+        remove_toml_file(app, profile_id);
+        return Err(format!("start shim failed: {e}"));
+    }
 
     // 构建命令
     let mut cmd = Command::new(&exe_path);
@@ -52,74 +245,94 @@ pub async fn start(app: &AppHandle, state: &AppState, proc_state: &FrpcProcState
         cmd.creation_flags(CREATE_NO_WINDOW);
     }
 
-    let mut child = cmd
-        .spawn()
-        .map_err(|e| format!("spawn frpc failed: {e} (exe: {exe_path}, cfg: {cfg_path})"))?;
-    let pid = child.id();
-
-    let _ = notify_watchdog(app, format!("SET PID {pid}").into());
-
-    run_tcp_shim(app.clone(), proc_state).await.expect("TODO: panic message");
+    // 检查代数、启动、放入实例状态都在 child 锁内完成：
+    // stop() 先改代数再取 child，要么这里看到取消，要么它看到新进程
+    let spawned = {
+        let mut g = instance.child.lock().map_err(|e| e.to_string())?;
+        if instance.generation.load(Ordering::SeqCst) != generation {
+            Err("start cancelled by stop".to_string())
+        } else {
+            match cmd.spawn() {
+                Ok(mut child) => {
+                    let pid = child.id();
+                    let pipes = (child.stdout.take(), child.stderr.take());
+                    *g = Some(child);
+                    Ok((pid, pipes))
+                }
+                Err(e) => Err(format!(
+                    "spawn frpc failed: {e} (exe: {exe_path}, cfg: {cfg_path})"
+                )),
+            }
+        }
+    };
+    let (pid, (stdout, stderr)) = match spawned {
+        Ok(v) => v,
+        Err(e) => {
+            stop_shim(app, profile_id, instance).await;
+            remove_toml_file(app, profile_id);
+            return Err(e);
+        }
+    };
 
-    // 拿到输出管道
-    let stdout = child.stdout.take();
-    let stderr = child.stderr.take();
+    app.notify_watchdog(&format!("ADD PID {pid}"));
 
-    // 放入全局状态
-    {
-        let mut g = proc_state.child.lock().map_err(|e| e.to_string())?;
-        *g = Some(child);
-    }
+    // 本次运行的日志文件；打不开时只影响落盘，不影响启动
+    let log = match SessionLog::open(app, profile_id) {
+        Ok(l) => Some(Arc::new(Mutex::new(l))),
+        Err(e) => {
+            emit_both(
+                app,
+                EVT_LOG_ERROR,
+                profile_id,
+                format!("open log file failed: {e}"),
+            );
+            None
+        }
+    };
 
-    // stdout → 事件
+    // stdout / stderr → 事件
     if let Some(out) = stdout {
-        let app2 = app.clone();
-        thread::spawn(move || {
-            let reader = BufReader::new(out);
-            for line in reader.lines() {
-                match line {
-                    Ok(s) => {
-                        let _ = app2.emit(EVT_LOG_STDOUT, s);
-                    }
-                    Err(e) => {
-                        let _ = app2.emit(EVT_LOG_ERROR, format!("read stdout error: {e}"));
-                        break;
-                    }
-                }
-            }
-        });
+        spawn_line_forwarder(
+            app.clone(),
+            profile_id.to_string(),
+            out,
+            log.clone(),
+            EVT_LOG_STDOUT,
+            "stdout",
+        );
     }
-
-    // stderr → 事件
     if let Some(err) = stderr {
-        let app2 = app.clone();
-        thread::spawn(move || {
-            let reader = BufReader::new(err);
-            for line in reader.lines() {
-                match line {
-                    Ok(s) => {
-                        let _ = app2.emit(EVT_LOG_STDERR, s);
-                    }
-                    Err(e) => {
-                        let _ = app2.emit(EVT_LOG_ERROR, format!("read stderr error: {e}"));
-                        break;
-                    }
-                }
-            }
-        });
+        spawn_line_forwarder(
+            app.clone(),
+            profile_id.to_string(),
+            err,
+            log.clone(),
+            EVT_LOG_STDERR,
+            "stderr",
+        );
     }
 
-    // 退出监控线程：子进程退出→清空句柄并发 EVT_CLOSE
+    status_poller::spawn(app, profile_id, instance);
+
+    // 退出监控线程：子进程退出→清空句柄并发 EVT_CLOSE，再按策略决定是否重启
     let app_close = app.clone();
-    let child_arc = proc_state.child.clone();
-    thread::spawn(move || loop {
+    let id = profile_id.to_string();
+    let instance_monitor = &instance.monitor;
+    let instance = instance.clone();
+    let started_at = Instant::now();
+    let monitor = thread::spawn(move || loop {
         let status_opt = {
-            let mut guard = child_arc.lock().expect("poisoned");
+            let mut guard = instance.child.lock().expect("poisoned");
             if let Some(ch) = guard.as_mut() {
                 match ch.try_wait() {
                     Ok(st) => st,
                     Err(e) => {
-                        let _ = app_close.emit(EVT_LOG_ERROR, format!("try_wait error: {e}"));
+                        emit_both(
+                            &app_close,
+                            EVT_LOG_ERROR,
+                            &id,
+                            format!("try_wait error: {e}"),
+                        );
                         None
                     }
                 }
@@ -130,29 +343,541 @@ pub async fn start(app: &AppHandle, state: &AppState, proc_state: &FrpcProcState
 
         if let Some(status) = status_opt {
             // 清空句柄、发送 close 事件
-            let mut guard = child_arc.lock().expect("poisoned");
-            *guard = None;
+            {
+                let mut guard = instance.child.lock().expect("poisoned");
+                *guard = None;
+            }
+            app_close.notify_watchdog(&format!("DEL PID {pid}"));
+            remove_toml_file(&app_close, &id);
             let code = status.code();
-            let _ = app_close.emit(EVT_CLOSE, ClosePayload { code });
+            write_log(&log, &format!("[frpc-desktop] frpc exited, code={code:?}"));
+            emit_both(
+                &app_close,
+                EVT_CLOSE,
+                &id,
+                ClosePayload {
+                    instance: id.clone(),
+                    code,
+                },
+            );
+            restart_loop(
+                &app_close,
+                &instance,
+                &id,
+                generation,
+                code,
+                started_at.elapsed(),
+            );
             break;
         }
 
         thread::sleep(Duration::from_millis(200));
     });
+    if let Ok(mut g) = instance_monitor.lock() {
+        *g = Some(monitor);
+    }
 
+    emit_both(
+        app,
+        EVT_STARTED,
+        profile_id,
+        StartedPayload {
+            instance: profile_id.to_string(),
+            pid,
+        },
+    );
     Ok(pid)
 }
 
-pub fn stop(app: &AppHandle, proc_state: &FrpcProcState) -> Result<(), String> {
-    let mut g = proc_state.child.lock().map_err(|e| e.to_string())?;
-    if let Some(ch) = g.as_mut() {
-        ch.kill().map_err(|e| format!("kill frpc failed: {e}"))?;
+// 在监控线程中执行；启动失败也计为一次失败，继续按退避重试
+fn restart_loop<H: Host>(
+    app: &H,
+    instance: &Arc<FrpcInstance>,
+    id: &str,
+    generation: u64,
+    mut code: Option<i32>,
+    mut ran_for: Duration,
+) {
+    let state = app.app_state();
+    loop {
+        let cancelled = || instance.generation.load(Ordering::SeqCst) != generation;
+        if cancelled() {
+            return;
+        }
+        let Some(policy) = state.read().profile(id).map(|p| p.config.restart.clone()) else {
+            return;
+        };
+        if !policy.should_restart(code) {
+            return;
+        }
+
+        let (attempt, gave_up) = {
+            let mut r = instance.restart.lock().expect("poisoned");
+            if ran_for >= STABLE_RUN {
+                r.attempt = 0;
+            }
+            r.last_exit_code = code;
+            r.gave_up = policy.exhausted(r.attempt);
+            if !r.gave_up {
+                r.attempt += 1;
+            }
+            (r.attempt, r.gave_up)
+        };
+        let delay = if gave_up {
+            Duration::ZERO
+        } else {
+            policy.delay_for(attempt)
+        };
+        emit_both(
+            app,
+            EVT_RESTART,
+            id,
+            RestartPayload {
+                instance: id.to_string(),
+                attempt,
+                max_retries: policy.max_retries,
+                delay_ms: delay.as_millis() as u64,
+                code,
+                gave_up,
+            },
+        );
+        if gave_up {
+            emit_both(
+                app,
+                EVT_LOG_ERROR,
+                id,
+                format!("restart limit reached ({attempt})"),
+            );
+            return;
+        }
+
+        let retry_at =
+            chrono::Utc::now() + chrono::Duration::milliseconds(delay.as_millis() as i64);
+        if let Ok(mut r) = instance.restart.lock() {
+            r.next_retry_at = Some(retry_at.timestamp_millis());
+        }
+        // 分段睡眠，便于 stop() 及时取消
+        let deadline = Instant::now() + delay;
+        while Instant::now() < deadline {
+            if cancelled() {
+                break;
+            }
+            thread::sleep(
+                Duration::from_millis(200).min(deadline.saturating_duration_since(Instant::now())),
+            );
+        }
+        if let Ok(mut r) = instance.restart.lock() {
+            r.next_retry_at = None;
+        }
+        if cancelled() {
+            return;
+        }
+
+        let started_at = Instant::now();
+        match tauri::async_runtime::block_on(spawn_instance(app, &state, instance, id, generation))
+        {
+            Ok(_) => return, // 新的监控线程接管
+            Err(_) if cancelled() => return,
+            Err(e) => {
+                emit_both(app, EVT_LOG_ERROR, id, format!("restart failed: {e}"));
+                code = None;
+                ran_for = started_at.elapsed();
+            }
+        }
+    }
+}
+
+/// 运行中且开启了 webServer 时通过 admin API 热重载，不中断已有连接；
+/// 返回 false 表示未重载（未运行或未开启 webServer），改动需重启后生效
+pub async fn reload<H: Host>(
+    app: &H,
+    state: &AppState,
+    proc_state: &FrpcProcState,
+    profile_id: &str,
+) -> Result<bool, String> {
+    if !is_running(proc_state, profile_id)? {
+        return Ok(false);
+    }
+    let Some(endpoint) = state
+        .read()
+        .profile(profile_id)
+        .and_then(|p| AdminEndpoint::from_config(&p.config))
+    else {
+        return Ok(false);
+    };
+    let instance = proc_state.instance(profile_id);
+    export_toml_to_file(app, state, profile_id, &instance)?;
+    // 先让新代理的 shim 就绪，再通知 frpc
+    run_shim(app.clone(), profile_id, &instance)
+        .await
+        .map_err(|e| format!("update shim failed: {e}"))?;
+    endpoint.reload().await?;
+    Ok(true)
+}
+
+fn stop_grace(state: &AppState) -> Duration {
+    let ms = state
+        .read()
+        .settings
+        .get(STOP_GRACE_KEY)
+        .and_then(|v| v.as_u64())
+        .unwrap_or(DEFAULT_STOP_GRACE_MS);
+    Duration::from_millis(ms)
+}
+
+#[cfg(unix)]
+fn terminate(pid: u32) {
+    unsafe {
+        let _ = libc::kill(pid as i32, libc::SIGTERM);
     }
-    let _ = notify_watchdog(app, "CLEAR".into());
+}
+
+// Windows 控制台程序收不到 SIGTERM，不等待直接强杀
+#[cfg(windows)]
+fn terminate(_pid: u32) {}
+
+/// 先 SIGTERM，让 frpc 主动从 frps 注销代理；超过宽限期仍未退出再强杀。
+/// 返回前等待退出监控线程和 shim 任务结束
+pub async fn stop<H: Host>(
+    app: &H,
+    state: &AppState,
+    proc_state: &FrpcProcState,
+    profile_id: &str,
+) -> Result<(), String> {
+    let Some(instance) = proc_state.get(profile_id) else {
+        return Ok(());
+    };
+    // 取消排队中的自动重启
+    instance.generation.fetch_add(1, Ordering::SeqCst);
+
+    let pid = {
+        let g = instance.child.lock().map_err(|e| e.to_string())?;
+        g.as_ref().map(|ch| ch.id())
+    };
+    if let Some(pid) = pid {
+        terminate(pid);
+        let grace = if cfg!(unix) {
+            stop_grace(state)
+        } else {
+            Duration::ZERO
+        };
+        let deadline = Instant::now() + grace;
+        // 监控线程发现进程退出后会清空 child
+        while Instant::now() < deadline && is_running(proc_state, profile_id)? {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        {
+            let mut g = instance.child.lock().map_err(|e| e.to_string())?;
+            if let Some(ch) = g.as_mut() {
+                ch.kill().map_err(|e| format!("kill frpc failed: {e}"))?;
+            }
+        }
+        app.notify_watchdog(&format!("DEL PID {pid}"));
+    }
+
+    let monitor = instance.monitor.lock().ok().and_then(|mut g| g.take());
+    if let Some(h) = monitor {
+        let _ = tauri::async_runtime::spawn_blocking(move || h.join()).await;
+    }
+
+    let poller = instance
+        .status_poller
+        .lock()
+        .ok()
+        .and_then(|mut g| g.take());
+    if let Some(h) = poller {
+        h.abort();
+        let _ = h.await;
+    }
+    stop_shim(app, profile_id, &instance).await;
+    remove_toml_file(app, profile_id);
     Ok(())
 }
 
-pub fn is_running(proc_state: &FrpcProcState) -> Result<bool, String> {
-    let g = proc_state.child.lock().map_err(|e| e.to_string())?;
+/// 各实例并行停止，总耗时不超过一个宽限期
+pub async fn stop_all<H: Host>(app: &H, state: &AppState, proc_state: &FrpcProcState) {
+    let ids: Vec<String> = proc_state.all().into_iter().map(|(id, _)| id).collect();
+    let tasks = ids.iter().map(|id| stop(app, state, proc_state, id));
+    for r in futures_util::future::join_all(tasks).await {
+        if let Err(e) = r {
+            eprintln!("[runner] stop failed: {e}");
+        }
+    }
+}
+
+pub fn status(proc_state: &FrpcProcState, state: &AppState, profile_id: &str) -> FrpcStatus {
+    let policy = state
+        .read()
+        .profile(profile_id)
+        .map(|p| p.config.restart.mode)
+        .unwrap_or_default();
+    let Some(instance) = proc_state.get(profile_id) else {
+        return FrpcStatus {
+            running: false,
+            pid: None,
+            policy,
+            restart: RestartState::default(),
+        };
+    };
+    let pid = instance
+        .child
+        .lock()
+        .ok()
+        .and_then(|g| g.as_ref().map(|c| c.id()));
+    let restart = instance
+        .restart
+        .lock()
+        .map(|r| r.clone())
+        .unwrap_or_default();
+    FrpcStatus {
+        running: pid.is_some(),
+        pid,
+        policy,
+        restart,
+    }
+}
+
+pub fn proxy_status(proc_state: &FrpcProcState, profile_id: &str) -> Vec<ProxyStatus> {
+    proc_state
+        .get(profile_id)
+        .map(|i| status_poller::snapshot(&i))
+        .unwrap_or_default()
+}
+
+pub fn is_running(proc_state: &FrpcProcState, profile_id: &str) -> Result<bool, String> {
+    let Some(instance) = proc_state.get(profile_id) else {
+        return Ok(false);
+    };
+    let g = instance.child.lock().map_err(|e| e.to_string())?;
     Ok(g.is_some())
 }
+
+pub fn any_running(proc_state: &FrpcProcState) -> bool {
+    proc_state
+        .all()
+        .iter()
+        .any(|(id, _)| is_running(proc_state, id).unwrap_or(false))
+}
+
+pub fn list_instances(state: &AppState, proc_state: &FrpcProcState) -> Vec<InstanceStatus> {
+    let profiles: Vec<(String, String)> = state
+        .read()
+        .profiles
+        .iter()
+        .map(|p| (p.id.clone(), p.name.clone()))
+        .collect();
+    profiles
+        .into_iter()
+        .map(|(id, name)| {
+            let pid = proc_state
+                .get(&id)
+                .and_then(|i| i.child.lock().ok().and_then(|g| g.as_ref().map(|c| c.id())));
+            InstanceStatus {
+                running: pid.is_some(),
+                id,
+                name,
+                pid,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daemon::DaemonHost;
+    use crate::domain::proxy::{Proxy, ProxyCommon, TcpProxy};
+    use crate::infra::http::stub;
+    use crate::infra::paths::{app_config_dir, instance_toml_file};
+    use std::collections::BTreeSet;
+
+    fn tcp(id: &str, local_port: u16) -> Proxy {
+        Proxy::Tcp(TcpProxy {
+            common: ProxyCommon {
+                id: id.into(),
+                name: id.into(),
+                enable: true,
+                local_ip: "127.0.0.1".into(),
+                local_port,
+                ..Default::default()
+            },
+            remote_port: 0,
+        })
+    }
+
+    // 重载只看句柄是否存在，进程本身很快退出也无妨
+    fn placeholder_child() -> std::process::Child {
+        Command::new(std::env::current_exe().unwrap())
+            .arg("--list")
+            .stdout(Stdio::null())
+            .spawn()
+            .unwrap()
+    }
+
+    async fn cleanup(
+        app: &DaemonHost,
+        profile_id: &str,
+        instance: &FrpcInstance,
+        dir: &std::path::Path,
+    ) {
+        stop_shim(app, profile_id, instance).await;
+        if let Some(mut child) = instance.child.lock().unwrap().take() {
+            let _ = child.wait();
+        }
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    fn set_proxies(state: &AppState, proxies: Vec<Proxy>) {
+        state.write().config_mut().proxies = proxies;
+    }
+
+    #[tokio::test]
+    async fn reload_only_touches_changed_routes() {
+        let server = stub::serve(&[("/api/reload", "200 OK", "")]).await;
+        let dir = std::env::temp_dir().join(format!("frpc-runner-{}", uuid::Uuid::new_v4()));
+        let app = DaemonHost::new(dir.clone(), dir.clone());
+        let state = app.app_state();
+        let profile_id = state.read().active_profile.clone();
+        {
+            let mut g = state.write();
+            let cfg = g.config_mut();
+            cfg.server_addr = "127.0.0.1".into();
+            cfg.switch.web_server = true;
+            cfg.web_server.addr = "127.0.0.1".into();
+            cfg.web_server.port = server.port();
+            cfg.web_server.user = "admin".into();
+            cfg.web_server.password = "pw".into();
+        }
+        set_proxies(&state, vec![tcp("a", 10001), tcp("b", 10002)]);
+        let proc_state = FrpcProcState::default();
+
+        // 未运行时不重载
+        assert!(!reload(&app, &state, &proc_state, &profile_id)
+            .await
+            .unwrap());
+        assert!(server.requests().is_empty());
+
+        let instance = proc_state.instance(&profile_id);
+        *instance.child.lock().unwrap() = Some(placeholder_child());
+
+        let route_ids = || -> BTreeSet<String> {
+            instance
+                .shim_routes
+                .lock()
+                .unwrap()
+                .keys()
+                .cloned()
+                .collect()
+        };
+        let handle = |id: &str| {
+            let routes = instance.shim_routes.lock().unwrap();
+            let r = &routes[id];
+            (r.listen, r.task.abort_handle())
+        };
+
+        assert!(reload(&app, &state, &proc_state, &profile_id)
+            .await
+            .unwrap());
+        assert_eq!(route_ids(), BTreeSet::from(["a".into(), "b".into()]));
+        let (a_listen, a_task) = handle("a");
+        let (_, b_task) = handle("b");
+
+        // 删 b、加 c，a 不变
+        set_proxies(&state, vec![tcp("a", 10001), tcp("c", 10003)]);
+        assert!(reload(&app, &state, &proc_state, &profile_id)
+            .await
+            .unwrap());
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(route_ids(), BTreeSet::from(["a".into(), "c".into()]));
+        assert_eq!(handle("a").0, a_listen);
+        assert!(!a_task.is_finished());
+        assert!(b_task.is_finished());
+        let (_, c_task) = handle("c");
+
+        // 改了目标的 a 换新监听，c 不受影响
+        set_proxies(&state, vec![tcp("a", 10004), tcp("c", 10003)]);
+        assert!(reload(&app, &state, &proc_state, &profile_id)
+            .await
+            .unwrap());
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let (new_listen, new_task) = handle("a");
+        assert_ne!(new_listen, a_listen);
+        assert!(a_task.is_finished());
+        assert!(!new_task.is_finished());
+        assert!(!c_task.is_finished());
+
+        let reqs = server.requests();
+        assert_eq!(reqs.len(), 3);
+        for r in &reqs {
+            assert_eq!(r.path, "/api/reload");
+            assert_eq!(r.header("authorization"), Some("Basic YWRtaW46cHc="));
+        }
+
+        cleanup(&app, &profile_id, &instance, &dir).await;
+    }
+
+    #[tokio::test]
+    async fn reload_reports_admin_errors() {
+        let server = stub::serve(&[("/api/reload", "500 Internal Server Error", "bad")]).await;
+        let dir = std::env::temp_dir().join(format!("frpc-runner-{}", uuid::Uuid::new_v4()));
+        let app = DaemonHost::new(dir.clone(), dir.clone());
+        let state = app.app_state();
+        let profile_id = state.read().active_profile.clone();
+        {
+            let mut g = state.write();
+            let cfg = g.config_mut();
+            cfg.switch.web_server = true;
+            cfg.web_server.port = server.port();
+        }
+        let proc_state = FrpcProcState::default();
+        let instance = proc_state.instance(&profile_id);
+        *instance.child.lock().unwrap() = Some(placeholder_child());
+
+        let err = reload(&app, &state, &proc_state, &profile_id)
+            .await
+            .unwrap_err();
+        assert!(err.contains("500"), "{err}");
+
+        cleanup(&app, &profile_id, &instance, &dir).await;
+    }
+
+    #[tokio::test]
+    async fn stop_cancels_a_pending_restart() {
+        let dir = std::env::temp_dir().join(format!("frpc-runner-{}", uuid::Uuid::new_v4()));
+        let app = DaemonHost::new(dir.clone(), dir.clone());
+        let state = app.app_state();
+        let profile_id = state.read().active_profile.clone();
+        state.write().config_mut().server_addr = "127.0.0.1".into();
+        set_proxies(&state, vec![tcp("a", 10001)]);
+        // 真启动时会跑测试程序自身，这里只要求路径能找到
+        let exe = std::env::current_exe().unwrap();
+        state.write().settings.insert(
+            crate::domain::active_frp::SETTINGS_ACTIVE_KEY.into(),
+            serde_json::json!({
+                "name": "test",
+                "archivePath": "",
+                "unpackDir": "",
+                "exePath": exe.to_string_lossy(),
+                "activatedAt": "",
+            }),
+        );
+        let proc_state = FrpcProcState::default();
+        let instance = proc_state.instance(&profile_id);
+
+        // 重启线程记下的代数，随后 stop() 在它启动进程之前完成
+        let generation = instance.generation.load(Ordering::SeqCst);
+        stop(&app, &state, &proc_state, &profile_id).await.unwrap();
+
+        let err = spawn_instance(&app, &state, &instance, &profile_id, generation)
+            .await
+            .unwrap_err();
+        assert!(err.contains("cancelled"), "{err}");
+        assert!(instance.child.lock().unwrap().is_none());
+        assert!(instance.shim_routes.lock().unwrap().is_empty());
+        assert!(!app_config_dir(&app)
+            .join(instance_toml_file(&profile_id))
+            .exists());
+
+        cleanup(&app, &profile_id, &instance, &dir).await;
+    }
+}
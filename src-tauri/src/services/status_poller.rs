@@ -0,0 +1,105 @@
+use crate::domain::proxy_status::{ProxyPhase, ProxyStatus};
+use crate::events::{instance_event, EVT_PROXY_STATUS};
+use crate::host::Host;
+use crate::infra::frpc_admin::AdminEndpoint;
+use crate::state::FrpcInstance;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ProxyStatusPayload {
+    pub instance: String,
+    pub proxies: Vec<ProxyStatus>,
+}
+
+/// frpc 启动后轮询 admin /api/status，状态有变化时发 EVT_PROXY_STATUS；进程退出后自行结束
+pub fn spawn<H: Host>(app: &H, instance_id: &str, instance: &Arc<FrpcInstance>) {
+    let app = app.clone();
+    let id = instance_id.to_string();
+    let inst = instance.clone();
+    let handle = tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let alive = inst.child.lock().map(|g| g.is_some()).unwrap_or(false);
+            if !alive {
+                publish(&app, &id, &inst, Vec::new());
+                break;
+            }
+            // 每次重新读取配置：webServer 开关和代理列表都可能已改动
+            let (endpoint, names) = {
+                let state = app.app_state();
+                let g = state.read();
+                match g.profile(&id) {
+                    Some(p) => (
+                        AdminEndpoint::from_config(&p.config),
+                        p.config
+                            .proxies
+                            .iter()
+                            .map(|x| (x.name.clone(), x.id.clone()))
+                            .collect::<Vec<_>>(),
+                    ),
+                    None => break,
+                }
+            };
+            let Some(endpoint) = endpoint else {
+                continue;
+            };
+            // admin API 在 frpc 刚启动时可能还没就绪，失败时保留上一次结果
+            let Ok(items) = endpoint.status().await else {
+                continue;
+            };
+            let mut list: Vec<ProxyStatus> = items
+                .into_iter()
+                .filter_map(|s| {
+                    let (_, pid) = names.iter().find(|(n, _)| *n == s.name)?;
+                    Some(ProxyStatus {
+                        id: pid.clone(),
+                        name: s.name,
+                        phase: ProxyPhase::parse(&s.status),
+                        error: s.err,
+                        remote_addr: s.remote_addr,
+                    })
+                })
+                .collect();
+            list.sort_by(|a, b| a.name.cmp(&b.name));
+            publish(&app, &id, &inst, list);
+        }
+    });
+    if let Ok(mut g) = instance.status_poller.lock() {
+        if let Some(old) = g.replace(handle) {
+            old.abort();
+        }
+    }
+}
+
+pub fn snapshot(instance: &FrpcInstance) -> Vec<ProxyStatus> {
+    instance
+        .proxy_status
+        .lock()
+        .map(|g| g.clone())
+        .unwrap_or_default()
+}
+
+fn publish<H: Host>(app: &H, instance_id: &str, instance: &FrpcInstance, list: Vec<ProxyStatus>) {
+    {
+        let Ok(mut g) = instance.proxy_status.lock() else {
+            return;
+        };
+        if *g == list {
+            return;
+        }
+        *g = list.clone();
+    }
+    let payload = ProxyStatusPayload {
+        instance: instance_id.to_string(),
+        proxies: list,
+    };
+    app.emit(
+        &instance_event(EVT_PROXY_STATUS, instance_id),
+        payload.clone(),
+    );
+    app.emit(EVT_PROXY_STATUS, payload);
+}
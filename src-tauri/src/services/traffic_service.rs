@@ -0,0 +1,137 @@
+use crate::domain::traffic::{
+    month_start, parse_time, points_to_csv, ExportFormat, ProxyUsage, TrafficPoint, TrafficQuery,
+    TrafficSpan,
+};
+use crate::errors::{AppError, Result};
+use crate::host::Host;
+use crate::infra::paths::{app_data_dir, TRAFFIC_DB};
+use crate::infra::traffic_db::{TrafficDb, TrafficDelta};
+use crate::state::AppState;
+use chrono::{DateTime, Local};
+use std::sync::atomic::{AtomicI64, Ordering};
+
+// 清理过期桶的间隔（秒），不必每次写入都做
+const PRUNE_EVERY: i64 = 3600;
+static LAST_PRUNE: AtomicI64 = AtomicI64::new(0);
+
+pub fn open<H: Host>(app: &H) -> Result<TrafficDb> {
+    TrafficDb::open(&app_data_dir(app).join(TRAFFIC_DB))
+}
+
+/// 写入某个 profile 各代理的新增字节数 (proxy_id, up, down)，并按需清理过期的桶
+pub fn record<H: Host>(
+    app: &H,
+    db: &mut TrafficDb,
+    profile_id: &str,
+    deltas: Vec<(String, u64, u64)>,
+) -> Result<()> {
+    let deltas: Vec<(String, u64, u64)> = deltas
+        .into_iter()
+        .filter(|(_, up, down)| *up > 0 || *down > 0)
+        .collect();
+    if deltas.is_empty() {
+        return Ok(());
+    }
+    // 记下当前名称，代理删除后报表里仍能认出来
+    let deltas: Vec<TrafficDelta> = {
+        let state = app.app_state();
+        let g = state.read();
+        let proxies = g.profile(profile_id).map(|p| p.config.proxies.as_slice());
+        deltas
+            .into_iter()
+            .map(|(id, up, down)| TrafficDelta {
+                proxy_name: proxies
+                    .and_then(|ps| ps.iter().find(|p| p.id == id))
+                    .map(|p| p.name.clone())
+                    .unwrap_or_default(),
+                proxy_id: id,
+                up,
+                down,
+            })
+            .collect()
+    };
+
+    let now = Local::now();
+    let buckets: Vec<(TrafficSpan, i64)> = TrafficSpan::ALL
+        .iter()
+        .map(|s| (*s, s.bucket_start(now)))
+        .collect();
+    db.add(profile_id, &buckets, &deltas)?;
+
+    let ts = now.timestamp();
+    if ts - LAST_PRUNE.load(Ordering::Relaxed) >= PRUNE_EVERY {
+        LAST_PRUNE.store(ts, Ordering::Relaxed);
+        for span in TrafficSpan::ALL {
+            if let Some(keep) = span.retention() {
+                db.prune(span, (now - keep).timestamp())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse_bound(s: Option<&str>) -> Result<Option<DateTime<Local>>> {
+    s.filter(|s| !s.trim().is_empty())
+        .map(|s| {
+            parse_time(s.trim()).ok_or_else(|| {
+                AppError::Validation(format!("invalid time: {s}, use RFC 3339 or YYYY-MM-DD"))
+            })
+        })
+        .transpose()
+}
+
+// 未给出 to 时到当前时刻为止；起点对齐到所在的桶
+fn range(
+    span: TrafficSpan,
+    from: Option<&str>,
+    to: Option<&str>,
+    default_from: impl FnOnce(DateTime<Local>) -> DateTime<Local>,
+) -> Result<(i64, i64)> {
+    let to = parse_bound(to)?.unwrap_or_else(Local::now);
+    let from = parse_bound(from)?.unwrap_or_else(|| default_from(to));
+    if from >= to {
+        return Err(AppError::Validation("from must be earlier than to".into()));
+    }
+    Ok((span.bucket_start(from), to.timestamp()))
+}
+
+fn profile_or_active(state: &AppState, profile_id: Option<&str>) -> String {
+    profile_id
+        .map(str::to_string)
+        .unwrap_or_else(|| state.read().active_profile.clone())
+}
+
+/// 画图用的时间序列
+pub fn query<H: Host>(app: &H, state: &AppState, q: &TrafficQuery) -> Result<Vec<TrafficPoint>> {
+    let profile_id = profile_or_active(state, q.profile_id.as_deref());
+    let (from, to) = range(q.span, q.from.as_deref(), q.to.as_deref(), |to| {
+        to - q.span.default_window()
+    })?;
+    open(app)?.points(&profile_id, q.proxy_id.as_deref(), q.span, from, to)
+}
+
+/// 每个代理在一段时间内的合计，默认从本月 1 日起
+pub fn usage<H: Host>(
+    app: &H,
+    state: &AppState,
+    profile_id: Option<&str>,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<Vec<ProxyUsage>> {
+    let profile_id = profile_or_active(state, profile_id);
+    let (from, to) = range(TrafficSpan::Day, from, to, month_start)?;
+    open(app)?.usage(&profile_id, from, to)
+}
+
+pub fn export<H: Host>(
+    app: &H,
+    state: &AppState,
+    q: &TrafficQuery,
+    format: ExportFormat,
+) -> Result<String> {
+    let points = query(app, state, q)?;
+    Ok(match format {
+        ExportFormat::Csv => points_to_csv(&points),
+        ExportFormat::Json => serde_json::to_string_pretty(&points)?,
+    })
+}
@@ -0,0 +1,187 @@
+use crate::domain::config::FrpcConfig;
+use crate::domain::profile::Profile;
+use crate::errors::{AppError, Result};
+use crate::host::Host;
+use crate::infra::crypto::{is_sealed, new_salt, SecretKey};
+use crate::infra::paths::STORE_FILE;
+use crate::infra::store::{store, VAULT_KEY};
+use crate::services::config_service::save_with_reason;
+use crate::services::history_service;
+use crate::state::{AppState, VaultState};
+use base64::engine::general_purpose::STANDARD as B64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+// 用固定明文的密文来校验主密码是否正确
+const CHECK_PLAIN: &str = "frpc-desktop";
+const MIN_PASSPHRASE_LEN: usize = 8;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct VaultMeta {
+    salt: String,
+    check: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VaultStatus {
+    pub enabled: bool,
+    pub locked: bool,
+}
+
+pub fn locked_error() -> AppError {
+    AppError::Other("secrets are locked, unlock with the master passphrase first".into())
+}
+
+pub fn ensure_unlocked(state: &AppState) -> Result<()> {
+    if state.read().vault.locked() {
+        return Err(locked_error());
+    }
+    Ok(())
+}
+
+pub fn status(state: &AppState) -> VaultStatus {
+    let g = state.read();
+    VaultStatus {
+        enabled: g.vault.enabled,
+        locked: g.vault.locked(),
+    }
+}
+
+/// 加密尚未加密的敏感字段，空值保持为空
+pub fn seal_config(cfg: &mut FrpcConfig, key: &SecretKey) -> Result<()> {
+    for s in cfg.secrets_mut() {
+        if !s.is_empty() && !is_sealed(s) {
+            *s = key.seal(s)?;
+        }
+    }
+    Ok(())
+}
+
+/// 解密敏感字段；遇到密文但没有密钥时报未解锁
+pub fn open_config(cfg: &mut FrpcConfig, key: Option<&SecretKey>) -> Result<()> {
+    for s in cfg.secrets_mut() {
+        if is_sealed(s) {
+            *s = key.ok_or_else(locked_error)?.open(s)?;
+        }
+    }
+    Ok(())
+}
+
+/// 写入 store 前的 profiles：开启加密后敏感字段一律存密文。
+/// 未解锁时内存里本就是密文，可以原样写回，但不能写入新填的明文
+pub fn profiles_for_store(vault: &VaultState, profiles: &[Profile]) -> Result<Vec<Profile>> {
+    let mut out = profiles.to_vec();
+    if !vault.enabled {
+        return Ok(out);
+    }
+    for p in out.iter_mut() {
+        match &vault.key {
+            Some(key) => seal_config(&mut p.config, key)?,
+            None => {
+                if p.config
+                    .secrets_mut()
+                    .iter()
+                    .any(|s| !s.is_empty() && !is_sealed(s))
+                {
+                    return Err(locked_error());
+                }
+            }
+        }
+    }
+    Ok(out)
+}
+
+fn load_meta<H: Host>(app: &H) -> Result<Option<VaultMeta>> {
+    let st = store(app, STORE_FILE)?;
+    Ok(st
+        .get(VAULT_KEY)
+        .and_then(|json| serde_json::from_value(json).ok()))
+}
+
+fn derive_checked(meta: &VaultMeta, passphrase: &str) -> Result<SecretKey> {
+    let salt = B64
+        .decode(&meta.salt)
+        .map_err(|e| AppError::Crypto(e.to_string()))?;
+    let key = SecretKey::derive(passphrase, &salt)?;
+    match key.open(&meta.check) {
+        Ok(plain) if plain == CHECK_PLAIN => Ok(key),
+        _ => Err(AppError::Crypto("wrong passphrase".into())),
+    }
+}
+
+// 全部成功才替换，避免一半明文一半密文
+fn open_profiles(state: &AppState, key: &SecretKey) -> Result<()> {
+    let mut g = state.write();
+    let mut profiles = g.profiles.clone();
+    for p in profiles.iter_mut() {
+        open_config(&mut p.config, Some(key))?;
+    }
+    g.profiles = profiles;
+    Ok(())
+}
+
+/// 设置主密码并把 store 与历史快照中的敏感字段改为密文
+pub fn enable<H: Host>(app: &H, state: &AppState, passphrase: &str) -> Result<()> {
+    if state.read().vault.enabled {
+        return Err(AppError::Other("master passphrase is already set".into()));
+    }
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(AppError::Validation(format!(
+            "passphrase must be at least {MIN_PASSPHRASE_LEN} characters"
+        )));
+    }
+    let salt = new_salt();
+    let key = SecretKey::derive(passphrase, &salt)?;
+    let meta = VaultMeta {
+        salt: B64.encode(salt),
+        check: key.seal(CHECK_PLAIN)?,
+    };
+    history_service::reseal(app, None, Some(&key))?;
+
+    let st = store(app, STORE_FILE)?;
+    st.set(VAULT_KEY, serde_json::to_value(&meta)?);
+    state.write().vault = VaultState {
+        enabled: true,
+        key: Some(key),
+    };
+    save_with_reason(app, state, "enable encryption")
+}
+
+/// 启动后输入主密码，在内存中解密
+pub fn unlock<H: Host>(app: &H, state: &AppState, passphrase: &str) -> Result<()> {
+    let meta =
+        load_meta(app)?.ok_or_else(|| AppError::Other("master passphrase is not set".into()))?;
+    let key = derive_checked(&meta, passphrase)?;
+    if !state.read().vault.locked() {
+        return Ok(());
+    }
+    unlock_with_key(state, key)?;
+    // 锁定期间未记录历史，解锁后补一份
+    if let Err(e) = history_service::record(app, state, "unlocked") {
+        eprintln!("[history] record failed: {e}");
+    }
+    Ok(())
+}
+
+/// 用已有的密钥解密内存中的 profiles，例如守护进程重新读取 store 之后
+pub fn unlock_with_key(state: &AppState, key: SecretKey) -> Result<()> {
+    open_profiles(state, &key)?;
+    state.write().vault.key = Some(key);
+    Ok(())
+}
+
+/// 校验主密码后关闭加密，store 与历史快照恢复为明文
+pub fn disable<H: Host>(app: &H, state: &AppState, passphrase: &str) -> Result<()> {
+    let meta =
+        load_meta(app)?.ok_or_else(|| AppError::Other("master passphrase is not set".into()))?;
+    let key = derive_checked(&meta, passphrase)?;
+    if state.read().vault.locked() {
+        open_profiles(state, &key)?;
+    }
+    history_service::reseal(app, Some(&key), None)?;
+
+    let st = store(app, STORE_FILE)?;
+    st.delete(VAULT_KEY);
+    state.write().vault = VaultState::default();
+    save_with_reason(app, state, "disable encryption")
+}
@@ -0,0 +1,191 @@
+use crate::errors::{AppError, Result};
+use crate::services::config_service::{render_toml, write_private};
+use crate::services::vault_service;
+use crate::services::version_service::get_active;
+use crate::state::{AppState, FrpcInstance};
+use regex::Regex;
+use serde::Serialize;
+use std::path::Path;
+use std::process::Command;
+use std::sync::OnceLock;
+
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyMessage {
+    pub message: String,
+    // 对应 toml 中的行号（从 1 开始），定位不到时为空
+    pub line: Option<usize>,
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyResult {
+    pub ok: bool,
+    pub version: String,
+    pub messages: Vec<VerifyMessage>,
+    // 被校验的 toml 原文，便于前端按行号展示
+    pub toml: String,
+}
+
+/// 用当前激活的 frpc 执行 `frpc verify -c`，检查生成的配置能否被该版本接受
+pub fn verify_config(state: &AppState, profile_id: &str) -> Result<VerifyResult> {
+    let active =
+        get_active(state).ok_or_else(|| AppError::Other("no active frpc version".into()))?;
+    vault_service::ensure_unlocked(state)?;
+    let cfg = state
+        .read()
+        .profile(profile_id)
+        .map(|p| p.config.clone())
+        .ok_or_else(|| AppError::Other(format!("profile not found: {profile_id}")))?;
+
+    // 临时实例：shim 端口只用于占位，校验结束即释放
+    let toml_str = render_toml(&cfg, Some(&FrpcInstance::default()))?;
+    let path = std::env::temp_dir().join(format!("frpc-verify-{}.toml", uuid::Uuid::new_v4()));
+    write_private(&path, &toml_str)?;
+
+    let mut cmd = Command::new(&active.exe_path);
+    cmd.arg("verify").arg("-c").arg(&path);
+    #[cfg(windows)]
+    {
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    let output = cmd.output();
+    let _ = std::fs::remove_file(&path);
+    let output = output.map_err(|e| AppError::Other(format!("run frpc verify failed: {e}")))?;
+
+    let mut text = String::from_utf8_lossy(&output.stdout).to_string();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+
+    Ok(VerifyResult {
+        ok: output.status.success(),
+        messages: parse_output(&text, &path, &toml_str),
+        version: active.name,
+        toml: toml_str,
+    })
+}
+
+// 临时文件路径换成 frpc.toml，避免把随机文件名展示给用户
+fn parse_output(text: &str, path: &Path, toml_str: &str) -> Vec<VerifyMessage> {
+    let path_str = path.display().to_string();
+    text.lines()
+        .map(|l| l.replace(&path_str, "frpc.toml"))
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .map(|l| locate(&l, toml_str))
+        .collect()
+}
+
+fn line_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"line (\d+)").unwrap())
+}
+
+fn field_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r#"unknown field "([^"]+)""#).unwrap())
+}
+
+// frpc 的报错有两种定位方式：toml 解析错误自带 "line N"；严格模式下的
+// unknown field "xxx" 只给键名，需要回到 toml 原文里找
+fn locate(message: &str, toml_str: &str) -> VerifyMessage {
+    let line = line_re()
+        .captures(message)
+        .and_then(|c| c[1].parse::<usize>().ok())
+        .or_else(|| {
+            let key = field_re().captures(message)?[1].to_string();
+            toml_str
+                .lines()
+                .position(|l| {
+                    let l = l.trim_start();
+                    l.strip_prefix(&key)
+                        .is_some_and(|rest| rest.trim_start().starts_with('='))
+                        || l.strip_prefix('"')
+                            .and_then(|r| r.strip_prefix(&key))
+                            .is_some_and(|rest| rest.starts_with('"'))
+                })
+                .map(|i| i + 1)
+        });
+
+    VerifyMessage {
+        message: message.to_string(),
+        text: line
+            .and_then(|n| n.checked_sub(1))
+            .and_then(|i| toml_str.lines().nth(i))
+            .map(str::to_string),
+        line,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOML: &str = r#"serverAddr = "frp.example.com"
+serverPort = 7000
+
+[[proxies]]
+name = "ssh"
+type = "tcp"
+localPort = 22
+remotePort = 6000
+bogus = 1
+
+[[proxies]]
+"weird key" = true
+"#;
+
+    fn located(message: &str) -> (Option<usize>, Option<String>) {
+        let m = locate(message, TOML);
+        assert_eq!(m.message, message);
+        (m.line, m.text)
+    }
+
+    #[test]
+    fn maps_reported_line_numbers() {
+        assert_eq!(
+            located("toml: line 6: expected a value"),
+            (Some(6), Some(r#"type = "tcp""#.into()))
+        );
+        // 超出范围或为 0 时只保留行号
+        assert_eq!(located("line 99: eof"), (Some(99), None));
+        assert_eq!(located("line 0: eof"), (Some(0), None));
+        assert_eq!(located("start error: port unavailable"), (None, None));
+    }
+
+    #[test]
+    fn finds_unknown_fields_in_toml() {
+        assert_eq!(
+            located(r#"json: unknown field "bogus""#),
+            (Some(9), Some("bogus = 1".into()))
+        );
+        assert_eq!(
+            located(r#"json: unknown field "weird key""#),
+            (Some(12), Some(r#""weird key" = true"#.into()))
+        );
+        // 只匹配完整键名
+        assert_eq!(located(r#"json: unknown field "bog""#), (None, None));
+        assert_eq!(located(r#"json: unknown field "missing""#), (None, None));
+    }
+
+    #[test]
+    fn replaces_temp_path_and_drops_blank_lines() {
+        let path = Path::new("/tmp/frpc-verify-1234.toml");
+        let text = "\n  /tmp/frpc-verify-1234.toml: line 2: bad\n\nframe ok\n";
+        let messages = parse_output(text, path, TOML);
+        let got: Vec<_> = messages
+            .iter()
+            .map(|m| (m.message.as_str(), m.line))
+            .collect();
+        assert_eq!(
+            got,
+            [("frpc.toml: line 2: bad", Some(2)), ("frame ok", None)]
+        );
+    }
+}
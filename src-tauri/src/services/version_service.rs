@@ -2,6 +2,7 @@ use crate::domain::active_frp::{ActiveFrp, SETTINGS_ACTIVE_KEY};
 use crate::domain::progress_payload::ProgressPayload;
 use crate::domain::version::{FrpVersion, SETTINGS_VERSIONS_KEY};
 use crate::events::{EVT_ACTIVATING_STATUS, EVT_DOWNLOAD_PROGRESS};
+use crate::host::Host;
 use crate::infra::archive::{extract_archive_to, find_executable_recursively, frpc_name};
 use crate::infra::paths::unpack_dir_for;
 use crate::services::config_service::save_now;
@@ -20,7 +21,6 @@ use serde::Deserialize;
 use serde_json::json;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter, State};
 use tokio::io::AsyncWriteExt;
 use tokio::time::{self, Duration as TokioDuration, MissedTickBehavior};
 
@@ -39,8 +39,8 @@ struct GhRelease {
     assets: Option<Vec<GhAsset>>,
 }
 
-fn update_frp_version(
-    app: &AppHandle,
+fn update_frp_version<H: Host>(
+    app: &H,
     state: &AppState,
     name: &str,
     exist: Option<bool>,
@@ -118,7 +118,7 @@ fn pick_asset_by_name(name: &str) -> bool {
     }
 }
 
-pub async fn get_versions(app: &AppHandle, state: &AppState) -> Result<Vec<FrpVersion>> {
+pub async fn get_versions<H: Host>(app: &H, state: &AppState) -> Result<Vec<FrpVersion>> {
     let cached: Option<Vec<FrpVersion>> = {
         let r = state.read();
         r.settings
@@ -150,7 +150,7 @@ pub async fn get_versions(app: &AppHandle, state: &AppState) -> Result<Vec<FrpVe
         .await
         .map_err(|e| crate::errors::AppError::Other(e.to_string()))?;
 
-    let base = get_download_dir(&app)?;
+    let base = get_download_dir(app)?;
     let mut versions: Vec<FrpVersion> = Vec::new();
 
     for rel in releases {
@@ -181,7 +181,7 @@ pub async fn get_versions(app: &AppHandle, state: &AppState) -> Result<Vec<FrpVe
                 .map_err(|e| crate::errors::AppError::Other(e.to_string()))?,
         );
     }
-    save_now(&app, &state)?;
+    save_now(app, state)?;
 
     Ok(versions)
 }
@@ -193,7 +193,7 @@ pub fn get_active(state: &AppState) -> Option<ActiveFrp> {
         .and_then(|v| serde_json::from_value::<ActiveFrp>(v.clone()).ok())
 }
 
-pub fn set_active(app: &AppHandle, state: &AppState, active_version: &ActiveFrp) -> Result<()> {
+pub fn set_active<H: Host>(app: &H, state: &AppState, active_version: &ActiveFrp) -> Result<()> {
     {
         let mut w = state.write();
         w.settings.insert(
@@ -205,7 +205,7 @@ pub fn set_active(app: &AppHandle, state: &AppState, active_version: &ActiveFrp)
     Ok(())
 }
 
-pub fn activate(app: &AppHandle, state: &AppState, name: &str) -> Result<()> {
+pub fn activate<H: Host>(app: &H, state: &AppState, name: &str) -> Result<()> {
     // 1) 确认压缩包存在
     let downloads_dir = get_download_dir(app)?;
     let archive = downloads_dir.join(name);
@@ -249,14 +249,12 @@ pub fn activate(app: &AppHandle, state: &AppState, name: &str) -> Result<()> {
     // 5) 写入激活记录
     set_active(app, state, &active_version)?;
     update_frp_version(app, state, name, None, Option::from(true))?;
-    let _ = app
-        .clone()
-        .emit(EVT_ACTIVATING_STATUS, json!({ "status": false }));
+    app.emit(EVT_ACTIVATING_STATUS, json!({ "status": false }));
 
     Ok(())
 }
 
-pub fn clear_active_if_matches(app: &AppHandle, state: &AppState, name: &str) -> Result<()> {
+pub fn clear_active_if_matches<H: Host>(app: &H, state: &AppState, name: &str) -> Result<()> {
     let mut need_clear = false;
     {
         let g = state.read();
@@ -280,29 +278,29 @@ pub fn clear_active_if_matches(app: &AppHandle, state: &AppState, name: &str) ->
     Ok(())
 }
 
-fn stop_if_target_active(
-    app: &AppHandle,
+async fn stop_if_target_active<H: Host>(
+    app: &H,
     state: &AppState,
-    proc_state: &State<FrpcProcState>,
+    proc_state: &FrpcProcState,
     name: &str,
 ) {
     if let Some(active) = get_active(state) {
         if active.name == name {
-            let running = runner::is_running(proc_state).unwrap_or_else(|_e| false);
-            if running {
-                let _ = runner::stop(app, proc_state);
+            // 所有实例共用同一个 frpc 可执行文件
+            if runner::any_running(proc_state) {
+                runner::stop_all(app, state, proc_state).await;
             }
         }
     }
 }
 
-pub fn deactivate(
-    app: &AppHandle,
+pub async fn deactivate<H: Host>(
+    app: &H,
     state: &AppState,
-    proc_state: &State<FrpcProcState>,
+    proc_state: &FrpcProcState,
     name: &str,
 ) -> Result<()> {
-    stop_if_target_active(app, state, proc_state, name);
+    stop_if_target_active(app, state, proc_state, name).await;
 
     // 如果当前激活的是它，先清空记录（调用者也可在外层先停进程）
     clear_active_if_matches(app, state, name)?;
@@ -318,13 +316,13 @@ pub fn deactivate(
     Ok(())
 }
 
-pub fn delete(
-    app: &AppHandle,
+pub async fn delete<H: Host>(
+    app: &H,
     state: &AppState,
-    proc_state: &State<FrpcProcState>,
+    proc_state: &FrpcProcState,
     name: &str,
 ) -> Result<()> {
-    stop_if_target_active(app, state, proc_state, name);
+    stop_if_target_active(app, state, proc_state, name).await;
 
     // 如果当前激活的是它，先清空记录（调用者也可在外层先停进程）
     clear_active_if_matches(app, state, name)?;
@@ -347,13 +345,13 @@ pub fn delete(
     Ok(())
 }
 
-pub async fn download(app: &AppHandle, state: &AppState, name: &str, url: &str) -> Result<()> {
+pub async fn download<H: Host>(app: &H, state: &AppState, name: &str, url: &str) -> Result<()> {
     let dir = get_download_dir(app)?;
     let target = dir.join(name);
 
     // 已存在：直接发 100 并返回
     if target.exists() {
-        let _ = app.emit(
+        app.emit(
             EVT_DOWNLOAD_PROGRESS,
             ProgressPayload {
                 name: name.to_string(),
@@ -396,7 +394,7 @@ pub async fn download(app: &AppHandle, state: &AppState, name: &str, url: &str)
 
         reporter = Some(tokio::spawn(async move {
             let mut last_pct: u8 = 0;
-            let _ = app_cloned.emit(
+            app_cloned.emit(
                 EVT_DOWNLOAD_PROGRESS,
                 ProgressPayload {
                     name: name_s.clone(),
@@ -418,7 +416,7 @@ pub async fn download(app: &AppHandle, state: &AppState, name: &str, url: &str)
 
                 if pct != last_pct {
                     last_pct = pct;
-                    let _ = app_cloned.emit(
+                    app_cloned.emit(
                         EVT_DOWNLOAD_PROGRESS,
                         ProgressPayload {
                             name: name_s.clone(),
@@ -435,7 +433,7 @@ pub async fn download(app: &AppHandle, state: &AppState, name: &str, url: &str)
             let r = rec.load(Ordering::Relaxed);
             let pct = ((r as f64 / total as f64) * 100.0).floor() as u8;
             if pct < 100 {
-                let _ = app_cloned.emit(
+                app_cloned.emit(
                     EVT_DOWNLOAD_PROGRESS,
                     ProgressPayload {
                         name: name_s.clone(),
@@ -472,7 +470,7 @@ pub async fn download(app: &AppHandle, state: &AppState, name: &str, url: &str)
     }
 
     // 一定发最终 100
-    let _ = app.emit(
+    app.emit(
         EVT_DOWNLOAD_PROGRESS,
         ProgressPayload {
             name: name.to_string(),
@@ -1,12 +1,68 @@
 use crate::domain::config::FrpcConfig;
+use crate::domain::profile::{Profile, DEFAULT_PROFILE_NAME};
+use crate::infra::crypto::SecretKey;
 use serde_json::{Map, Value};
 use std::process::Child;
 use std::sync::{Arc, RwLock};
 
-#[derive(Default)]
 pub struct Inner {
-    pub config: FrpcConfig,
+    // 至少保留一个 profile，active_profile 始终指向其中之一
+    pub profiles: Vec<Profile>,
+    pub active_profile: String,
     pub settings: Map<String, Value>,
+    pub vault: VaultState,
+}
+
+/// 主密码模式：开启后 store 中的敏感字段加密保存
+#[derive(Debug, Default)]
+pub struct VaultState {
+    pub enabled: bool,
+    // 解锁后才有；未解锁时内存中的敏感字段仍是密文
+    pub key: Option<SecretKey>,
+}
+
+impl VaultState {
+    pub fn locked(&self) -> bool {
+        self.enabled && self.key.is_none()
+    }
+}
+
+impl Default for Inner {
+    fn default() -> Self {
+        let profile = Profile::new(DEFAULT_PROFILE_NAME, FrpcConfig::default());
+        Self {
+            active_profile: profile.id.clone(),
+            profiles: vec![profile],
+            settings: Map::new(),
+            vault: VaultState::default(),
+        }
+    }
+}
+
+impl Inner {
+    fn active_index(&self) -> usize {
+        self.profiles
+            .iter()
+            .position(|p| p.id == self.active_profile)
+            .unwrap_or(0)
+    }
+    pub fn profile(&self, id: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.id == id)
+    }
+    pub fn profile_mut(&mut self, id: &str) -> Option<&mut Profile> {
+        self.profiles.iter_mut().find(|p| p.id == id)
+    }
+    pub fn active(&self) -> &Profile {
+        &self.profiles[self.active_index()]
+    }
+    /// 当前激活 profile 的配置
+    pub fn config(&self) -> &FrpcConfig {
+        &self.active().config
+    }
+    pub fn config_mut(&mut self) -> &mut FrpcConfig {
+        let idx = self.active_index();
+        &mut self.profiles[idx].config
+    }
 }
 
 #[derive(Clone, Default)]
@@ -21,27 +77,55 @@ impl AppState {
     }
 }
 
-use crate::services::local_proxy::ProxySpec;
+use crate::domain::proxy_status::ProxyStatus;
+use crate::domain::restart::RestartState;
+use crate::services::local_proxy::{ProxySpec, ShimRoute};
+use std::collections::HashMap;
+use std::sync::atomic::AtomicU64;
 use std::sync::Mutex;
-use tauri::{AppHandle, Manager};
 use tauri_plugin_shell::process::CommandChild;
 use tokio::task::JoinHandle;
 
+/// 一个 frpc 进程及其 shim，按 profile id 区分
 #[derive(Default)]
-pub struct FrpcProcState {
+pub struct FrpcInstance {
     pub child: Arc<Mutex<Option<Child>>>,
-    pub watchdog: Arc<Mutex<Option<CommandChild>>>,
     pub proxy_specs: Arc<Mutex<Vec<ProxySpec>>>,
-    pub shim_tasks: Mutex<Vec<JoinHandle<()>>>,
+    pub shim_routes: Arc<Mutex<HashMap<String, ShimRoute>>>,
+    pub shim_sampler: Mutex<Option<JoinHandle<()>>>,
+    pub restart: Mutex<RestartState>,
+    // 每次手动 start / stop 自增；退出监控线程据此判断重启是否已被取消
+    pub generation: AtomicU64,
+    pub proxy_status: Mutex<Vec<ProxyStatus>>,
+    pub status_poller: Mutex<Option<JoinHandle<()>>>,
+    pub monitor: Mutex<Option<std::thread::JoinHandle<()>>>,
 }
 
-pub fn notify_watchdog(app: &AppHandle, msg: String) -> Result<(), std::io::Error> {
-    let state: tauri::State<'_, FrpcProcState> = app.state();
-    {
-        let mut guard = state.watchdog.lock().unwrap();
-        if let Some(child) = guard.as_mut() {
-            let _ = child.write((msg + "\n").as_bytes());
-        }
+#[derive(Default)]
+pub struct FrpcProcState {
+    pub instances: Mutex<HashMap<String, Arc<FrpcInstance>>>,
+    pub watchdog: Arc<Mutex<Option<CommandChild>>>,
+}
+
+impl FrpcProcState {
+    /// 取实例，不存在则新建
+    pub fn instance(&self, id: &str) -> Arc<FrpcInstance> {
+        self.instances
+            .lock()
+            .unwrap()
+            .entry(id.to_string())
+            .or_default()
+            .clone()
+    }
+    pub fn get(&self, id: &str) -> Option<Arc<FrpcInstance>> {
+        self.instances.lock().unwrap().get(id).cloned()
+    }
+    pub fn all(&self) -> Vec<(String, Arc<FrpcInstance>)> {
+        self.instances
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
     }
-    Ok(())
 }
@@ -1,11 +1,38 @@
 import {call} from './_invoke'
 import type {FrpcConfig} from '@/domain/frpc'
-import type {Proxy} from '@/domain/types'
+import type {Proxy, RateLimit, Visitor} from '@/domain/types'
 
 export const loadConfig = () => call<FrpcConfig>('load_config')
 export const saveServer = (cfg: FrpcConfig) => call<void>('save_server', {partial: cfg})
+export const importConfig = (path: string, apply: boolean) => call<any>('import_config', {path, apply})
+export const probeOidc = () => call<{ tokenType: string; expiresIn: number | null }>('probe_oidc')
 export const loadProxies = () => call<Proxy[]>('load_proxies')
 export const saveProxy = (proxy: Proxy) => call<void>('save_proxy', {proxy})
 export const removeProxy = (name: string) => call<boolean>('remove_proxy', {name})
+// 立即作用于运行中的代理，不重载 frpc；返回是否已生效
+export const setProxyRateLimit = (id: string, limit: RateLimit, profileId?: string) =>
+    call<boolean>('set_proxy_rate_limit', {id, limit, profileId})
+export const loadVisitors = () => call<Visitor[]>('load_visitors')
+export const saveVisitor = (visitor: Visitor) => call<void>('save_visitor', {visitor})
+export const removeVisitor = (id: string) => call<boolean>('remove_visitor', {id})
 export const setSetting = (key: string, value: unknown) => call<boolean>('set_setting', {key, value})
-export const getSetting = <T = unknown>(key: string) => call<T | null>('get_setting', {key})
\ No newline at end of file
+export const getSetting = <T = unknown>(key: string) => call<T | null>('get_setting', {key})
+export interface ValidationIssue {
+    severity: 'error' | 'warning'
+    // 例如 serverAddr、proxies[ssh].localIP
+    path: string
+    targetId?: string | null
+    message: string
+}
+
+export const validateConfig = (profileId?: string) =>
+    call<{ issues: ValidationIssue[] }>('validate_config', {profileId})
+
+export interface VerifyResult {
+    ok: boolean
+    version: string
+    messages: { message: string; line?: number | null; text?: string | null }[]
+    toml: string
+}
+
+export const verifyConfig = (profileId?: string) => call<VerifyResult>('verify_config', {profileId})
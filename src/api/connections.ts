@@ -0,0 +1,26 @@
+import {call} from './_invoke'
+
+// peer 为真实访问者地址；frpc 未带 PROXY protocol 头时是 frpc 的本地地址
+export interface ConnInfo {
+    id: number
+    proxy: string
+    peer: string
+    target: string
+    startedAt: string
+    up: number
+    down: number
+}
+
+// 事件 frp:connections 的 payload，连接表非空时每秒一次
+export interface ConnectionsEvent {
+    instance: string
+    connections: ConnInfo[]
+}
+
+// 不传 profileId 时作用于当前激活的 profile；不传 proxyId 时列出全部代理
+export const listConnections = (profileId?: string, proxyId?: string) =>
+    call<ConnInfo[]>('list_connections', {profileId, proxyId})
+export const killConnection = (proxyId: string, connId: number, profileId?: string) =>
+    call<boolean>('kill_connection', {profileId, proxyId, connId})
+export const killProxyConnections = (proxyId: string, profileId?: string) =>
+    call<number>('kill_proxy_connections', {profileId, proxyId})
@@ -1,5 +1,87 @@
 import {call} from './_invoke'
+import type {RestartMode} from '@/domain/frpc'
 
-export const startFrpc = () => call<number>('start_frpc')
-export const stopFrpc = () => call<void>('stop_frpc')
-export const frpcStatus = () => call<boolean>('frpc_status')
\ No newline at end of file
+export interface InstanceStatus {
+    id: string
+    name: string
+    running: boolean
+    pid?: number | null
+}
+
+export interface FrpcStatus {
+    running: boolean
+    pid?: number | null
+    policy: RestartMode
+    restart: {
+        attempt: number
+        lastExitCode?: number | null
+        nextRetryAt?: number | null
+        gaveUp: boolean
+    }
+}
+
+export interface RestartEvent {
+    instance: string
+    attempt: number
+    maxRetries: number
+    delayMs: number
+    code: number | null
+    gaveUp: boolean
+}
+
+// 不传 profileId 时作用于当前激活的 profile
+export const startFrpc = (profileId?: string) => call<number>('start_frpc', {profileId})
+export const stopFrpc = (profileId?: string) => call<void>('stop_frpc', {profileId})
+export const frpcStatus = (profileId?: string) => call<FrpcStatus>('frpc_status', {profileId})
+export const listInstances = () => call<InstanceStatus[]>('list_instances')
+
+export type ProxyPhase = 'new' | 'wait-start' | 'start-error' | 'running' | 'check-failed' | 'closed' | 'unknown'
+
+export interface ProxyStatus {
+    id: string
+    name: string
+    phase: ProxyPhase
+    error: string
+    remoteAddr: string
+}
+
+// 事件 frpc://proxy-status 的 payload
+export interface ProxyStatusEvent {
+    instance: string
+    proxies: ProxyStatus[]
+}
+
+export const proxyStatus = (profileId?: string) => call<ProxyStatus[]>('proxy_status', {profileId})
+
+export type FrpcLogLevel = 'trace' | 'debug' | 'info' | 'warn' | 'error'
+
+// 事件 frpc://log：每行日志的解析结果
+export interface FrpcLogLine {
+    instance: string
+    stream: 'stdout' | 'stderr'
+    ts?: string | null
+    level: FrpcLogLevel
+    source?: string | null
+    runId?: string | null
+    proxy?: string | null
+    message: string
+    raw: string
+}
+
+export type FrpcLogEventKind =
+    'login-success'
+    | 'login-failed'
+    | 'proxy-started'
+    | 'proxy-start-error'
+    | 'reconnecting'
+    | 'work-conn-error'
+
+// 事件 frpc://event：识别出的关键事件
+export interface FrpcLogEvent {
+    instance: string
+    kind: FrpcLogEventKind
+    proxy?: string | null
+    level: FrpcLogLevel
+    ts?: string | null
+    message: string
+}
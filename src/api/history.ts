@@ -0,0 +1,35 @@
+import {call} from './_invoke'
+import type {FrpcConfig} from '@/domain/frpc'
+
+export interface SnapshotSummary {
+    id: string
+    profileId: string
+    createdAt: string
+    reason: string
+    proxyCount: number
+    visitorCount: number
+}
+
+export interface ConfigSnapshot {
+    id: string
+    profileId: string
+    createdAt: string
+    reason: string
+    config: FrpcConfig
+}
+
+export type ChangeKind = 'added' | 'removed' | 'changed'
+
+// path 形如 serverPort、proxies[ssh].localPort
+export interface ConfigChange {
+    path: string
+    kind: ChangeKind
+    before?: unknown
+    after?: unknown
+}
+
+export const listSnapshots = (profileId?: string) => call<SnapshotSummary[]>('list_snapshots', {profileId})
+export const getSnapshot = (id: string) => call<ConfigSnapshot>('get_snapshot', {id})
+// 不传 to 时与当前配置比较
+export const diffSnapshots = (from: string, to?: string) => call<ConfigChange[]>('diff_snapshots', {from, to})
+export const rollbackSnapshot = (id: string) => call<void>('rollback_snapshot', {id})
@@ -0,0 +1,25 @@
+import {call} from './_invoke'
+import type {FrpcLogLevel, FrpcLogLine} from './frpc'
+
+export interface LogSession {
+    id: string
+    parts: number
+    size: number
+    updatedAt: number
+}
+
+export interface LogPage {
+    total: number
+    offset: number
+    lines: Omit<FrpcLogLine, 'instance' | 'stream'>[]
+}
+
+export const listLogSessions = (profileId?: string) => call<LogSession[]>('list_log_sessions', {profileId})
+
+// level 为最低级别
+export const readLog = (session: string, opts: {
+    profileId?: string,
+    offset?: number,
+    limit?: number,
+    level?: FrpcLogLevel
+} = {}) => call<LogPage>('read_log', {session, ...opts})
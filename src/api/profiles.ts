@@ -0,0 +1,24 @@
+import {call} from './_invoke'
+import type {FrpcConfig} from '@/domain/frpc'
+
+export interface ProfileSummary {
+    id: string
+    name: string
+    serverAddr: string
+    serverPort: number
+    proxyCount: number
+    active: boolean
+}
+
+export interface Profile {
+    id: string
+    name: string
+    config: FrpcConfig
+}
+
+export const listProfiles = () => call<ProfileSummary[]>('list_profiles')
+export const createProfile = (name: string) => call<Profile>('create_profile', {name})
+export const cloneProfile = (id: string, name: string) => call<Profile>('clone_profile', {id, name})
+export const renameProfile = (id: string, name: string) => call<void>('rename_profile', {id, name})
+export const deleteProfile = (id: string) => call<void>('delete_profile', {id})
+export const activateProfile = (id: string) => call<void>('activate_profile', {id})
@@ -0,0 +1,56 @@
+import {call} from './_invoke'
+
+export type TrafficSpan = 'minute' | 'hour' | 'day'
+
+// profileId 为空时查当前激活的 profile；from / to 为 RFC 3339 或 YYYY-MM-DD
+export interface TrafficQuery {
+    profileId?: string
+    proxyId?: string
+    span: TrafficSpan
+    from?: string
+    to?: string
+}
+
+export interface TrafficPoint {
+    proxyId: string
+    proxyName: string
+    // 桶的起点，RFC 3339
+    bucket: string
+    up: number
+    down: number
+}
+
+export interface ProxyUsage {
+    proxyId: string
+    proxyName: string
+    up: number
+    down: number
+    total: number
+}
+
+export type QuotaPeriod = 'daily' | 'monthly'
+export type QuotaLevel = 'ok' | 'warning' | 'exceeded'
+
+export interface QuotaStatus {
+    proxyId: string
+    proxyName: string
+    period: QuotaPeriod
+    used: number
+    limit: number
+    level: QuotaLevel
+    since: string
+    resetsAt: string
+}
+
+// frp:quota 事件，级别变化或周期重置时发出
+export interface QuotaEvent extends QuotaStatus {
+    instance: string
+}
+
+export const queryTraffic = (query: TrafficQuery) => call<TrafficPoint[]>('query_traffic', {query})
+// 不传 from 时从本月 1 日开始
+export const trafficUsage = (opts: {profileId?: string, from?: string, to?: string} = {}) =>
+    call<ProxyUsage[]>('traffic_usage', opts)
+export const exportTraffic = (query: TrafficQuery, format: 'csv' | 'json') =>
+    call<string>('export_traffic', {query, format})
+export const quotaStatus = (profileId?: string) => call<QuotaStatus[]>('quota_status', {profileId})
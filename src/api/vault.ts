@@ -0,0 +1,12 @@
+import {call} from './_invoke'
+
+export interface VaultStatus {
+    enabled: boolean
+    // 已开启但尚未输入主密码
+    locked: boolean
+}
+
+export const vaultStatus = () => call<VaultStatus>('vault_status')
+export const enableVault = (passphrase: string) => call<void>('enable_vault', {passphrase})
+export const unlockVault = (passphrase: string) => call<void>('unlock_vault', {passphrase})
+export const disableVault = (passphrase: string) => call<void>('disable_vault', {passphrase})
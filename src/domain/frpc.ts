@@ -1,20 +1,73 @@
-import type {AuthType} from './types'
-import type {HttpProxy} from './types'
+import type {AuthType, TransportProtocol} from './types'
+import type {HttpProxy, Visitor} from './types'
+
+export interface Transport {
+    protocol: TransportProtocol
+    tls: { enable: boolean; certFile: string; keyFile: string; trustedCaFile: string; serverName: string }
+    poolCount: number
+    tcpMux: boolean
+    heartbeatInterval: number
+    heartbeatTimeout: number
+    proxyURL: string
+    dialServerTimeout: number
+}
+
+export type RestartMode = 'never' | 'on-failure' | 'always'
+
+export interface RestartPolicy {
+    mode: RestartMode
+    initialDelayMs: number
+    maxDelayMs: number
+    maxRetries: number
+    jitterMs: number
+}
 
 export interface FrpcConfig {
     serverAddr: string
     serverPort: number
-    auth: { method: AuthType; token: string }
+    auth: {
+        method: AuthType
+        token: string
+        additionalScopes: ('HeartBeats' | 'NewWorkConns')[]
+        oidc: {
+            clientID: string
+            clientSecret: string
+            audience: string
+            scope: string
+            tokenEndpointURL: string
+            additionalEndpointParams: Record<string, string>
+        }
+    }
     webServer: { addr: string; port: number; user: string; password: string }
+    transport: Transport
     proxies: HttpProxy[] | any[]
-    switch: { auth: boolean; webServer: boolean }
+    visitors: Visitor[]
+    switch: { auth: boolean; webServer: boolean; transport: boolean }
+    restart: RestartPolicy
 }
 
 export const defaultConfig: FrpcConfig = {
     serverAddr: '127.0.0.1',
     serverPort: 7000,
-    auth: {method: 'token' as any, token: ''},
+    auth: {
+        method: 'token' as any,
+        token: '',
+        additionalScopes: [],
+        oidc: {clientID: '', clientSecret: '', audience: '', scope: '', tokenEndpointURL: '', additionalEndpointParams: {}},
+    },
     webServer: {addr: '127.0.0.1', port: 7400, user: '', password: ''},
+    transport: {
+        protocol: 'tcp' as any,
+        tls: {enable: true, certFile: '', keyFile: '', trustedCaFile: '', serverName: ''},
+        poolCount: 0,
+        tcpMux: true,
+        heartbeatInterval: 0,
+        heartbeatTimeout: 0,
+        proxyURL: '',
+        dialServerTimeout: 0,
+    },
     proxies: [],
-    switch: {auth: false, webServer: false},
+    visitors: [],
+    switch: {auth: false, webServer: false, transport: false},
+    restart: {mode: 'never', initialDelayMs: 1000, maxDelayMs: 60000, maxRetries: 0, jitterMs: 0},
 }
\ No newline at end of file
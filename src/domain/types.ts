@@ -12,6 +12,8 @@ export enum AuthType { TOKEN = 'token', OIDC = 'oidc' }
 
 export enum DomainType { SUB = 'sub', CUSTOM = 'custom' }
 
+export enum TransportProtocol { TCP = 'tcp', KCP = 'kcp', QUIC = 'quic', WEBSOCKET = 'websocket', WSS = 'wss' }
+
 export interface Proxy {
     id: string;
     name: string;
@@ -19,6 +21,27 @@ export interface Proxy {
     enable: boolean
     localIP: string
     localPort: number
+    quota?: TrafficQuota
+    rateLimit?: RateLimit
+    // 让 frpc 带 PROXY protocol v2 头，连接表显示真实访问者；只对经 shim 的 TCP 类代理有效
+    proxyProtocol?: boolean
+}
+
+// 按上行加下行计算，0 表示不限；只在桌面端生效
+export interface TrafficQuota {
+    dailyBytes: number
+    monthlyBytes: number
+    warnPercent: number
+    // 1-28
+    resetDay: number
+}
+
+// 字节/秒，0 表示不限；down 为本地服务发往访问者的方向
+export interface RateLimit {
+    upBytesPerSec: number
+    downBytesPerSec: number
+    // 0 时取一秒的量
+    burstBytes: number
 }
 
 export interface HttpSwitch {
@@ -33,4 +56,54 @@ export interface HttpProxy extends Proxy {
     httpUser: string
     httpPassword: string
     switch: HttpSwitch
-}
\ No newline at end of file
+}
+export interface TcpProxy extends Proxy {
+    remotePort: number
+}
+
+export interface UdpProxy extends Proxy {
+    remotePort: number
+}
+
+export interface SecretProxy extends Proxy {
+    secretKey: string
+    allowUsers: string[]
+}
+
+export enum VisitorType { STCP = 'stcp', SUDP = 'sudp', XTCP = 'xtcp' }
+
+export interface Visitor {
+    id: string
+    name: string
+    type: VisitorType
+    enable: boolean
+    serverUser: string
+    serverName: string
+    secretKey: string
+    bindAddr: string
+    bindPort: number
+}
+
+export interface XtcpVisitor extends Visitor {
+    protocol: string
+    keepTunnelOpen: boolean
+    maxRetriesAnHour: number
+    minRetryInterval: number
+    fallbackTo: string
+    fallbackTimeoutMs: number
+}
+
+export interface HttpsPluginOptions {
+    type: 'https2http' | 'https2https'
+    crtPath: string
+    keyPath: string
+    hostHeaderRewrite: string
+    requestHeaders: Record<string, string>
+}
+
+export interface HttpsProxy extends Proxy {
+    subdomain: string
+    customDomains: string[]
+    switch: HttpSwitch
+    plugin: HttpsPluginOptions | null
+}
@@ -1,7 +1,7 @@
 // src/stores/useFrpcStore.ts
 import {defineStore} from 'pinia'
 import {listen, type UnlistenFn} from '@tauri-apps/api/event'
-import {frpcStatus} from '@/api/frpc'
+import {frpcStatus, type FrpcStatus, type RestartEvent} from '@/api/frpc'
 
 export type LogLevel = 'stdout' | 'stderr' | 'system'
 
@@ -17,6 +17,7 @@ const MAX_LINES = 5000
 export const useFrpcStore = defineStore('frpc', {
     state: () => ({
         running: false,
+        status: null as FrpcStatus | null,
         entries: [] as LogEntry[],
         _nextId: 1,
         _listening: false,
@@ -43,11 +44,21 @@ export const useFrpcStore = defineStore('frpc', {
             if (this._listening) return
             const un1 = await listen<string>('frpc://stdout', e => this.pushRaw(e.payload, 'stdout'))
             const un2 = await listen<string>('frpc://stderr', e => this.pushRaw(e.payload, 'stderr'))
-            const un3 = await listen<{ code: number | null }>('frpc://close', e => {
+            const un3 = await listen<{ instance: string, code: number | null }>('frpc://close', async e => {
                 this.pushRaw(`\n[frpc] 退出 code=${e.payload.code ?? 'null'} signal=null`, 'system')
-                this.running = false
+                // 其它实例退出不影响当前 profile 的状态
+                await this.refreshStatus()
             })
-            this._un = [un1, un2, un3]
+            const un4 = await listen<RestartEvent>('frpc://restart', async e => {
+                const p = e.payload
+                const text = p.gaveUp
+                    ? `[frpc] 已达到最大重试次数 ${p.maxRetries}，停止自动重启`
+                    : `[frpc] ${Math.round(p.delayMs / 1000)}s 后自动重启（第 ${p.attempt} 次）`
+                this.pushRaw(text, 'system')
+                await this.refreshStatus()
+            })
+            const un5 = await listen('frpc://started', () => this.refreshStatus())
+            this._un = [un1, un2, un3, un4, un5]
             this._listening = true
         },
         detachListeners() {
@@ -60,8 +71,12 @@ export const useFrpcStore = defineStore('frpc', {
             this._un = []
             this._listening = false
         },
+        async refreshStatus() {
+            this.status = await frpcStatus()
+            this.running = this.status.running
+        },
         async hydrate() {
-            this.running = await frpcStatus()
+            await this.refreshStatus()
             await this.attachListeners()
         },
     },
@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::io::{BufRead, BufReader};
 use std::thread;
 use std::time::Duration;
@@ -74,18 +75,25 @@ fn kill_tree_win(root_pid: u32) {
     }
 }
 
+fn kill_target(pid: i64) {
+    #[cfg(unix)]
+    {
+        kill_pid(pid as i32);
+    }
+    #[cfg(windows)]
+    {
+        kill_tree_win(pid as u32);
+    }
+}
+
 /// 作为独立进程运行；只通过 stdin 接受主进程发来的“目标”命令：
-/// SET PG <pid>    （Unix: 记住进程组）
-/// SET PID <pid>   （Windows 或 Unix 备用：按 PID 杀）
+/// ADD PID <pid>   （追加一个目标，支持多个 frpc 实例同时运行）
+/// DEL PID <pid>   （移除一个目标）
+/// SET PID <pid>   （兼容旧协议：清空后只保留该目标）
 /// CLEAR           （清空目标）
-/// EOF             （主进程死亡）→ 根据最后一次 SET 执行清理 → 退出
+/// EOF             （主进程死亡）→ 杀掉所有仍在记录中的目标 → 退出
 pub fn run() -> ! {
-    #[derive(Clone, Copy)]
-    enum Target {
-        None,
-        Pid(i64),
-    }
-    let mut tgt = Target::None;
+    let mut targets: HashSet<i64> = HashSet::new();
 
     let mut reader = BufReader::new(std::io::stdin());
     let mut line = String::new();
@@ -93,18 +101,8 @@ pub fn run() -> ! {
     while let Ok(n) = reader.read_line(&mut line) {
         if n == 0 {
             // EOF：父进程已死亡，执行清理
-            match tgt {
-                Target::None => {}
-                Target::Pid(x) => {
-                    #[cfg(unix)]
-                    {
-                        kill_pid(x as i32);
-                    }
-                    #[cfg(windows)]
-                    {
-                        kill_tree_win(x as u32);
-                    }
-                }
+            for &pid in targets.iter() {
+                kill_target(pid);
             }
             std::process::exit(0);
         }
@@ -116,13 +114,24 @@ pub fn run() -> ! {
 
         let mut it = cmd.split_whitespace();
         match (it.next(), it.next(), it.next()) {
+            (Some("ADD"), Some("PID"), Some(pid)) => {
+                if let Ok(v) = pid.parse::<i64>() {
+                    targets.insert(v);
+                }
+            }
+            (Some("DEL"), Some("PID"), Some(pid)) => {
+                if let Ok(v) = pid.parse::<i64>() {
+                    targets.remove(&v);
+                }
+            }
             (Some("SET"), Some("PID"), Some(pid)) => {
                 if let Ok(v) = pid.parse::<i64>() {
-                    tgt = Target::Pid(v);
+                    targets.clear();
+                    targets.insert(v);
                 }
             }
             (Some("CLEAR"), _, _) => {
-                tgt = Target::None;
+                targets.clear();
             }
             _ => {}
         }